@@ -134,6 +134,11 @@ impl GerbApp {
         application.set_accels_for_action("app.about", &["question", "F1"]);
         application.set_accels_for_action("app.undo", &["<Primary>Z"]);
         application.set_accels_for_action("app.redo", &["<Primary>R"]);
+        application.set_accels_for_action("app.new", &["<Primary>N"]);
+        application.set_accels_for_action("app.save", &["<Primary>S"]);
+        application.set_accels_for_action("app.save-as", &["<Primary><Shift>S"]);
+        application.set_accels_for_action("app.kerning", &["<Primary>K"]);
+        let main_window = self.imp().window.get().unwrap().clone();
         let window = self.imp().window.get().unwrap().upcast_ref::<gtk::Window>();
         let quit = gtk::gio::SimpleAction::new("quit", None);
         quit.connect_activate(glib::clone!(@weak window => move |_, _| {
@@ -222,6 +227,131 @@ impl GerbApp {
             }
             dialog.hide();
         }));
+        let new = gtk::gio::SimpleAction::new("new", None);
+        new.connect_activate(
+            glib::clone!(@weak window, @weak main_window => move |_, _| {
+                if let Some((template, path)) = crate::window::new_project_dialog(&window) {
+                    let project = crate::project::Project::from_template(&template);
+                    if let Err(err) = project.save_as(&path) {
+                        error_dialog(&window, "Error: Could not create project", &err.to_string());
+                    } else {
+                        main_window.imp().load_project(project);
+                        window.show_all();
+                    }
+                }
+            }),
+        );
+        let save_as = gtk::gio::SimpleAction::new("save-as", None);
+        save_as.connect_activate(glib::clone!(@weak window, @weak main_window => move |_, _| {
+            let dialog = gtk::FileChooserNative::new(
+                Some("Save font.ufo directory as..."),
+                Some(&window),
+                gtk::FileChooserAction::Save,
+                None,
+                None
+            );
+            dialog.set_do_overwrite_confirmation(true);
+            let project = main_window.imp().project.borrow().clone();
+            if let Some(name) = project.imp().path.borrow().as_ref().and_then(|p| p.file_name()) {
+                dialog.set_current_name(&name.to_string_lossy());
+            } else {
+                dialog.set_current_name("Untitled.ufo");
+            }
+            dialog.run();
+            if let Some(f) = dialog.filename() {
+                if let Err(err) = project.save_as(&f) {
+                    error_dialog(&window, "Error: Could not save project", &err.to_string());
+                }
+            }
+            dialog.hide();
+        }));
+        type ExportFn = fn(&crate::project::Project) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+        for (name, title, extension, export) in [
+            (
+                "export-ttf",
+                "Export TrueType font as...",
+                "ttf",
+                crate::export::export_ttf as ExportFn,
+            ),
+            (
+                "export-otf",
+                "Export OpenType CFF font as...",
+                "otf",
+                crate::export::export_otf as ExportFn,
+            ),
+        ] {
+            let action = gtk::gio::SimpleAction::new(name, None);
+            action.connect_activate(glib::clone!(@weak window, @weak main_window => move |_, _| {
+                let dialog = gtk::FileChooserNative::new(
+                    Some(title),
+                    Some(&window),
+                    gtk::FileChooserAction::Save,
+                    None,
+                    None
+                );
+                dialog.set_do_overwrite_confirmation(true);
+                let project = main_window.imp().project.borrow().clone();
+                dialog.set_current_name(&format!("{}.{}", crate::export::postscript_font_name(&project.fontinfo()), extension));
+                dialog.run();
+                if let Some(f) = dialog.filename() {
+                    if let Err(err) = export(&project).and_then(|data| Ok(std::fs::write(&f, data)?)) {
+                        error_dialog(&window, "Error: Could not export font", &err.to_string());
+                    }
+                }
+                dialog.hide();
+            }));
+            application.add_action(&action);
+        }
+        let export_svg = gtk::gio::SimpleAction::new("export-svg", None);
+        export_svg.connect_activate(
+            glib::clone!(@weak window, @weak main_window => move |_, _| {
+                let project = main_window.imp().project.borrow().clone();
+                crate::views::export_svg_dialog(Some(&window), &project, None);
+            }),
+        );
+        #[cfg(feature = "pdf")]
+        {
+            let print_proof = gtk::gio::SimpleAction::new("print-proof", None);
+            print_proof.connect_activate(glib::clone!(@weak window, @weak main_window => move |_, _| {
+                let options = match crate::window::proof_dialog(&window) {
+                    Some(o) => o,
+                    None => return,
+                };
+                let dialog = gtk::FileChooserNative::new(
+                    Some("Save proof as..."),
+                    Some(&window),
+                    gtk::FileChooserAction::Save,
+                    None,
+                    None
+                );
+                dialog.set_do_overwrite_confirmation(true);
+                let project = main_window.imp().project.borrow().clone();
+                dialog.set_current_name(&format!("{}-proof.pdf", crate::export::postscript_font_name(&project.fontinfo())));
+                dialog.run();
+                if let Some(f) = dialog.filename() {
+                    if let Err(err) = crate::export::render_proof(&project, &f, &options) {
+                        error_dialog(&window, "Error: Could not print proof", &err.to_string());
+                    }
+                }
+                dialog.hide();
+            }));
+            application.add_action(&print_proof);
+        }
+        let save = gtk::gio::SimpleAction::new("save", None);
+        save.connect_activate(
+            glib::clone!(@weak window, @weak main_window, @weak save_as => move |_, _| {
+                let project = main_window.imp().project.borrow().clone();
+                if project.imp().path.borrow().is_none() {
+                    save_as.activate(None);
+                } else if let Err(err) = project.save() {
+                    error_dialog(&window, "Error: Could not save project", &err.to_string());
+                }
+            }),
+        );
+        let kerning = gtk::gio::SimpleAction::new("kerning", None);
+        kerning.connect_activate(glib::clone!(@weak main_window => move |_, _| {
+            main_window.imp().edit_kerning();
+        }));
         let undo = gtk::gio::SimpleAction::new("undo", None);
         undo.set_enabled(false);
         undo.connect_activate(glib::clone!(@weak self as _self => move |_, _| {
@@ -241,7 +371,12 @@ impl GerbApp {
 
         application.add_action(&settings);
         application.add_action(&about);
+        application.add_action(&new);
         application.add_action(&open);
+        application.add_action(&save);
+        application.add_action(&save_as);
+        application.add_action(&export_svg);
+        application.add_action(&kerning);
         application.add_action(&undo);
         application.add_action(&redo);
         application.add_action(&quit);
@@ -259,7 +394,15 @@ impl GerbApp {
         // makes more sense when you'll be reading the "add_actions" function.
 
         file_menu.append(Some("File"), Some("app.file"));
+        file_menu.append(Some("New project"), Some("app.new"));
         file_menu.append(Some("Open"), Some("app.open"));
+        file_menu.append(Some("Save"), Some("app.save"));
+        file_menu.append(Some("Save as..."), Some("app.save-as"));
+        file_menu.append(Some("Export TTF..."), Some("app.export-ttf"));
+        file_menu.append(Some("Export OTF..."), Some("app.export-otf"));
+        file_menu.append(Some("Export SVG..."), Some("app.export-svg"));
+        #[cfg(feature = "pdf")]
+        file_menu.append(Some("Print proof..."), Some("app.print-proof"));
         file_menu.append(Some("Quit"), Some("app.quit"));
         menu_bar.append_submenu(Some("_File"), &file_menu);
 
@@ -267,6 +410,7 @@ impl GerbApp {
         settings_menu.append(None, None);
         settings_menu.append(Some("Undo"), Some("app.undo"));
         settings_menu.append(Some("Redo"), Some("app.redo"));
+        settings_menu.append(Some("Kerning"), Some("app.kerning"));
         settings_menu.append(Some("Sub another"), Some("app.sub_another"));
         submenu.append(Some("Sub sub another"), Some("app.sub_sub_another"));
         submenu.append(Some("Sub sub another2"), Some("app.sub_sub_another2"));
@@ -290,3 +434,16 @@ impl GerbApp {
         window.imp().widgets.get().unwrap().sidebar.tabinfo.clone()
     }
 }
+
+fn error_dialog(window: &gtk::Window, title: &str, msg: &str) {
+    let dialog = gtk::MessageDialog::new(
+        Some(window),
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        gtk::MessageType::Error,
+        gtk::ButtonsType::Close,
+        msg,
+    );
+    dialog.set_title(title);
+    dialog.run();
+    dialog.hide();
+}
@@ -0,0 +1,367 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Headless subcommands that run without initializing GTK, for batch use.
+
+use gtk::subclass::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::project::Project;
+
+pub const USAGE: &str = "Usage: gerb [-u /path/to/font.ufo]
+       gerb check FONT.ufo
+       gerb info FONT.ufo
+       gerb export FONT.ufo [--svg DIR] [--ttf FILE] [--otf FILE] [--pdf FILE]
+
+Commands:
+  check    Load the font and report problems. Exits with status 1 if there are any.
+  info     Print font metadata and statistics.
+  export   Write every glyph as an SVG file into DIR along with an SVG font of all
+           glyphs, compile the font to a TrueType or CFF OpenType file, and/or print
+           a proof of the glyphs to a PDF file.";
+
+/// Exit status for invalid arguments.
+const EXIT_USAGE: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Check(String),
+    Info(String),
+    Export {
+        ufo: String,
+        svg: Option<PathBuf>,
+        ttf: Option<PathBuf>,
+        otf: Option<PathBuf>,
+        pdf: Option<PathBuf>,
+    },
+    Help,
+}
+
+impl Command {
+    /// Parse the command line arguments, without the program name. Returns `Ok(None)` if they
+    /// are GUI options or a single path to open, in which case the GUI is started.
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let (command, rest) = match args.split_first() {
+            Some((command, rest)) => (command.as_str(), rest),
+            None => return Ok(None),
+        };
+        let ufo = || match rest.first() {
+            Some(path) if !path.starts_with('-') => Ok(path.clone()),
+            _ => Err(format!("{}: missing FONT.ufo argument", command)),
+        };
+        match command {
+            "check" if rest.len() <= 1 => Ok(Some(Command::Check(ufo()?))),
+            "info" if rest.len() <= 1 => Ok(Some(Command::Info(ufo()?))),
+            "check" | "info" => Err(format!("{}: unexpected argument {}", command, rest[1])),
+            "export" => {
+                let ufo = ufo()?;
+                let (mut svg, mut ttf, mut otf, mut pdf) = (None, None, None, None);
+                let mut options = rest[1..].iter();
+                while let Some(option) = options.next() {
+                    let target = match option.as_str() {
+                        "--svg" => &mut svg,
+                        "--ttf" => &mut ttf,
+                        "--otf" => &mut otf,
+                        "--pdf" => &mut pdf,
+                        other => return Err(format!("export: unknown option {}", other)),
+                    };
+                    match options.next() {
+                        Some(path) => *target = Some(PathBuf::from(path)),
+                        None => return Err(format!("export: {} requires a path", option)),
+                    }
+                }
+                if svg.is_none() && ttf.is_none() && otf.is_none() && pdf.is_none() {
+                    return Err(
+                        "export: nothing to do, use --svg, --ttf, --otf or --pdf".to_string()
+                    );
+                }
+                Ok(Some(Command::Export {
+                    ufo,
+                    svg,
+                    ttf,
+                    otf,
+                    pdf,
+                }))
+            }
+            "help" | "--help" | "-h" => Ok(Some(Command::Help)),
+            _ if command.starts_with('-') || rest.is_empty() => Ok(None),
+            _ => Err(format!("unknown command {}", command)),
+        }
+    }
+
+    /// Run the command, printing its output. Returns the exit status.
+    pub fn run(&self) -> i32 {
+        let load = |path: &str| {
+            Project::from_path(path).map_err(|err| {
+                eprintln!("{}", err);
+            })
+        };
+        match self {
+            Command::Help => {
+                println!("{}", USAGE);
+                0
+            }
+            Command::Check(path) => {
+                let project = match load(path) {
+                    Ok(p) => p,
+                    Err(()) => return 1,
+                };
+                let problems = check(&project);
+                for p in &problems {
+                    println!("{}: {}", path, p);
+                }
+                i32::from(!problems.is_empty())
+            }
+            Command::Info(path) => match load(path) {
+                Ok(project) => {
+                    print!("{}", info(&project));
+                    0
+                }
+                Err(()) => 1,
+            },
+            Command::Export {
+                ufo,
+                svg,
+                ttf,
+                otf,
+                pdf,
+            } => {
+                let project = match load(ufo) {
+                    Ok(p) => p,
+                    Err(()) => return 1,
+                };
+                let mut status = 0;
+                let mut report = |result: Result<String, Box<dyn std::error::Error>>| match result {
+                    Ok(msg) => println!("{}", msg),
+                    Err(err) => {
+                        eprintln!("{}: {}", ufo, err);
+                        status = 1;
+                    }
+                };
+                if let Some(dir) = svg {
+                    report(export_svg(&project, dir));
+                }
+                for (path, export) in [
+                    (ttf, crate::export::export_ttf as ExportFn),
+                    (otf, crate::export::export_otf as ExportFn),
+                ] {
+                    if let Some(path) = path {
+                        report(export(&project).and_then(|data| {
+                            std::fs::write(path, data)?;
+                            Ok(format!("Wrote {}", path.display()))
+                        }));
+                    }
+                }
+                if let Some(path) = pdf {
+                    report(print_proof(&project, path));
+                }
+                status
+            }
+        }
+    }
+}
+
+type ExportFn = fn(&Project) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+
+/// Run a subcommand if `args` (without the program name) start with one. Returns the exit
+/// status, or `None` if the GUI should be started instead.
+pub fn run(args: &[String]) -> Option<i32> {
+    match Command::parse(args) {
+        Ok(command) => command.map(|c| c.run()),
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            Some(EXIT_USAGE)
+        }
+    }
+}
+
+/// Problems found when loading `project`, followed by problems in its glyphs: components whose
+/// base glyph doesn't exist, open contours and code points mapped to more than one glyph.
+pub fn check(project: &Project) -> Vec<String> {
+    let mut ret = project
+        .imp()
+        .load_report
+        .borrow()
+        .warnings
+        .iter()
+        .map(|w| w.to_string())
+        .collect::<Vec<String>>();
+    let glyphs = project.imp().glyphs.borrow();
+    let mut names = glyphs.keys().collect::<Vec<&String>>();
+    names.sort();
+    let mut code_points: BTreeMap<char, Vec<&str>> = BTreeMap::new();
+    for name in names {
+        let glyph = glyphs[name].borrow();
+        for c in &glyph.components {
+            if !glyphs.contains_key(c.base_name()) {
+                ret.push(format!(
+                    "Glyph {:?} has a component of glyph {:?} which does not exist.",
+                    name,
+                    c.base_name()
+                ));
+            }
+        }
+        let open = glyph.contours.iter().filter(|c| *c.open().borrow()).count();
+        if open > 0 {
+            ret.push(format!(
+                "Glyph {:?} has {} open contour{}.",
+                name,
+                open,
+                if open == 1 { "" } else { "s" }
+            ));
+        }
+        for c in &glyph.unicode {
+            code_points.entry(*c).or_default().push(name);
+        }
+    }
+    for (c, names) in code_points.into_iter().filter(|(_, n)| n.len() > 1) {
+        ret.push(format!(
+            "Code point U+{:04X} is mapped to more than one glyph: {}.",
+            c as u32,
+            names
+                .iter()
+                .map(|n| format!("{:?}", n))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+    ret
+}
+
+pub fn info(project: &Project) -> String {
+    let mut ret = String::new();
+    let info = project.fontinfo();
+    let imp = info.imp();
+    let glyphs = project.imp().glyphs.borrow();
+    let _ = writeln!(ret, "Family: {}", info.family_name());
+    let _ = writeln!(ret, "Style: {}", info.style_name());
+    let _ = writeln!(
+        ret,
+        "Version: {}.{:03}",
+        imp.version_major.borrow().unwrap_or(0),
+        imp.version_minor.borrow().unwrap_or(0)
+    );
+    let _ = writeln!(ret, "Units per em: {}", info.units_per_em());
+    let _ = writeln!(ret, "Ascender: {}", info.ascender());
+    let _ = writeln!(ret, "Descender: {}", info.descender());
+    let _ = writeln!(ret, "x-height: {}", info.x_height());
+    let _ = writeln!(ret, "Cap height: {}", info.cap_height());
+    let _ = writeln!(ret, "Italic angle: {}", info.italic_angle());
+    let _ = writeln!(
+        ret,
+        "Glyphs: {} ({} with code points, {} empty)",
+        glyphs.len(),
+        glyphs
+            .values()
+            .filter(|g| !g.borrow().unicode.is_empty())
+            .count(),
+        glyphs.values().filter(|g| g.borrow().is_empty()).count()
+    );
+    let _ = writeln!(ret, "Layers: {}", project.layer_names().join(", "));
+    let kerning = project.imp().kerning.borrow();
+    let _ = writeln!(
+        ret,
+        "Kerning pairs: {}",
+        kerning.pairs.values().map(|p| p.len()).sum::<usize>()
+    );
+    let _ = writeln!(
+        ret,
+        "Groups: {}",
+        project.imp().groups.borrow().groups.len()
+    );
+    ret
+}
+
+/// Write every glyph of `project` to an SVG file in `dir`, along with an SVG font.
+fn export_svg(
+    project: &Project,
+    dir: &std::path::Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let count = crate::export::export_svg(project, dir, None)?;
+    Ok(format!(
+        "Wrote {} glyphs and {}.svg to {}",
+        count,
+        crate::export::postscript_font_name(&project.fontinfo()),
+        dir.display()
+    ))
+}
+
+/// Print a proof with the default options to the PDF file `path`.
+#[cfg(feature = "pdf")]
+fn print_proof(
+    project: &Project,
+    path: &std::path::Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let pages =
+        crate::export::render_proof(project, path, &crate::export::ProofOptions::default())?;
+    Ok(format!("Wrote {} ({} pages)", path.display(), pages))
+}
+
+#[cfg(not(feature = "pdf"))]
+fn print_proof(_: &Project, _: &std::path::Path) -> Result<String, Box<dyn std::error::Error>> {
+    Err("gerb was built without PDF support.".into())
+}
+
+#[test]
+fn test_cli() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    assert_eq!(Command::parse(&[]), Ok(None));
+    assert_eq!(Command::parse(&args("-u font.ufo")), Ok(None));
+    assert_eq!(
+        Command::parse(&args("check font.ufo")),
+        Ok(Some(Command::Check("font.ufo".to_string())))
+    );
+    assert!(Command::parse(&args("info")).is_err());
+    assert!(Command::parse(&args("info a.ufo b.ufo")).is_err());
+    assert_eq!(
+        Command::parse(&args("export font.ufo --svg out --otf a.otf")),
+        Ok(Some(Command::Export {
+            ufo: "font.ufo".to_string(),
+            svg: Some("out".into()),
+            ttf: None,
+            otf: Some("a.otf".into()),
+            pdf: None,
+        }))
+    );
+    assert!(Command::parse(&args("export font.ufo")).is_err());
+    assert!(Command::parse(&args("export font.ufo --svg")).is_err());
+    assert!(Command::parse(&args("export font.ufo --png out")).is_err());
+    assert_eq!(Command::parse(&args("font.ufo")), Ok(None));
+    assert!(Command::parse(&args("chek font.ufo")).is_err());
+
+    let project = Project::from_template(&crate::project::ProjectTemplate {
+        charset: vec!['A', 'B'],
+        ..Default::default()
+    });
+    let b = project.imp().glyphs.borrow()["B"].clone();
+    b.borrow_mut().unicode.push('A');
+    let problems = check(&project);
+    assert_eq!(problems.len(), 1, "{:?}", problems);
+    assert_eq!(
+        problems[0],
+        "Code point U+0041 is mapped to more than one glyph: \"A\", \"B\"."
+    );
+    let output = info(&project);
+    assert!(output.contains("Family: Untitled\n"));
+    assert!(output.contains("Glyphs: 2 (2 with code points, 2 empty)\n"));
+}
@@ -0,0 +1,154 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Compile a [`Project`] to binary OpenType font files, write its glyphs as SVG or print them
+//! to a PDF proof.
+//!
+//! Glyphs are first collected into [`ExportGlyph`]s, which only hold plain data, and the font
+//! tables are then built from those and the project's [`FontInfo`]. TrueType (`.ttf`) and CFF
+//! (`.otf`) fonts only differ in their outline tables; the rest are built by the same code.
+
+use glib::subclass::prelude::*;
+use std::collections::HashMap;
+
+use crate::glyphs::{Glyph, MAX_COMPONENT_DEPTH};
+use crate::project::{FontInfo, Project};
+
+mod cff;
+pub use cff::*;
+mod outline;
+pub use outline::*;
+#[cfg(feature = "pdf")]
+mod proof;
+#[cfg(feature = "pdf")]
+pub use proof::*;
+mod sfnt;
+pub use sfnt::postscript_font_name;
+mod svg;
+pub use svg::*;
+mod ttf;
+pub use ttf::*;
+
+/// A glyph as it will be written to a font file.
+#[derive(Debug, Clone, Default)]
+pub struct ExportGlyph {
+    pub name: String,
+    pub unicode: Vec<char>,
+    pub advance: f64,
+    /// The glyph's own contours, without its components.
+    pub outline: Outline,
+    /// Base glyph name and transformation of each component.
+    pub components: Vec<(String, Transform)>,
+}
+
+impl ExportGlyph {
+    pub fn from_glyph(glyph: &Glyph) -> Self {
+        let mut unicode = glyph.unicode.clone();
+        if let (true, crate::glyphs::GlyphKind::Char(c)) = (unicode.is_empty(), &glyph.kind) {
+            unicode.push(*c);
+        }
+        ExportGlyph {
+            name: glyph.name.to_string(),
+            unicode,
+            advance: glyph.width.unwrap_or(0.),
+            outline: Outline::from_contours(&glyph.contours),
+            components: glyph
+                .components
+                .iter()
+                .map(|c| (c.base_name().to_string(), c.transform()))
+                .collect(),
+        }
+    }
+
+    /// The outline with every component recursively decomposed. Components whose base glyph
+    /// doesn't exist are skipped, and cyclic references stop at [`MAX_COMPONENT_DEPTH`].
+    pub fn decomposed(&self, glyphs: &HashMap<&str, &ExportGlyph>) -> Outline {
+        fn append(
+            ret: &mut Outline,
+            glyph: &ExportGlyph,
+            glyphs: &HashMap<&str, &ExportGlyph>,
+            transform: Transform,
+            depth: usize,
+        ) {
+            ret.append_transformed(&glyph.outline, transform);
+            if depth >= MAX_COMPONENT_DEPTH {
+                return;
+            }
+            for (base, t) in &glyph.components {
+                if let Some(base) = glyphs.get(base.as_str()) {
+                    append(ret, base, glyphs, compose(transform, *t), depth + 1);
+                }
+            }
+        }
+        let mut ret = Outline::default();
+        append(&mut ret, self, glyphs, IDENTITY, 0);
+        ret
+    }
+
+    /// A `.notdef` glyph drawn as a rectangle, for fonts that don't have one.
+    pub fn notdef(info: &FontInfo) -> Self {
+        let upm = info.units_per_em();
+        let advance = (upm / 2.).round();
+        let (x0, x1, y0, y1) = (upm * 0.05, advance - upm * 0.05, 0., info.cap_height());
+        let stroke = upm * 0.05;
+        let rect = |x0: f64, y0: f64, x1: f64, y1: f64, clockwise: bool| {
+            let mut points = vec![(x0, y1), (x1, y1), (x1, y0)];
+            if !clockwise {
+                points.reverse();
+            }
+            PathContour {
+                start: (x0, y0),
+                segments: points.into_iter().map(Segment::Line).collect(),
+            }
+        };
+        ExportGlyph {
+            name: ".notdef".to_string(),
+            unicode: vec![],
+            advance,
+            outline: Outline {
+                contours: vec![
+                    rect(x0, y0, x1, y1, false),
+                    rect(x0 + stroke, y0 + stroke, x1 - stroke, y1 - stroke, true),
+                ],
+            },
+            components: vec![],
+        }
+    }
+}
+
+/// The project's glyphs in font order: `.notdef` first, then in the project's display order.
+/// A `.notdef` glyph is created if the project doesn't have one.
+pub fn export_glyphs(project: &Project) -> Vec<ExportGlyph> {
+    let glyphs = project.imp().glyphs.borrow();
+    let mut sorted = glyphs.values().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| project.cmp_glyphs(&a.borrow(), &b.borrow()));
+    let mut ret = sorted
+        .into_iter()
+        .map(|g| ExportGlyph::from_glyph(&g.borrow()))
+        .collect::<Vec<ExportGlyph>>();
+    if let Some(i) = ret.iter().position(|g| g.name == ".notdef") {
+        let notdef = ret.remove(i);
+        ret.insert(0, notdef);
+    } else {
+        ret.insert(0, ExportGlyph::notdef(&project.fontinfo()));
+    }
+    ret
+}
@@ -0,0 +1,851 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! PostScript outlines: the `CFF ` table with Type 2 charstrings.
+//!
+//! See <https://adobe-type-tools.github.io/font-tech-notes/pdfs/5176.CFF.pdf> and
+//! <https://adobe-type-tools.github.io/font-tech-notes/pdfs/5177.Type2.pdf>.
+
+use std::collections::HashMap;
+
+use super::sfnt::{self, to_i16, to_u16, FontMetrics, GlyphMetrics, Writer};
+use super::*;
+
+/// Number of the first string in the String INDEX; lower numbers are standard strings.
+const FIRST_CUSTOM_SID: u16 = 391;
+
+/// Maximum number of arguments on the Type 2 charstring stack.
+const MAX_STACK: usize = 48;
+
+mod op {
+    pub const RMOVETO: u8 = 21;
+    pub const RLINETO: u8 = 5;
+    pub const RRCURVETO: u8 = 8;
+    pub const ENDCHAR: u8 = 14;
+    pub const CALLGSUBR: u8 = 29;
+    pub const RETURN: u8 = 11;
+}
+
+/// Top and Private DICT operators. Two byte operators are escaped with 12.
+mod dict_op {
+    pub const VERSION: &[u8] = &[0];
+    pub const NOTICE: &[u8] = &[1];
+    pub const COPYRIGHT: &[u8] = &[12, 0];
+    pub const FULL_NAME: &[u8] = &[2];
+    pub const FAMILY_NAME: &[u8] = &[3];
+    pub const WEIGHT: &[u8] = &[4];
+    pub const IS_FIXED_PITCH: &[u8] = &[12, 1];
+    pub const ITALIC_ANGLE: &[u8] = &[12, 2];
+    pub const UNDERLINE_POSITION: &[u8] = &[12, 3];
+    pub const UNDERLINE_THICKNESS: &[u8] = &[12, 4];
+    pub const UNIQUE_ID: &[u8] = &[13];
+    pub const FONT_BBOX: &[u8] = &[5];
+    pub const FONT_MATRIX: &[u8] = &[12, 7];
+    pub const CHARSET: &[u8] = &[15];
+    pub const CHAR_STRINGS: &[u8] = &[17];
+    pub const PRIVATE: &[u8] = &[18];
+
+    pub const BLUE_VALUES: &[u8] = &[6];
+    pub const OTHER_BLUES: &[u8] = &[7];
+    pub const FAMILY_BLUES: &[u8] = &[8];
+    pub const FAMILY_OTHER_BLUES: &[u8] = &[9];
+    pub const BLUE_SCALE: &[u8] = &[12, 9];
+    pub const BLUE_SHIFT: &[u8] = &[12, 10];
+    pub const BLUE_FUZZ: &[u8] = &[12, 11];
+    pub const STD_HW: &[u8] = &[10];
+    pub const STD_VW: &[u8] = &[11];
+    pub const STEM_SNAP_H: &[u8] = &[12, 12];
+    pub const STEM_SNAP_V: &[u8] = &[12, 13];
+    pub const FORCE_BOLD: &[u8] = &[12, 14];
+    pub const DEFAULT_WIDTH_X: &[u8] = &[20];
+    pub const NOMINAL_WIDTH_X: &[u8] = &[21];
+}
+
+/// Encode an integer operand, shared by DICTs and charstrings except for the five byte form.
+fn encode_int(w: &mut Writer, v: i32) {
+    match v {
+        -107..=107 => {
+            w.u8((v + 139) as u8);
+        }
+        108..=1131 => {
+            let v = v - 108;
+            w.u8((v / 256 + 247) as u8).u8((v % 256) as u8);
+        }
+        -1131..=-108 => {
+            let v = -v - 108;
+            w.u8((v / 256 + 251) as u8).u8((v % 256) as u8);
+        }
+        _ if i16::try_from(v).is_ok() => {
+            w.u8(28).i16(v as i16);
+        }
+        _ => {
+            w.u8(29).bytes(&v.to_be_bytes());
+        }
+    }
+}
+
+/// Encode a DICT number, as an integer if it has no fractional part.
+fn encode_dict_number(w: &mut Writer, v: f64) {
+    if v.fract() == 0. && v.abs() < i32::MAX as f64 {
+        encode_int(w, v as i32);
+        return;
+    }
+    /* Real numbers are written as a sequence of nibbles. */
+    let mut nibbles = vec![];
+    for c in format!("{}", v).chars() {
+        nibbles.push(match c {
+            '0'..='9' => c as u8 - b'0',
+            '.' => 0xa,
+            '-' => 0xe,
+            _ => continue,
+        });
+    }
+    nibbles.push(0xf);
+    if nibbles.len() % 2 == 1 {
+        nibbles.push(0xf);
+    }
+    w.u8(30);
+    for pair in nibbles.chunks(2) {
+        w.u8(pair[0] << 4 | pair[1]);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Dict(Writer);
+
+impl Dict {
+    fn entry(&mut self, operator: &[u8], operands: &[f64]) -> &mut Self {
+        for &v in operands {
+            encode_dict_number(&mut self.0, v);
+        }
+        self.0.bytes(operator);
+        self
+    }
+
+    /// An offset, always written in the five byte form so that the DICT's size doesn't depend on
+    /// its value.
+    fn offset_entry(&mut self, operator: &[u8], operands: &[usize]) -> &mut Self {
+        for &v in operands {
+            self.0.u8(29).u32(v as u32);
+        }
+        self.0.bytes(operator);
+        self
+    }
+
+    /// A delta encoded array, as used by the blue zones and stem snaps.
+    fn delta_entry(&mut self, operator: &[u8], values: &[f64]) -> &mut Self {
+        if values.is_empty() {
+            return self;
+        }
+        let mut prev = 0.;
+        let deltas = values
+            .iter()
+            .map(|&v| {
+                let d = v - prev;
+                prev = v;
+                d
+            })
+            .collect::<Vec<f64>>();
+        self.entry(operator, &deltas)
+    }
+}
+
+fn index<T: AsRef<[u8]>>(items: &[T]) -> Vec<u8> {
+    let mut w = Writer::default();
+    w.u16(items.len() as u16);
+    if items.is_empty() {
+        return w.0;
+    }
+    let mut offsets = vec![1_usize];
+    for item in items {
+        offsets.push(offsets.last().unwrap() + item.as_ref().len());
+    }
+    let last = *offsets.last().unwrap();
+    let off_size: u8 = match last {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x10000..=0xFF_FFFF => 3,
+        _ => 4,
+    };
+    w.u8(off_size);
+    for offset in offsets {
+        w.bytes(&(offset as u32).to_be_bytes()[4 - off_size as usize..]);
+    }
+    for item in items {
+        w.bytes(item.as_ref());
+    }
+    w.0
+}
+
+/// A charstring operator along with its operands. Charstrings are built out of tokens so that
+/// repeated tokens can be moved to subroutines.
+type Token = Vec<u8>;
+
+/// Charstring of a glyph: the advance width operand, if any, is part of the first token.
+fn charstring(outline: &Outline, width: Option<i32>) -> Vec<Token> {
+    let mut ret = vec![];
+    let mut width = width;
+    let mut current = (0_i32, 0_i32);
+    let mut args: Vec<i32> = vec![];
+    let mut pending_op = None;
+    let flush = |ret: &mut Vec<Token>, args: &mut Vec<i32>, op: u8, width: &mut Option<i32>| {
+        let mut w = Writer::default();
+        if let Some(width) = width.take() {
+            encode_int(&mut w, width);
+        }
+        for &a in args.iter() {
+            encode_int(&mut w, a);
+        }
+        w.u8(op);
+        ret.push(w.0);
+        args.clear();
+    };
+    let round = |(x, y): Point| (to_i16(x) as i32, to_i16(y) as i32);
+    for contour in &outline.contours {
+        if let Some(op) = pending_op.take() {
+            flush(&mut ret, &mut args, op, &mut width);
+        }
+        let start = round(contour.start);
+        args.extend([start.0 - current.0, start.1 - current.1]);
+        flush(&mut ret, &mut args, op::RMOVETO, &mut width);
+        current = start;
+        let mut prev = contour.start;
+        for segment in &contour.segments {
+            let (op, points) = match *segment {
+                Segment::Line(p) => (op::RLINETO, vec![p]),
+                /* Quadratic curves are raised to cubic ones, which is exact. */
+                Segment::Quad(c, p) => (
+                    op::RRCURVETO,
+                    vec![
+                        (
+                            prev.0 + 2. / 3. * (c.0 - prev.0),
+                            prev.1 + 2. / 3. * (c.1 - prev.1),
+                        ),
+                        (p.0 + 2. / 3. * (c.0 - p.0), p.1 + 2. / 3. * (c.1 - p.1)),
+                        p,
+                    ],
+                ),
+                Segment::Cubic(c1, c2, p) => (op::RRCURVETO, vec![c1, c2, p]),
+            };
+            prev = segment.end();
+            if pending_op.map(|o| o != op).unwrap_or(false)
+                || args.len() + 2 * points.len() > MAX_STACK - 1
+            {
+                flush(&mut ret, &mut args, pending_op.unwrap(), &mut width);
+            }
+            for p in points {
+                let p = round(p);
+                args.extend([p.0 - current.0, p.1 - current.1]);
+                current = p;
+            }
+            pending_op = Some(op);
+        }
+    }
+    if let Some(op) = pending_op.take() {
+        flush(&mut ret, &mut args, op, &mut width);
+    }
+    flush(&mut ret, &mut args, op::ENDCHAR, &mut width);
+    ret
+}
+
+/// The subroutine number operand for subroutine `i`, out of `count` subroutines.
+fn subr_bias(count: usize) -> i32 {
+    match count {
+        0..=1239 => 107,
+        1240..=33899 => 1131,
+        _ => 32768,
+    }
+}
+
+/// Move token sequences that repeat across charstrings to global subroutines. Returns the new
+/// charstrings and the subroutines.
+///
+/// Every sequence of up to [`MAX_SUBR_TOKENS`] tokens that occurs more than once is a candidate;
+/// candidates are tried in order of estimated savings and kept if replacing their remaining
+/// occurrences still saves space. The first token of each charstring (which may hold the advance
+/// width) and `endchar` are never moved.
+fn subroutinize(charstrings: Vec<Vec<Token>>) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    const MAX_SUBR_TOKENS: usize = 16;
+    const MAX_SUBRS: usize = 65535;
+    /* Operand and operator of a call, and the INDEX offset and `return` of a subroutine. */
+    const CALL_COST: usize = 3;
+    const SUBR_OVERHEAD: usize = 4;
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Item {
+        Token(usize),
+        Fixed(usize),
+        Call(usize),
+    }
+
+    let mut tokens: Vec<Token> = vec![];
+    let mut token_ids: HashMap<Token, usize> = HashMap::new();
+    let mut glyphs: Vec<Vec<Item>> = charstrings
+        .into_iter()
+        .map(|cs| {
+            let last = cs.len() - 1;
+            cs.into_iter()
+                .enumerate()
+                .map(|(i, t)| {
+                    let id = *token_ids.entry(t.clone()).or_insert_with(|| {
+                        tokens.push(t);
+                        tokens.len() - 1
+                    });
+                    if i == 0 || i == last {
+                        Item::Fixed(id)
+                    } else {
+                        Item::Token(id)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut candidates: HashMap<Vec<usize>, usize> = HashMap::new();
+    for g in &glyphs {
+        for start in 0..g.len() {
+            let mut seq = vec![];
+            for item in g[start..].iter().take(MAX_SUBR_TOKENS) {
+                match item {
+                    Item::Token(id) => seq.push(*id),
+                    _ => break,
+                }
+                *candidates.entry(seq.clone()).or_default() += 1;
+            }
+        }
+    }
+    let size = |seq: &[usize]| seq.iter().map(|&id| tokens[id].len()).sum::<usize>();
+    let savings = |seq: &[usize], count: usize| -> isize {
+        let len = size(seq);
+        count as isize * (len as isize - CALL_COST as isize) - (len + SUBR_OVERHEAD) as isize
+    };
+    let mut candidates = candidates
+        .into_iter()
+        .filter(|(seq, count)| *count > 1 && savings(seq, *count) > 0)
+        .collect::<Vec<(Vec<usize>, usize)>>();
+    candidates
+        .sort_by(|(a, ac), (b, bc)| savings(b, *bc).cmp(&savings(a, *ac)).then_with(|| a.cmp(b)));
+
+    /* Glyphs that contain each token */
+    let mut containing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, g) in glyphs.iter().enumerate() {
+        for item in g {
+            if let Item::Token(id) = item {
+                let list = containing.entry(*id).or_default();
+                if list.last() != Some(&i) {
+                    list.push(i);
+                }
+            }
+        }
+    }
+
+    let matches = |g: &[Item], at: usize, seq: &[usize]| {
+        g.len() >= at + seq.len()
+            && g[at..at + seq.len()]
+                .iter()
+                .zip(seq)
+                .all(|(item, id)| *item == Item::Token(*id))
+    };
+    let mut subrs: Vec<Vec<usize>> = vec![];
+    for (seq, _) in candidates {
+        if subrs.len() == MAX_SUBRS {
+            break;
+        }
+        let mut occurrences = vec![];
+        for &gi in containing.get(&seq[0]).into_iter().flatten() {
+            let g = &glyphs[gi];
+            let mut at = 0;
+            while at < g.len() {
+                if matches(g, at, &seq) {
+                    occurrences.push((gi, at));
+                    at += seq.len();
+                } else {
+                    at += 1;
+                }
+            }
+        }
+        if savings(&seq, occurrences.len()) <= 0 {
+            continue;
+        }
+        let subr = subrs.len();
+        /* Replace back to front so that earlier positions stay valid. */
+        for &(gi, at) in occurrences.iter().rev() {
+            glyphs[gi].splice(at..at + seq.len(), [Item::Call(subr)]);
+        }
+        subrs.push(seq);
+    }
+
+    let bias = subr_bias(subrs.len());
+    let encode = |items: &mut dyn Iterator<Item = Item>| {
+        let mut w = Writer::default();
+        for item in items {
+            match item {
+                Item::Token(id) | Item::Fixed(id) => {
+                    w.bytes(&tokens[id]);
+                }
+                Item::Call(subr) => {
+                    encode_int(&mut w, subr as i32 - bias);
+                    w.u8(op::CALLGSUBR);
+                }
+            }
+        }
+        w.0
+    };
+    let subrs = subrs
+        .iter()
+        .map(|seq| {
+            let mut ret = encode(&mut seq.iter().map(|&id| Item::Token(id)));
+            ret.push(op::RETURN);
+            ret
+        })
+        .collect();
+    let glyphs = glyphs
+        .into_iter()
+        .map(|g| encode(&mut g.into_iter()))
+        .collect();
+    (glyphs, subrs)
+}
+
+/// The most common advance width, used as `defaultWidthX` if fontinfo doesn't set it.
+fn most_common_width(widths: &[i32]) -> i32 {
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for &w in widths {
+        *counts.entry(w).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(w, count)| (count, -w))
+        .map(|(w, _)| w)
+        .unwrap_or(0)
+}
+
+fn private_dict(info: &FontInfo, default_width: i32, nominal_width: i32) -> Vec<u8> {
+    let imp = info.imp();
+    let mut d = Dict::default();
+    for (operator, values) in [
+        (dict_op::BLUE_VALUES, &imp.postscript_blue_values),
+        (dict_op::OTHER_BLUES, &imp.postscript_other_blues),
+        (dict_op::FAMILY_BLUES, &imp.postscript_family_blues),
+        (
+            dict_op::FAMILY_OTHER_BLUES,
+            &imp.postscript_family_other_blues,
+        ),
+    ] {
+        if let Some(values) = values.borrow().as_ref() {
+            d.delta_entry(operator, values);
+        }
+    }
+    for (operator, value) in [
+        (dict_op::BLUE_SCALE, &imp.postscript_blue_scale),
+        (dict_op::BLUE_SHIFT, &imp.postscript_blue_shift),
+        (dict_op::BLUE_FUZZ, &imp.postscript_blue_fuzz),
+    ] {
+        if let Some(value) = *value.borrow() {
+            d.entry(operator, &[value]);
+        }
+    }
+    for (std_operator, snap_operator, values) in [
+        (
+            dict_op::STD_HW,
+            dict_op::STEM_SNAP_H,
+            &imp.postscript_stem_snap_h,
+        ),
+        (
+            dict_op::STD_VW,
+            dict_op::STEM_SNAP_V,
+            &imp.postscript_stem_snap_v,
+        ),
+    ] {
+        if let Some(values) = values.borrow().as_ref().filter(|v| !v.is_empty()) {
+            d.entry(std_operator, &values[..1]);
+            d.delta_entry(snap_operator, values);
+        }
+    }
+    if *imp.postscript_force_bold.borrow() == Some(true) {
+        d.entry(dict_op::FORCE_BOLD, &[1.]);
+    }
+    if default_width != 0 {
+        d.entry(dict_op::DEFAULT_WIDTH_X, &[default_width as f64]);
+    }
+    if nominal_width != 0 {
+        d.entry(dict_op::NOMINAL_WIDTH_X, &[nominal_width as f64]);
+    }
+    d.0 .0
+}
+
+/// Build the `CFF ` table. `metrics` are the font's metrics, for `FontBBox`.
+fn cff(
+    info: &FontInfo,
+    glyphs: &[ExportGlyph],
+    outlines: &[Outline],
+    metrics: &FontMetrics,
+) -> Vec<u8> {
+    let imp = info.imp();
+    let mut strings: Vec<String> = vec![];
+    let mut sid = |s: &str| -> f64 {
+        let i = strings.iter().position(|o| o == s).unwrap_or_else(|| {
+            strings.push(s.to_string());
+            strings.len() - 1
+        });
+        (FIRST_CUSTOM_SID as usize + i) as f64
+    };
+
+    let mut top = Dict::default();
+    top.entry(dict_op::VERSION, &[sid(&sfnt::version_string(info))]);
+    for (operator, value) in [
+        (dict_op::NOTICE, imp.trademark.borrow().clone()),
+        (dict_op::COPYRIGHT, imp.copyright.borrow().clone()),
+        (
+            dict_op::FULL_NAME,
+            imp.postscript_full_name.borrow().clone(),
+        ),
+        (dict_op::FAMILY_NAME, Some(info.family_name())),
+        (dict_op::WEIGHT, imp.postscript_weight_name.borrow().clone()),
+    ] {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            top.entry(operator, &[sid(&value)]);
+        }
+    }
+    if *imp.postscript_is_fixed_pitch.borrow() == Some(true) {
+        top.entry(dict_op::IS_FIXED_PITCH, &[1.]);
+    }
+    let upm = info.units_per_em();
+    if info.italic_angle() != 0. {
+        top.entry(dict_op::ITALIC_ANGLE, &[info.italic_angle()]);
+    }
+    top.entry(
+        dict_op::UNDERLINE_POSITION,
+        &[imp
+            .postscript_underline_position
+            .borrow()
+            .unwrap_or(-upm * 0.075)
+            .round()],
+    )
+    .entry(
+        dict_op::UNDERLINE_THICKNESS,
+        &[imp
+            .postscript_underline_thickness
+            .borrow()
+            .unwrap_or(upm * 0.05)
+            .round()],
+    );
+    if let Some(id) = *imp.postscript_unique_id.borrow() {
+        top.entry(dict_op::UNIQUE_ID, &[id as f64]);
+    }
+    if upm != 1000. {
+        top.entry(dict_op::FONT_MATRIX, &[1. / upm, 0., 0., 1. / upm, 0., 0.]);
+    }
+    top.entry(
+        dict_op::FONT_BBOX,
+        &[
+            metrics.x_min as f64,
+            metrics.y_min as f64,
+            metrics.x_max as f64,
+            metrics.y_max as f64,
+        ],
+    );
+
+    let charset = {
+        let mut w = Writer::default();
+        /* Format 0: the name of every glyph after .notdef */
+        w.u8(0);
+        for g in glyphs.iter().skip(1) {
+            w.u16(sid(&g.name) as u16);
+        }
+        w.0
+    };
+
+    let widths = glyphs
+        .iter()
+        .map(|g| to_u16(g.advance) as i32)
+        .collect::<Vec<i32>>();
+    let default_width = imp
+        .postscript_default_width_x
+        .borrow()
+        .map(|w| w.round() as i32)
+        .unwrap_or_else(|| most_common_width(&widths));
+    let nominal_width = imp
+        .postscript_nominal_width_x
+        .borrow()
+        .map(|w| w.round() as i32)
+        .unwrap_or(default_width);
+    let (charstrings, global_subrs) = subroutinize(
+        outlines
+            .iter()
+            .zip(widths.iter())
+            .map(|(outline, &width)| {
+                charstring(
+                    outline,
+                    Some(width - nominal_width).filter(|_| width != default_width),
+                )
+            })
+            .collect(),
+    );
+    let private = private_dict(info, default_width, nominal_width);
+
+    let header = [1_u8, 0, 4, 4];
+    let name_index = index(&[sfnt::postscript_font_name(info)]);
+    let string_index = index(&strings);
+    let global_subr_index = index(&global_subrs);
+    let charstrings_index = index(&charstrings);
+    let top_dict = |charset_offset, charstrings_offset, private_offset| {
+        let mut d = Dict(Writer(top.0 .0.clone()));
+        d.offset_entry(dict_op::CHARSET, &[charset_offset])
+            .offset_entry(dict_op::CHAR_STRINGS, &[charstrings_offset])
+            .offset_entry(dict_op::PRIVATE, &[private.len(), private_offset]);
+        index(&[d.0 .0])
+    };
+    let top_index_len = top_dict(0, 0, 0).len();
+    let charset_offset = header.len()
+        + name_index.len()
+        + top_index_len
+        + string_index.len()
+        + global_subr_index.len();
+    let charstrings_offset = charset_offset + charset.len();
+    let private_offset = charstrings_offset + charstrings_index.len();
+
+    let mut w = Writer::default();
+    w.bytes(&header)
+        .bytes(&name_index)
+        .bytes(&top_dict(
+            charset_offset,
+            charstrings_offset,
+            private_offset,
+        ))
+        .bytes(&string_index)
+        .bytes(&global_subr_index)
+        .bytes(&charset)
+        .bytes(&charstrings_index)
+        .bytes(&private);
+    w.0
+}
+
+/// Build an OpenType font with CFF outlines out of `glyphs`, whose first glyph must be
+/// `.notdef`. Components are decomposed.
+pub fn compile_otf(
+    info: &FontInfo,
+    glyphs: &[ExportGlyph],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if glyphs.len() > u16::MAX as usize {
+        return Err(format!(
+            "A font can have at most {} glyphs, the project has {}.",
+            u16::MAX,
+            glyphs.len()
+        )
+        .into());
+    }
+    let by_name = glyphs
+        .iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<&str, &ExportGlyph>>();
+    let outlines = glyphs
+        .iter()
+        .map(|g| g.decomposed(&by_name))
+        .collect::<Vec<Outline>>();
+    let metrics = glyphs
+        .iter()
+        .zip(outlines.iter())
+        .map(|(g, outline)| GlyphMetrics {
+            advance: to_u16(g.advance),
+            bounds: outline.bounds().map(|(x0, y0, x1, y1)| {
+                (
+                    to_i16(x0.floor()),
+                    to_i16(y0.floor()),
+                    to_i16(x1.ceil()),
+                    to_i16(y1.ceil()),
+                )
+            }),
+        })
+        .collect::<Vec<GlyphMetrics>>();
+    let font_metrics = FontMetrics::new(&metrics);
+    Ok(sfnt::assemble(
+        u32::from_be_bytes(*b"OTTO"),
+        vec![
+            (*b"CFF ", cff(info, glyphs, &outlines, &font_metrics)),
+            (*b"head", sfnt::head(info, &font_metrics, false)),
+            (
+                *b"hhea",
+                sfnt::hhea(info, &font_metrics, glyphs.len() as u16),
+            ),
+            (*b"maxp", sfnt::maxp_cff(glyphs.len() as u16)),
+            (*b"OS/2", sfnt::os2(info, &font_metrics, glyphs)),
+            (*b"hmtx", sfnt::hmtx(&metrics)),
+            (*b"cmap", sfnt::cmap(glyphs)?),
+            (*b"name", sfnt::name(info)),
+            (*b"post", sfnt::post(info, None)?),
+        ],
+    ))
+}
+
+/// Compile `project` to an OpenType font file with CFF outlines.
+pub fn export_otf(project: &Project) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    compile_otf(&project.fontinfo(), &export_glyphs(project))
+}
+
+#[test]
+fn test_compile_otf() {
+    use glib::subclass::prelude::*;
+
+    let info = FontInfo::new();
+    *info.imp().family_name.borrow_mut() = Some("Test Serif".to_string());
+    *info.imp().units_per_em.borrow_mut() = Some(2048.);
+    *info.imp().postscript_blue_values.borrow_mut() = Some(vec![-10., 0., 500., 510.]);
+    *info.imp().postscript_stem_snap_v.borrow_mut() = Some(vec![80., 90.]);
+
+    let square = |x: f64| PathContour {
+        start: (x, 0.),
+        segments: vec![
+            Segment::Line((x, 100.)),
+            Segment::Line((x + 100., 100.)),
+            Segment::Line((x + 100., 0.)),
+        ],
+    };
+    let bowl = PathContour {
+        start: (250., 0.),
+        segments: vec![
+            Segment::Cubic((388., 0.), (500., 112.), (500., 250.)),
+            Segment::Cubic((500., 388.), (388., 500.), (250., 500.)),
+            Segment::Cubic((112., 500.), (0., 388.), (0., 250.)),
+            Segment::Quad((0., 0.), (250., 0.)),
+        ],
+    };
+    let mut glyphs = vec![ExportGlyph::notdef(&info)];
+    /* Glyphs that share contours, so that the charstrings have something to subroutinize */
+    for (i, c) in ('a'..='z').enumerate() {
+        glyphs.push(ExportGlyph {
+            name: c.to_string(),
+            unicode: vec![c],
+            advance: 600. + (i % 3) as f64,
+            outline: Outline {
+                contours: vec![bowl.clone(), square(i as f64 * 10.)],
+            },
+            components: vec![],
+        });
+    }
+    glyphs.push(ExportGlyph {
+        name: "A".to_string(),
+        unicode: vec!['A'],
+        advance: 1200.,
+        outline: Outline::default(),
+        components: vec![
+            ("a".to_string(), [1., 0., 0., 1., 0., 0.]),
+            ("b".to_string(), [1., 0., 0., 1., 600., 0.]),
+        ],
+    });
+    let data = compile_otf(&info, &glyphs).unwrap();
+
+    let face = ttf_parser::Face::from_slice(&data, 0).unwrap();
+    assert_eq!(face.number_of_glyphs(), 28);
+    assert_eq!(face.units_per_em(), 2048);
+    assert!(face.tables().cff.is_some());
+    let family = face
+        .names()
+        .into_iter()
+        .find(|n| n.name_id == ttf_parser::name_id::FAMILY)
+        .and_then(|n| n.to_string());
+    assert_eq!(family.as_deref(), Some("Test Serif"));
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+    impl ttf_parser::OutlineBuilder for Recorder {
+        fn move_to(&mut self, x: f32, y: f32) {
+            self.0.push(format!("M {} {}", x, y));
+        }
+        fn line_to(&mut self, x: f32, y: f32) {
+            self.0.push(format!("L {} {}", x, y));
+        }
+        fn quad_to(&mut self, _: f32, _: f32, x: f32, y: f32) {
+            self.0.push(format!("Q {} {}", x, y));
+        }
+        fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+            self.0
+                .push(format!("C {} {} {} {} {} {}", x1, y1, x2, y2, x, y));
+        }
+        fn close(&mut self) {
+            self.0.push("Z".to_string());
+        }
+    }
+    for (i, c) in ('a'..='z').enumerate() {
+        let id = face.glyph_index(c).unwrap();
+        assert_eq!(id.0 as usize, i + 1);
+        assert_eq!(face.glyph_hor_advance(id), Some(600 + (i % 3) as u16));
+        assert_eq!(face.glyph_name(id), Some(glyphs[i + 1].name.as_str()));
+        let mut recorder = Recorder::default();
+        let rect = face.outline_glyph(id, &mut recorder).unwrap();
+        assert_eq!(
+            (rect.x_min, rect.y_min, rect.x_max, rect.y_max),
+            (0, 0, 500, 500)
+        );
+        assert_eq!(recorder.0[0], "M 250 0");
+        /* Cubic curves are kept as they are, the quadratic is raised exactly. */
+        assert_eq!(recorder.0[1], "C 388 0 500 112 500 250");
+        assert_eq!(recorder.0[4], "C 0 83 83 0 250 0");
+        assert_eq!(recorder.0[6], format!("M {} 0", i * 10));
+        assert_eq!(recorder.0.len(), 11);
+    }
+    let id = face.glyph_index('A').unwrap();
+    let mut recorder = Recorder::default();
+    let rect = face.outline_glyph(id, &mut recorder).unwrap();
+    assert_eq!((rect.x_min, rect.x_max), (0, 1100));
+    assert_eq!(recorder.0.iter().filter(|s| *s == "Z").count(), 4);
+}
+
+#[test]
+fn test_subroutinize() {
+    let token = |v: i32, op: u8| {
+        let mut w = Writer::default();
+        encode_int(&mut w, v);
+        w.u8(op);
+        w.0
+    };
+    let shared = (0..10)
+        .map(|i| token(1000 + i, op::RLINETO))
+        .collect::<Vec<Token>>();
+    let charstrings = (0..5)
+        .map(|i| {
+            let mut cs = vec![token(i, op::RMOVETO)];
+            cs.extend(shared.iter().cloned());
+            cs.push(vec![op::ENDCHAR]);
+            cs
+        })
+        .collect::<Vec<Vec<Token>>>();
+    let flat_size = charstrings.iter().flatten().map(Vec::len).sum::<usize>();
+    let (glyphs, subrs) = subroutinize(charstrings.clone());
+    assert_eq!(subrs.len(), 1);
+    assert_eq!(
+        subrs[0],
+        [shared.concat(), vec![op::RETURN]].concat(),
+        "{:?}",
+        subrs
+    );
+    let size = glyphs
+        .iter()
+        .chain(subrs.iter())
+        .map(Vec::len)
+        .sum::<usize>();
+    assert!(size < flat_size / 2);
+    for (g, cs) in glyphs.iter().zip(charstrings.iter()) {
+        /* moveto, call to subroutine 0, endchar */
+        let mut expected = cs[0].clone();
+        expected.extend([(0 - subr_bias(1) + 139) as u8, op::CALLGSUBR, op::ENDCHAR]);
+        assert_eq!(g, &expected);
+    }
+}
@@ -0,0 +1,316 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Glyph outlines as plain segments, independent of the editor's objects.
+
+use crate::glyphs::Contour;
+use crate::utils::curves::{bounding_box, union_bbox};
+
+pub type Point = (f64, f64);
+
+/// Affine transformation `[xx, xy, yx, yy, dx, dy]`, mapping `(x, y)` to
+/// `(xx * x + yx * y + dx, xy * x + yy * y + dy)`. This is the order of the GLIF component
+/// attributes.
+pub type Transform = [f64; 6];
+
+pub const IDENTITY: Transform = [1., 0., 0., 1., 0., 0.];
+
+pub fn apply(t: Transform, (x, y): Point) -> Point {
+    (t[0] * x + t[2] * y + t[4], t[1] * x + t[3] * y + t[5])
+}
+
+/// The transformation that applies `inner` and then `outer`.
+pub fn compose(outer: Transform, inner: Transform) -> Transform {
+    [
+        outer[0] * inner[0] + outer[2] * inner[1],
+        outer[1] * inner[0] + outer[3] * inner[1],
+        outer[0] * inner[2] + outer[2] * inner[3],
+        outer[1] * inner[2] + outer[3] * inner[3],
+        outer[0] * inner[4] + outer[2] * inner[5] + outer[4],
+        outer[1] * inner[4] + outer[3] * inner[5] + outer[5],
+    ]
+}
+
+/// A segment of a contour, starting where the previous one ended. Points are the off-curve
+/// control points followed by the on-curve end point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    Line(Point),
+    Quad(Point, Point),
+    Cubic(Point, Point, Point),
+}
+
+impl Segment {
+    pub fn end(&self) -> Point {
+        match *self {
+            Segment::Line(p) | Segment::Quad(_, p) | Segment::Cubic(_, _, p) => p,
+        }
+    }
+
+    fn map(self, f: impl Fn(Point) -> Point) -> Self {
+        match self {
+            Segment::Line(p) => Segment::Line(f(p)),
+            Segment::Quad(c, p) => Segment::Quad(f(c), f(p)),
+            Segment::Cubic(c1, c2, p) => Segment::Cubic(f(c1), f(c2), f(p)),
+        }
+    }
+
+    fn points(&self) -> Vec<Point> {
+        match *self {
+            Segment::Line(p) => vec![p],
+            Segment::Quad(c, p) => vec![c, p],
+            Segment::Cubic(c1, c2, p) => vec![c1, c2, p],
+        }
+    }
+}
+
+/// A contour; font contours are always closed, so if the last segment doesn't end at `start` a
+/// line back to it is implied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathContour {
+    pub start: Point,
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outline {
+    pub contours: Vec<PathContour>,
+}
+
+impl Outline {
+    /// Convert the editor's contours. Curves of degree higher than three are approximated with
+    /// lines, and a final line back to the start point is dropped since it's implied.
+    pub fn from_contours(contours: &[Contour]) -> Self {
+        const HIGHER_DEGREE_STEPS: usize = 16;
+        let mut ret = Outline::default();
+        for contour in contours {
+            let curves = contour.curves().borrow();
+            let mut path: Option<PathContour> = None;
+            for curve in curves.iter() {
+                let points = curve
+                    .points()
+                    .borrow()
+                    .iter()
+                    .map(|&(x, y)| (x as f64, y as f64))
+                    .collect::<Vec<Point>>();
+                if points.len() < 2 {
+                    continue;
+                }
+                let path = path.get_or_insert_with(|| PathContour {
+                    start: points[0],
+                    segments: vec![],
+                });
+                match points.len() {
+                    2 => path.segments.push(Segment::Line(points[1])),
+                    3 => path.segments.push(Segment::Quad(points[1], points[2])),
+                    4 => path
+                        .segments
+                        .push(Segment::Cubic(points[1], points[2], points[3])),
+                    _ => {
+                        for i in 1..=HIGHER_DEGREE_STEPS {
+                            let (x, y) = curve.compute(i as f64 / HIGHER_DEGREE_STEPS as f64);
+                            path.segments.push(Segment::Line((x as f64, y as f64)));
+                        }
+                    }
+                }
+            }
+            if let Some(mut path) = path {
+                if matches!(path.segments.last(), Some(Segment::Line(p)) if *p == path.start) {
+                    path.segments.pop();
+                }
+                if !path.segments.is_empty() {
+                    ret.contours.push(path);
+                }
+            }
+        }
+        ret
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+
+    pub fn append_transformed(&mut self, other: &Outline, transform: Transform) {
+        self.contours.extend(other.contours.iter().map(|c| {
+            PathContour {
+                start: apply(transform, c.start),
+                segments: c
+                    .segments
+                    .iter()
+                    .map(|s| s.map(|p| apply(transform, p)))
+                    .collect(),
+            }
+        }));
+    }
+
+    /// Bounding box `(x_min, y_min, x_max, y_max)` of all points, including off-curve ones.
+    pub fn control_bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let mut points = self.contours.iter().flat_map(|c| {
+            std::iter::once(c.start).chain(c.segments.iter().flat_map(Segment::points))
+        });
+        let first = points.next()?;
+        Some(points.fold(
+            (first.0, first.1, first.0, first.1),
+            |(x0, y0, x1, y1), (x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        ))
+    }
+    /// Exact bounding box `(x_min, y_min, x_max, y_max)` of the curves, including their
+    /// extrema.
+    pub fn bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let mut ret = None;
+        for c in &self.contours {
+            let mut current = c.start;
+            ret = union_bbox(ret, Some((current.0, current.1, current.0, current.1)));
+            for s in &c.segments {
+                let points = std::iter::once(current)
+                    .chain(s.points())
+                    .collect::<Vec<Point>>();
+                ret = union_bbox(ret, Some(bounding_box(&points)));
+                current = s.end();
+            }
+        }
+        ret
+    }
+}
+
+fn lerp(a: Point, b: Point, t: f64) -> Point {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+fn cubic_point(p: [Point; 4], t: f64) -> Point {
+    let mt = 1. - t;
+    let (a, b, c, d) = (mt * mt * mt, 3. * mt * mt * t, 3. * mt * t * t, t * t * t);
+    (
+        a * p[0].0 + b * p[1].0 + c * p[2].0 + d * p[3].0,
+        a * p[0].1 + b * p[1].1 + c * p[2].1 + d * p[3].1,
+    )
+}
+
+/// The part of cubic `p` between `t0` and `t1`.
+fn cubic_range(p: [Point; 4], t0: f64, t1: f64) -> [Point; 4] {
+    fn split(p: [Point; 4], t: f64) -> ([Point; 4], [Point; 4]) {
+        let (ab, bc, cd) = (
+            lerp(p[0], p[1], t),
+            lerp(p[1], p[2], t),
+            lerp(p[2], p[3], t),
+        );
+        let (abc, bcd) = (lerp(ab, bc, t), lerp(bc, cd, t));
+        let m = lerp(abc, bcd, t);
+        ([p[0], ab, abc, m], [m, bcd, cd, p[3]])
+    }
+    let right = if t0 > 0. { split(p, t0).1 } else { p };
+    if t1 >= 1. {
+        return right;
+    }
+    split(right, (t1 - t0) / (1. - t0)).0
+}
+
+/// Approximate the cubic `p0 c1 c2 p3` with quadratic segments that are at most `tolerance`
+/// away from it. Returns the control and end point of each quadratic.
+pub fn cubic_to_quadratics(
+    p0: Point,
+    c1: Point,
+    c2: Point,
+    p3: Point,
+    tolerance: f64,
+) -> Vec<(Point, Point)> {
+    const MAX_SEGMENTS: usize = 32;
+    const SAMPLES: usize = 8;
+    let cubic = [p0, c1, c2, p3];
+    let mut ret = vec![];
+    for n in 1..=MAX_SEGMENTS {
+        ret.clear();
+        let mut fits = true;
+        for i in 0..n {
+            let part = cubic_range(cubic, i as f64 / n as f64, (i + 1) as f64 / n as f64);
+            /* The quadratic that has the same end points and the same midpoint tangent */
+            let q = (
+                (3. * (part[1].0 + part[2].0) - part[0].0 - part[3].0) / 4.,
+                (3. * (part[1].1 + part[2].1) - part[0].1 - part[3].1) / 4.,
+            );
+            for s in 1..SAMPLES {
+                let t = s as f64 / SAMPLES as f64;
+                let c = cubic_point(part, t);
+                let quad = lerp(lerp(part[0], q, t), lerp(q, part[3], t), t);
+                if (c.0 - quad.0).hypot(c.1 - quad.1) > tolerance {
+                    fits = false;
+                    break;
+                }
+            }
+            ret.push((q, part[3]));
+            if !fits && n < MAX_SEGMENTS {
+                break;
+            }
+        }
+        if fits {
+            break;
+        }
+    }
+    ret
+}
+
+#[test]
+fn test_cubic_to_quadratics() {
+    /* A quadratic raised to a cubic converts back to a single quadratic. */
+    let q = cubic_to_quadratics((0., 0.), (100., 200.), (200., 200.), (300., 0.), 1.0);
+    assert_eq!(q, vec![((150., 300.), (300., 0.))]);
+
+    /* A quarter circle needs more than one. */
+    let k = 0.5523 * 500.;
+    let cubic = [(500., 0.), (500., k), (k, 500.), (0., 500.)];
+    let q = cubic_to_quadratics(cubic[0], cubic[1], cubic[2], cubic[3], 0.5);
+    assert!(q.len() > 1);
+    assert_eq!(q.last().unwrap().1, (0., 500.));
+    let mut start = cubic[0];
+    for (i, &(c, end)) in q.iter().enumerate() {
+        for s in 0..=10 {
+            let t = s as f64 / 10.;
+            let p = lerp(lerp(start, c, t), lerp(c, end, t), t);
+            let expected = cubic_point(cubic, (i as f64 + t) / q.len() as f64);
+            assert!((p.0 - expected.0).hypot(p.1 - expected.1) <= 0.5);
+        }
+        start = end;
+    }
+
+    let outline = Outline {
+        contours: vec![PathContour {
+            start: cubic[0],
+            segments: vec![Segment::Cubic(cubic[1], cubic[2], cubic[3])],
+        }],
+    };
+    assert_eq!(outline.control_bounds(), Some((0., 0., 500., 500.)));
+    assert_eq!(outline.bounds(), Some((0., 0., 500., 500.)));
+    let outline = Outline {
+        contours: vec![PathContour {
+            start: (0., 0.),
+            segments: vec![
+                Segment::Quad((50., 100.), (100., 0.)),
+                Segment::Cubic((100., -90.), (0., -90.), (0., 0.)),
+            ],
+        }],
+    };
+    let (x0, y0, x1, y1) = outline.bounds().unwrap();
+    assert_eq!((x0, x1, y1), (0., 100., 50.));
+    assert!((y0 + 67.5).abs() < 1e-9);
+
+    let t = compose([2., 0., 0., 2., 10., 0.], [1., 0., 0.5, 1., 0., 5.]);
+    assert_eq!(apply(t, (1., 1.)), (13., 12.));
+}
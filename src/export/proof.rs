@@ -0,0 +1,448 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Printable proofs: a character grid, a waterfall and sample texts laid out with the project's
+//! glyphs on the pages of a PDF file.
+
+use super::*;
+use cairo::{Context, FontSlant, FontWeight, Matrix};
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::glyphs::GlyphDrawingOptions;
+
+/// Contents and layout of a proof. Sizes are in points.
+#[derive(Debug, Clone)]
+pub struct ProofOptions {
+    /// Page width and height.
+    pub page_size: (f64, f64),
+    pub margin: f64,
+    /// Em size of the glyphs in the character grid, or `None` to leave the grid out.
+    pub grid_size: Option<f64>,
+    /// Each size is a line of `waterfall_text`, cut at the right margin.
+    pub waterfall_sizes: Vec<f64>,
+    pub waterfall_text: String,
+    /// Paragraphs wrapped to the page width at `text_size`. Line breaks start new paragraphs.
+    pub texts: Vec<String>,
+    pub text_size: f64,
+}
+
+pub const A4: (f64, f64) = (595.28, 841.89);
+pub const LETTER: (f64, f64) = (612., 792.);
+
+impl Default for ProofOptions {
+    fn default() -> Self {
+        ProofOptions {
+            page_size: A4,
+            margin: 42.,
+            grid_size: Some(28.),
+            waterfall_sizes: vec![72., 48., 36., 24., 18., 14., 12., 10., 9., 8., 7., 6.],
+            waterfall_text: "Hamburgefontsiv".to_string(),
+            texts: vec![],
+            text_size: 12.,
+        }
+    }
+}
+
+const FOOTER_SIZE: f64 = 7.;
+const HEADING_SIZE: f64 = 12.;
+const LABEL_SIZE: f64 = 5.;
+
+/// A line of glyphs: each glyph, or `None` for a character the project has no glyph for, with
+/// its horizontal position in font units.
+struct Run {
+    glyphs: Vec<(Option<Rc<RefCell<Glyph>>>, f64)>,
+    width: f64,
+}
+
+struct Proof<'a> {
+    cr: Context,
+    project: &'a Project,
+    options: &'a ProofOptions,
+    units_per_em: f64,
+    ascender: f64,
+    descender: f64,
+    /// Glyph for each code point, and the glyph used for characters that aren't in the font.
+    cmap: HashMap<char, Rc<RefCell<Glyph>>>,
+    notdef: Option<Rc<RefCell<Glyph>>>,
+    footer: String,
+    page: usize,
+    /// Top of the free space on the current page.
+    y: f64,
+}
+
+impl<'a> Proof<'a> {
+    fn bottom(&self) -> f64 {
+        self.options.page_size.1 - self.options.margin - 2. * FOOTER_SIZE
+    }
+
+    fn content_width(&self) -> f64 {
+        self.options.page_size.0 - 2. * self.options.margin
+    }
+
+    fn finish_page(&mut self) -> Result<(), cairo::Error> {
+        let (width, height) = self.options.page_size;
+        let margin = self.options.margin;
+        let cr = &self.cr;
+        cr.set_source_rgb(0.3, 0.3, 0.3);
+        cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+        cr.set_font_size(FOOTER_SIZE);
+        cr.move_to(margin, height - margin);
+        cr.show_text(&self.footer)?;
+        let page = format!("{}", self.page + 1);
+        let extents = cr.text_extents(&page)?;
+        cr.move_to(width - margin - extents.x_advance, height - margin);
+        cr.show_text(&page)?;
+        cr.show_page()?;
+        self.page += 1;
+        self.y = margin;
+        Ok(())
+    }
+
+    /// Start a new page unless `height` fits below the current position.
+    fn reserve(&mut self, height: f64) -> Result<(), cairo::Error> {
+        if self.y + height > self.bottom() && self.y > self.options.margin {
+            self.finish_page()?;
+        }
+        Ok(())
+    }
+
+    fn heading(&mut self, title: &str) -> Result<(), cairo::Error> {
+        self.reserve(HEADING_SIZE * 4.)?;
+        if self.y > self.options.margin {
+            self.y += HEADING_SIZE;
+        }
+        let cr = &self.cr;
+        cr.set_source_rgb(0., 0., 0.);
+        cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+        cr.set_font_size(HEADING_SIZE);
+        cr.move_to(self.options.margin, self.y + HEADING_SIZE);
+        cr.show_text(title)?;
+        self.y += HEADING_SIZE * 2.;
+        Ok(())
+    }
+
+    /// Draw `glyph` with its origin at `(x, baseline)` and an em of `size` points.
+    fn draw_glyph(&self, glyph: &Glyph, x: f64, baseline: f64, size: f64) {
+        let scale = size / self.units_per_em;
+        let mut matrix = Matrix::identity();
+        matrix.translate(x, baseline - self.units_per_em * scale);
+        matrix.scale(scale, scale);
+        glyph.draw(
+            &self.cr,
+            GlyphDrawingOptions {
+                outline: (0., 0., 0., 0.),
+                inner_fill: Some((0., 0., 0., 1.)),
+                highlight: None,
+                matrix,
+                units_per_em: self.units_per_em,
+                line_width: 0.,
+            },
+        );
+    }
+
+    /// Lay out `text` with the glyphs' advances and the kerning between them.
+    fn shape(&self, text: &str) -> Run {
+        let mut glyphs = Vec::with_capacity(text.len());
+        let mut x = 0.;
+        let mut previous: Option<String> = None;
+        for c in text.chars() {
+            let glyph = self.cmap.get(&c).or(self.notdef.as_ref()).cloned();
+            match glyph {
+                Some(glyph) => {
+                    let name = glyph.borrow().name.to_string();
+                    if let Some(previous) = previous {
+                        x += self.project.kerning_value(&previous, &name);
+                    }
+                    let advance = glyph.borrow().width.unwrap_or(0.);
+                    glyphs.push((Some(glyph), x));
+                    x += advance;
+                    previous = Some(name);
+                }
+                None => {
+                    glyphs.push((None, x));
+                    x += self.units_per_em / 2.;
+                    previous = None;
+                }
+            }
+        }
+        Run { glyphs, width: x }
+    }
+
+    fn draw_run(&self, run: &Run, x: f64, baseline: f64, size: f64) -> Result<(), cairo::Error> {
+        let scale = size / self.units_per_em;
+        for (glyph, offset) in &run.glyphs {
+            let x = x + offset * scale;
+            match glyph {
+                Some(glyph) => self.draw_glyph(&glyph.borrow(), x, baseline, size),
+                None => {
+                    /* Missing glyph without a .notdef: an empty box. */
+                    let cr = &self.cr;
+                    cr.set_source_rgb(0., 0., 0.);
+                    cr.set_line_width(size / 20.);
+                    cr.rectangle(
+                        x + size * 0.05,
+                        baseline - size * 0.7,
+                        size * 0.4,
+                        size * 0.7,
+                    );
+                    cr.stroke()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn line_height(&self, size: f64) -> f64 {
+        (self.ascender - self.descender) / self.units_per_em * size * 1.2
+    }
+
+    fn character_grid(&mut self, size: f64) -> Result<(), cairo::Error> {
+        let glyphs = self.project.imp().glyphs.borrow();
+        let mut sorted = glyphs.values().cloned().collect::<Vec<_>>();
+        drop(glyphs);
+        sorted.sort_by(|a, b| self.project.cmp_glyphs(&a.borrow(), &b.borrow()));
+
+        self.heading("Character set")?;
+        let scale = size / self.units_per_em;
+        let cell_width = size * 1.5;
+        let cell_height = (self.ascender - self.descender) * scale + 2. * LABEL_SIZE;
+        let columns = ((self.content_width() / cell_width).floor() as usize).max(1);
+        for row in sorted.chunks(columns) {
+            self.reserve(cell_height)?;
+            for (i, glyph) in row.iter().enumerate() {
+                let glyph = glyph.borrow();
+                let x = self.options.margin + i as f64 * cell_width;
+                let cr = &self.cr;
+                cr.save()?;
+                cr.rectangle(x, self.y, cell_width, cell_height);
+                cr.set_source_rgb(0.7, 0.7, 0.7);
+                cr.set_line_width(0.25);
+                cr.stroke_preserve()?;
+                cr.clip();
+                let advance = glyph.width.unwrap_or(0.) * scale;
+                self.draw_glyph(
+                    &glyph,
+                    x + (cell_width - advance) / 2.,
+                    self.y + self.ascender * scale,
+                    size,
+                );
+                cr.set_source_rgb(0.3, 0.3, 0.3);
+                cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+                cr.set_font_size(LABEL_SIZE);
+                cr.move_to(x + 1., self.y + cell_height - LABEL_SIZE / 2.);
+                cr.show_text(&glyph.name)?;
+                cr.restore()?;
+            }
+            self.y += cell_height;
+        }
+        Ok(())
+    }
+
+    fn waterfall(&mut self) -> Result<(), cairo::Error> {
+        self.heading("Waterfall")?;
+        let run = self.shape(&self.options.waterfall_text);
+        let label_width = 30.;
+        let options = self.options;
+        for &size in &options.waterfall_sizes {
+            let height = self.line_height(size);
+            self.reserve(height)?;
+            let baseline = self.y + self.ascender / self.units_per_em * size;
+            let cr = &self.cr;
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+            cr.set_font_size(FOOTER_SIZE);
+            cr.move_to(self.options.margin, baseline);
+            cr.show_text(&format!("{} pt", size))?;
+            cr.save()?;
+            cr.rectangle(
+                self.options.margin + label_width,
+                self.y,
+                self.content_width() - label_width,
+                height,
+            );
+            cr.clip();
+            self.draw_run(&run, self.options.margin + label_width, baseline, size)?;
+            self.cr.restore()?;
+            self.y += height;
+        }
+        Ok(())
+    }
+
+    fn texts(&mut self) -> Result<(), cairo::Error> {
+        let size = self.options.text_size;
+        let scale = size / self.units_per_em;
+        let max_width = self.content_width() / scale;
+        let space = self
+            .cmap
+            .get(&' ')
+            .and_then(|g| g.borrow().width)
+            .unwrap_or(self.units_per_em / 4.);
+        self.heading("Text")?;
+        let options = self.options;
+        for paragraph in options.texts.iter().flat_map(|t| t.lines()) {
+            /* Break lines between words; a word longer than a line is cut at the margin. */
+            let mut lines: Vec<Vec<Run>> = vec![vec![]];
+            let mut width = 0.;
+            for word in paragraph.split(' ').map(|w| self.shape(w)) {
+                let line = lines.last_mut().unwrap();
+                if !line.is_empty() && width + space + word.width > max_width {
+                    width = word.width;
+                    lines.push(vec![word]);
+                } else {
+                    if !line.is_empty() {
+                        width += space;
+                    }
+                    width += word.width;
+                    line.push(word);
+                }
+            }
+            for line in lines {
+                let height = self.line_height(size);
+                self.reserve(height)?;
+                let baseline = self.y + self.ascender * scale;
+                let mut x = self.options.margin;
+                self.cr.save()?;
+                self.cr
+                    .rectangle(self.options.margin, self.y, self.content_width(), height);
+                self.cr.clip();
+                for word in line {
+                    self.draw_run(&word, x, baseline, size)?;
+                    x += (word.width + space) * scale;
+                }
+                self.cr.restore()?;
+                self.y += height;
+            }
+            self.y += self.line_height(size) / 2.;
+        }
+        Ok(())
+    }
+}
+
+/// Render a proof of `project` to the PDF file `path`. Every page has the family and style name,
+/// version, the time of rendering and the page number in its footer. Returns the number of pages.
+pub fn render_proof(
+    project: &Project,
+    path: &Path,
+    options: &ProofOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let info = project.fontinfo();
+    let surface = cairo::PdfSurface::new(options.page_size.0, options.page_size.1, path)
+        .map_err(|err| format!("Could not create {}: {}", path.display(), err))?;
+    let cr = Context::new(&surface)?;
+
+    let mut cmap = HashMap::new();
+    let mut notdef = None;
+    for glyph in project.imp().glyphs.borrow().values() {
+        let g = glyph.borrow();
+        if g.name == ".notdef" {
+            notdef = Some(glyph.clone());
+        }
+        for c in &g.unicode {
+            cmap.entry(*c).or_insert_with(|| glyph.clone());
+        }
+        if let crate::glyphs::GlyphKind::Char(c) = g.kind {
+            cmap.entry(c).or_insert_with(|| glyph.clone());
+        }
+    }
+    let footer = format!(
+        "{} {} — Version {} — {}",
+        info.family_name(),
+        info.style_name(),
+        sfnt::version_string(&info),
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+    );
+    let mut proof = Proof {
+        cr,
+        project,
+        options,
+        units_per_em: info.units_per_em(),
+        ascender: info.ascender(),
+        descender: info.descender(),
+        cmap,
+        notdef,
+        footer,
+        page: 0,
+        y: options.margin,
+    };
+    if let Some(size) = options.grid_size {
+        proof.character_grid(size)?;
+    }
+    if !options.waterfall_sizes.is_empty() && !options.waterfall_text.is_empty() {
+        proof.waterfall()?;
+    }
+    if !options.texts.is_empty() {
+        proof.texts()?;
+    }
+    proof.finish_page()?;
+    let pages = proof.page;
+    drop(proof);
+    surface.finish();
+    surface
+        .status()
+        .map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+    Ok(pages)
+}
+
+#[test]
+fn test_render_proof() {
+    use crate::glyphs::Contour;
+    use crate::utils::curves::Bezier;
+
+    let project = Project::from_template(&Default::default());
+    {
+        let glyphs = project.imp().glyphs.borrow();
+        let mut a = glyphs["A"].borrow_mut();
+        let contour = Contour::new();
+        *contour.open().borrow_mut() = false;
+        for points in [
+            vec![(0, 0), (250, 700)],
+            vec![(250, 700), (500, 0)],
+            vec![(500, 0), (0, 0)],
+        ] {
+            contour
+                .curves()
+                .borrow_mut()
+                .push(Bezier::new(false, points));
+        }
+        a.contours.push(contour);
+        a.width = Some(500.);
+    }
+    project.imp().kerning.borrow_mut().set("A", "V", Some(-80.));
+
+    let path = std::env::temp_dir().join(format!("gerb-test-proof-{}.pdf", std::process::id()));
+    let one_page = ProofOptions {
+        grid_size: None,
+        texts: vec!["AVA".to_string()],
+        ..ProofOptions::default()
+    };
+    assert_eq!(render_proof(&project, &path, &one_page).unwrap(), 1);
+    let options = ProofOptions {
+        texts: vec!["AVA AAA ¶ ".repeat(600)],
+        ..ProofOptions::default()
+    };
+    let pages = render_proof(&project, &path, &options).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(pages > 2);
+    assert!(data.starts_with(b"%PDF"));
+}
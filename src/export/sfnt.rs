@@ -0,0 +1,911 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The OpenType container and the tables that don't depend on the outline format.
+//!
+//! See <https://docs.microsoft.com/en-us/typography/opentype/spec/otff>.
+
+use glib::subclass::prelude::*;
+
+use super::ExportGlyph;
+use crate::project::FontInfo;
+
+pub type Tag = [u8; 4];
+
+/// Big-endian byte buffer.
+#[derive(Debug, Default)]
+pub struct Writer(pub Vec<u8>);
+
+impl Writer {
+    pub fn u8(&mut self, v: u8) -> &mut Self {
+        self.0.push(v);
+        self
+    }
+
+    pub fn u16(&mut self, v: u16) -> &mut Self {
+        self.0.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    pub fn i16(&mut self, v: i16) -> &mut Self {
+        self.0.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    pub fn u32(&mut self, v: u32) -> &mut Self {
+        self.0.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    pub fn i64(&mut self, v: i64) -> &mut Self {
+        self.0.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    /// 16.16 fixed point number.
+    pub fn fixed(&mut self, v: f64) -> &mut Self {
+        self.0
+            .extend_from_slice(&((v * 65536.).round() as i32).to_be_bytes());
+        self
+    }
+
+    pub fn bytes(&mut self, v: &[u8]) -> &mut Self {
+        self.0.extend_from_slice(v);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn align(&mut self, n: usize) -> &mut Self {
+        let padding = (n - self.0.len() % n) % n;
+        self.0.resize(self.0.len() + padding, 0);
+        self
+    }
+}
+
+/// Round and clamp to the range of an `i16`, the type of most coordinates in font tables.
+pub fn to_i16(v: f64) -> i16 {
+    v.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+}
+
+pub fn to_u16(v: f64) -> u16 {
+    v.round().clamp(0., u16::MAX as f64) as u16
+}
+
+/// `searchRange`, `entrySelector` and `rangeShift` for binary searching `n` items of `size`
+/// bytes.
+pub fn search_params(n: u16, size: u16) -> (u16, u16, u16) {
+    let mut entry_selector = 0;
+    while (1_u32 << (entry_selector + 1)) <= n as u32 {
+        entry_selector += 1;
+    }
+    let search_range = (1_u16 << entry_selector) * size;
+    (
+        search_range,
+        entry_selector,
+        (n * size).saturating_sub(search_range),
+    )
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0_u32, |acc, chunk| {
+        let mut word = [0_u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        acc.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Assemble a font file out of `tables`. The `head` table's `checkSumAdjustment` is filled in.
+pub fn assemble(sfnt_version: u32, mut tables: Vec<(Tag, Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    let num_tables = tables.len() as u16;
+    let (search_range, entry_selector, range_shift) = search_params(num_tables, 16);
+    let mut w = Writer::default();
+    w.u32(sfnt_version)
+        .u16(num_tables)
+        .u16(search_range)
+        .u16(entry_selector)
+        .u16(range_shift);
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, data) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        w.bytes(tag)
+            .u32(checksum(data))
+            .u32(offset as u32)
+            .u32(data.len() as u32);
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in &tables {
+        w.bytes(data).align(4);
+    }
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0AFBA_u32.wrapping_sub(checksum(&w.0));
+        w.0[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    w.0
+}
+
+/// Set bits from a list of bit numbers, ignoring the ones out of range.
+fn bits(list: &[i64]) -> u32 {
+    list.iter()
+        .filter(|&&b| (0..32).contains(&b))
+        .fold(0, |acc, &b| acc | (1 << b))
+}
+
+/// Metrics of the whole font, computed from the glyphs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FontMetrics {
+    pub x_min: i16,
+    pub y_min: i16,
+    pub x_max: i16,
+    pub y_max: i16,
+    pub advance_width_max: u16,
+    pub min_left_side_bearing: i16,
+    pub min_right_side_bearing: i16,
+    pub x_max_extent: i16,
+    pub avg_char_width: i16,
+}
+
+/// Horizontal metrics of a single glyph.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlyphMetrics {
+    pub advance: u16,
+    /// `(x_min, y_min, x_max, y_max)`, or `None` for empty glyphs.
+    pub bounds: Option<(i16, i16, i16, i16)>,
+}
+
+impl GlyphMetrics {
+    fn lsb(&self) -> i16 {
+        self.bounds.map(|b| b.0).unwrap_or(0)
+    }
+}
+
+impl FontMetrics {
+    pub fn new(glyphs: &[GlyphMetrics]) -> Self {
+        let mut ret = FontMetrics {
+            x_min: i16::MAX,
+            y_min: i16::MAX,
+            x_max: i16::MIN,
+            y_max: i16::MIN,
+            min_left_side_bearing: i16::MAX,
+            min_right_side_bearing: i16::MAX,
+            x_max_extent: i16::MIN,
+            ..Default::default()
+        };
+        let mut any_bounds = false;
+        for g in glyphs {
+            ret.advance_width_max = ret.advance_width_max.max(g.advance);
+            if let Some((x0, y0, x1, y1)) = g.bounds {
+                any_bounds = true;
+                ret.x_min = ret.x_min.min(x0);
+                ret.y_min = ret.y_min.min(y0);
+                ret.x_max = ret.x_max.max(x1);
+                ret.y_max = ret.y_max.max(y1);
+                ret.min_left_side_bearing = ret.min_left_side_bearing.min(x0);
+                ret.min_right_side_bearing = ret
+                    .min_right_side_bearing
+                    .min(to_i16(g.advance as f64 - x1 as f64));
+                ret.x_max_extent = ret.x_max_extent.max(x1);
+            }
+        }
+        if !any_bounds {
+            ret.x_min = 0;
+            ret.y_min = 0;
+            ret.x_max = 0;
+            ret.y_max = 0;
+            ret.min_left_side_bearing = 0;
+            ret.min_right_side_bearing = 0;
+            ret.x_max_extent = 0;
+        }
+        let advances = glyphs
+            .iter()
+            .filter(|g| g.advance > 0)
+            .map(|g| g.advance as f64)
+            .collect::<Vec<f64>>();
+        if !advances.is_empty() {
+            ret.avg_char_width = to_i16(advances.iter().sum::<f64>() / advances.len() as f64);
+        }
+        ret
+    }
+}
+
+/// `styleMapStyleName`, or `regular` if it isn't set.
+fn style_map_style(info: &FontInfo) -> String {
+    info.imp()
+        .style_map_style_name
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| "regular".to_string())
+}
+
+/// Seconds since 1904-01-01, the epoch of `head` timestamps.
+fn long_date_time(t: chrono::NaiveDateTime) -> i64 {
+    chrono::NaiveDate::from_ymd_opt(1904, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|epoch| t.signed_duration_since(epoch).num_seconds())
+        .unwrap_or(0)
+}
+
+pub fn head(info: &FontInfo, metrics: &FontMetrics, long_loca: bool) -> Vec<u8> {
+    let imp = info.imp();
+    let revision = imp.version_major.borrow().unwrap_or(0) as f64
+        + imp.version_minor.borrow().unwrap_or(0) as f64 / 1000.;
+    let now = chrono::Utc::now().naive_utc();
+    let created = imp
+        .open_type_head_created
+        .borrow()
+        .as_ref()
+        .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y/%m/%d %H:%M:%S").ok())
+        .unwrap_or(now);
+    let flags = imp
+        .open_type_head_flags
+        .borrow()
+        .as_ref()
+        .map(|f| bits(f))
+        .unwrap_or(0b11) as u16;
+    let style = style_map_style(info);
+    let mac_style = u16::from(style.contains("bold")) | u16::from(style.contains("italic")) << 1;
+    let mut w = Writer::default();
+    w.u16(1)
+        .u16(0)
+        .fixed(revision)
+        .u32(0)
+        .u32(0x5F0F3CF5)
+        .u16(flags)
+        .u16(to_u16(info.units_per_em()))
+        .i64(long_date_time(created))
+        .i64(long_date_time(now))
+        .i16(metrics.x_min)
+        .i16(metrics.y_min)
+        .i16(metrics.x_max)
+        .i16(metrics.y_max)
+        .u16(mac_style)
+        .u16(imp.open_type_head_lowest_rec_ppem.borrow().unwrap_or(6) as u16)
+        .i16(2)
+        .i16(i16::from(long_loca))
+        .i16(0);
+    w.0
+}
+
+pub fn hhea(info: &FontInfo, metrics: &FontMetrics, number_of_h_metrics: u16) -> Vec<u8> {
+    let imp = info.imp();
+    let upm = info.units_per_em();
+    let italic_angle = info.italic_angle();
+    let (default_rise, default_run) = if italic_angle == 0. {
+        (1, 0)
+    } else {
+        (
+            upm.round() as i64,
+            (upm * (-italic_angle).to_radians().tan()).round() as i64,
+        )
+    };
+    let mut w = Writer::default();
+    w.u16(1)
+        .u16(0)
+        .i16(
+            imp.open_type_hhea_ascender
+                .borrow()
+                .map(|v| v as i16)
+                .unwrap_or_else(|| to_i16(info.ascender())),
+        )
+        .i16(
+            imp.open_type_hhea_descender
+                .borrow()
+                .map(|v| v as i16)
+                .unwrap_or_else(|| to_i16(info.descender())),
+        )
+        .i16(imp.open_type_hhea_line_gap.borrow().unwrap_or(0) as i16)
+        .u16(metrics.advance_width_max)
+        .i16(metrics.min_left_side_bearing)
+        .i16(metrics.min_right_side_bearing)
+        .i16(metrics.x_max_extent)
+        .i16(
+            imp.open_type_hhea_caret_slope_rise
+                .borrow()
+                .unwrap_or(default_rise) as i16,
+        )
+        .i16(
+            imp.open_type_hhea_caret_slope_run
+                .borrow()
+                .unwrap_or(default_run) as i16,
+        )
+        .i16(imp.open_type_hhea_caret_offset.borrow().unwrap_or(0) as i16)
+        .i16(0)
+        .i16(0)
+        .i16(0)
+        .i16(0)
+        .i16(0)
+        .u16(number_of_h_metrics);
+    w.0
+}
+
+pub fn hmtx(glyphs: &[GlyphMetrics]) -> Vec<u8> {
+    let mut w = Writer::default();
+    for g in glyphs {
+        w.u16(g.advance).i16(g.lsb());
+    }
+    w.0
+}
+
+/// Version 0.5 `maxp` table, for CFF outlines. TrueType outlines need version 1.0, which is
+/// built with the `glyf` table.
+pub fn maxp_cff(num_glyphs: u16) -> Vec<u8> {
+    let mut w = Writer::default();
+    w.u32(0x00005000).u16(num_glyphs);
+    w.0
+}
+
+/// Character to glyph mapping, with a format 4 subtable for the Basic Multilingual Plane and a
+/// format 12 subtable if there are characters outside it. Fails if the format 4 subtable would
+/// be too long for its 16-bit length field.
+pub fn cmap(glyphs: &[ExportGlyph]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut mapping = glyphs
+        .iter()
+        .enumerate()
+        .flat_map(|(gid, g)| g.unicode.iter().map(move |&c| (c as u32, gid as u16)))
+        .collect::<Vec<(u32, u16)>>();
+    mapping.sort_unstable();
+    mapping.dedup_by_key(|(c, _)| *c);
+
+    /* Runs of consecutive characters mapped to consecutive glyphs */
+    let mut groups: Vec<(u32, u32, u16)> = vec![];
+    for &(c, gid) in &mapping {
+        match groups.last_mut() {
+            Some((start, end, start_gid))
+                if *end + 1 == c && (*start_gid as u32 + (c - *start)) == gid as u32 =>
+            {
+                *end = c;
+            }
+            _ => groups.push((c, c, gid)),
+        }
+    }
+
+    let mut format4 = Writer::default();
+    {
+        let mut segments = groups
+            .iter()
+            .filter(|(start, _, _)| *start <= 0xFFFF)
+            .map(|&(start, end, gid)| {
+                let end = end.min(0xFFFE);
+                (start as u16, end as u16, gid.wrapping_sub(start as u16))
+            })
+            .collect::<Vec<(u16, u16, u16)>>();
+        segments.push((0xFFFF, 0xFFFF, 1));
+        let length = 16 + 8 * segments.len();
+        if length > u16::MAX as usize {
+            return Err(format!(
+                "The characters form {} ranges, more than the {} a format 4 cmap subtable can hold.",
+                segments.len(),
+                (u16::MAX as usize - 16) / 8
+            )
+            .into());
+        }
+        let seg_count = segments.len() as u16;
+        let (search_range, entry_selector, range_shift) = search_params(seg_count, 2);
+        format4
+            .u16(4)
+            .u16(length as u16)
+            .u16(0)
+            .u16(seg_count * 2)
+            .u16(search_range)
+            .u16(entry_selector)
+            .u16(range_shift);
+        for s in &segments {
+            format4.u16(s.1);
+        }
+        format4.u16(0);
+        for s in &segments {
+            format4.u16(s.0);
+        }
+        for s in &segments {
+            format4.u16(s.2);
+        }
+        for _ in &segments {
+            format4.u16(0);
+        }
+    }
+
+    let format12 = if mapping.iter().any(|(c, _)| *c > 0xFFFF) {
+        let mut w = Writer::default();
+        w.u16(12)
+            .u16(0)
+            .u32(16 + 12 * groups.len() as u32)
+            .u32(0)
+            .u32(groups.len() as u32);
+        for &(start, end, gid) in &groups {
+            w.u32(start).u32(end).u32(gid as u32);
+        }
+        Some(w.0)
+    } else {
+        None
+    };
+
+    /* (platform, encoding, subtable index) */
+    let records: &[(u16, u16, usize)] = if format12.is_some() {
+        &[(0, 3, 0), (0, 4, 1), (3, 1, 0), (3, 10, 1)]
+    } else {
+        &[(0, 3, 0), (3, 1, 0)]
+    };
+    let subtables = std::iter::once(format4.0)
+        .chain(format12)
+        .collect::<Vec<Vec<u8>>>();
+    let mut offsets = vec![];
+    let mut offset = 4 + 8 * records.len();
+    for s in &subtables {
+        offsets.push(offset as u32);
+        offset += s.len();
+    }
+    let mut w = Writer::default();
+    w.u16(0).u16(records.len() as u16);
+    for &(platform, encoding, i) in records {
+        w.u16(platform).u16(encoding).u32(offsets[i]);
+    }
+    for s in &subtables {
+        w.bytes(s);
+    }
+    Ok(w.0)
+}
+
+const RIBBI: [&str; 4] = ["regular", "italic", "bold", "bold italic"];
+
+/// The family name used for style linking (name ID 1).
+fn style_map_family_name(info: &FontInfo) -> String {
+    if let Some(name) = info.imp().style_map_family_name.borrow().clone() {
+        return name;
+    }
+    let family = family_name(info);
+    let style = info.style_name();
+    if style.is_empty() || RIBBI.contains(&style.to_lowercase().as_str()) {
+        family
+    } else {
+        format!("{} {}", family, style)
+    }
+}
+
+fn family_name(info: &FontInfo) -> String {
+    match info.family_name() {
+        s if s.is_empty() => "Untitled".to_string(),
+        s => s,
+    }
+}
+
+fn title_case(s: &str) -> String {
+    s.split(' ')
+        .map(|w| {
+            let mut c = w.chars();
+            c.next()
+                .map(|f| f.to_uppercase().chain(c).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The PostScript name: printable ASCII without spaces or PostScript delimiters, at most 63
+/// characters long.
+pub fn postscript_font_name(info: &FontInfo) -> String {
+    let name = info
+        .imp()
+        .postscript_font_name
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| {
+            let style = match info.style_name() {
+                s if s.is_empty() => "Regular".to_string(),
+                s => s,
+            };
+            format!("{}-{}", family_name(info), style)
+        });
+    name.chars()
+        .filter(|c| c.is_ascii_graphic() && !"[](){}<>/%".contains(*c))
+        .take(63)
+        .collect()
+}
+
+/// `Version major.minor` string, with the minor version as three digits.
+pub fn version_string(info: &FontInfo) -> String {
+    let imp = info.imp();
+    format!(
+        "{}.{:03}",
+        imp.version_major.borrow().unwrap_or(0),
+        imp.version_minor.borrow().unwrap_or(0)
+    )
+}
+
+/// Name records as `(name ID, string)`, for Windows Unicode English.
+fn default_names(info: &FontInfo) -> Vec<(u16, String)> {
+    let imp = info.imp();
+    let family = style_map_family_name(info);
+    let subfamily = title_case(&style_map_style(info));
+    let preferred_family = imp
+        .open_type_name_preferred_family_name
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| family_name(info));
+    let preferred_subfamily = imp
+        .open_type_name_preferred_subfamily_name
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| match info.style_name() {
+            s if s.is_empty() => "Regular".to_string(),
+            s => s,
+        });
+    let postscript_name = postscript_font_name(info);
+    let vendor = imp
+        .open_type_os2_vendor_id
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| "NONE".to_string());
+    let mut ret = vec![];
+    let mut push = |id: u16, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            ret.push((id, value));
+        }
+    };
+    push(0, imp.copyright.borrow().clone());
+    push(1, Some(family.clone()));
+    push(2, Some(subfamily.clone()));
+    push(
+        3,
+        Some(
+            imp.open_type_name_unique_id
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| {
+                    format!("{};{};{}", version_string(info), vendor, postscript_name)
+                }),
+        ),
+    );
+    push(
+        4,
+        Some(
+            imp.postscript_full_name
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| format!("{} {}", preferred_family, preferred_subfamily)),
+        ),
+    );
+    push(
+        5,
+        Some(
+            imp.open_type_name_version
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| format!("Version {}", version_string(info))),
+        ),
+    );
+    push(6, Some(postscript_name));
+    push(7, imp.trademark.borrow().clone());
+    push(8, imp.open_type_name_manufacturer.borrow().clone());
+    push(9, imp.open_type_name_designer.borrow().clone());
+    push(10, imp.open_type_name_description.borrow().clone());
+    push(11, imp.open_type_name_manufacturer_url.borrow().clone());
+    push(12, imp.open_type_name_designer_url.borrow().clone());
+    push(13, imp.open_type_name_license.borrow().clone());
+    push(14, imp.open_type_name_license_url.borrow().clone());
+    if preferred_family != family {
+        push(16, Some(preferred_family));
+    }
+    if preferred_subfamily != subfamily {
+        push(17, Some(preferred_subfamily));
+    }
+    push(18, imp.open_type_name_compatible_full_name.borrow().clone());
+    push(19, imp.open_type_name_sample_text.borrow().clone());
+    push(21, imp.open_type_name_wws_family_name.borrow().clone());
+    push(22, imp.open_type_name_wws_subfamily_name.borrow().clone());
+    ret
+}
+
+pub fn name(info: &FontInfo) -> Vec<u8> {
+    const WINDOWS_ENGLISH: (u16, u16, u16) = (3, 1, 0x409);
+    /* (platform, encoding, language, name ID) -> string */
+    let mut records = std::collections::BTreeMap::new();
+    for (id, value) in default_names(info) {
+        records.insert(
+            (WINDOWS_ENGLISH.0, WINDOWS_ENGLISH.1, WINDOWS_ENGLISH.2, id),
+            value,
+        );
+    }
+    for r in info.imp().open_type_name_records.borrow().iter().flatten() {
+        if let (Some(id), Some(platform), Some(encoding), Some(language), Some(string)) = (
+            r.name_id,
+            r.platform_id,
+            r.encoding_id,
+            r.language_id,
+            r.string.clone(),
+        ) {
+            records.insert(
+                (platform as u16, encoding as u16, language as u16, id as u16),
+                string,
+            );
+        }
+    }
+    let mut storage = Writer::default();
+    let mut w = Writer::default();
+    w.u16(0)
+        .u16(records.len() as u16)
+        .u16(6 + 12 * records.len() as u16);
+    for ((platform, encoding, language, id), string) in records {
+        let bytes = if platform == 1 {
+            string
+                .chars()
+                .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+                .collect::<Vec<u8>>()
+        } else {
+            string
+                .encode_utf16()
+                .flat_map(u16::to_be_bytes)
+                .collect::<Vec<u8>>()
+        };
+        w.u16(platform)
+            .u16(encoding)
+            .u16(language)
+            .u16(id)
+            .u16(bytes.len() as u16)
+            .u16(storage.len() as u16);
+        storage.bytes(&bytes);
+    }
+    w.bytes(&storage.0);
+    w.0
+}
+
+pub fn os2(info: &FontInfo, metrics: &FontMetrics, glyphs: &[ExportGlyph]) -> Vec<u8> {
+    let imp = info.imp();
+    let upm = info.units_per_em();
+    let get = |v: &std::cell::RefCell<Option<i64>>, default: f64| -> i16 {
+        v.borrow()
+            .map(|v| v as i16)
+            .unwrap_or_else(|| to_i16(default))
+    };
+    let chars = glyphs
+        .iter()
+        .flat_map(|g| g.unicode.iter().map(|&c| c as u32))
+        .collect::<Vec<u32>>();
+    let first_char = chars.iter().copied().min().unwrap_or(0).min(0xFFFF) as u16;
+    let last_char = chars.iter().copied().max().unwrap_or(0).min(0xFFFF) as u16;
+    let unicode_ranges = match imp.open_type_os2_unicode_ranges.borrow().as_ref() {
+        Some(list) => {
+            let mut ret = [0_u32; 4];
+            for &b in list.iter().filter(|&&b| (0..128).contains(&b)) {
+                ret[b as usize / 32] |= 1 << (b % 32);
+            }
+            ret
+        }
+        None => [
+            u32::from(chars.iter().any(|c| (0x20..0x7F).contains(c)))
+                | u32::from(chars.iter().any(|c| (0xA0..0x100).contains(c))) << 1,
+            0,
+            0,
+            0,
+        ],
+    };
+    let code_page_ranges = match imp.open_type_os2_code_page_ranges.borrow().as_ref() {
+        Some(list) => {
+            let mut ret = [0_u32; 2];
+            for &b in list.iter().filter(|&&b| (0..64).contains(&b)) {
+                ret[b as usize / 32] |= 1 << (b % 32);
+            }
+            ret
+        }
+        None => [1, 0],
+    };
+    let style = style_map_style(info);
+    let fs_selection = imp
+        .open_type_os2_selection
+        .borrow()
+        .as_ref()
+        .map(|s| bits(s))
+        .unwrap_or(0)
+        & !(1 | 1 << 5 | 1 << 6)
+        | u32::from(style.contains("italic"))
+        | u32::from(style.contains("bold")) << 5
+        | u32::from(style == "regular") << 6;
+    let typo_ascender = get(&imp.open_type_os2_typo_ascender, info.ascender());
+    let typo_descender = get(&imp.open_type_os2_typo_descender, info.descender());
+    let typo_line_gap = get(
+        &imp.open_type_os2_typo_line_gap,
+        (upm * 1.2 - typo_ascender as f64 + typo_descender as f64).max(0.),
+    );
+    let vendor = imp
+        .open_type_os2_vendor_id
+        .borrow()
+        .clone()
+        .unwrap_or_else(|| "NONE".to_string());
+    let mut vendor_id = [b' '; 4];
+    for (i, c) in vendor.bytes().filter(u8::is_ascii).take(4).enumerate() {
+        vendor_id[i] = c;
+    }
+    let mut panose = [0_u8; 10];
+    for (i, &v) in imp
+        .open_type_os2_panose
+        .borrow()
+        .iter()
+        .flatten()
+        .take(10)
+        .enumerate()
+    {
+        panose[i] = v as u8;
+    }
+    let family_class = imp
+        .open_type_os2_family_class
+        .borrow()
+        .as_ref()
+        .filter(|v| v.len() == 2)
+        .map(|v| (v[0] << 8 | v[1]) as i16)
+        .unwrap_or(0);
+    let underline_thickness = imp
+        .postscript_underline_thickness
+        .borrow()
+        .unwrap_or(upm * 0.05);
+
+    let mut w = Writer::default();
+    w.u16(4)
+        .i16(metrics.avg_char_width)
+        .u16(imp.open_type_os2_weight_class.borrow().unwrap_or(400) as u16)
+        .u16(imp.open_type_os2_width_class.borrow().unwrap_or(5) as u16)
+        .u16(
+            imp.open_type_os2_type
+                .borrow()
+                .as_ref()
+                .map(|t| bits(t))
+                .unwrap_or(1 << 2) as u16,
+        )
+        .i16(get(&imp.open_type_os2_subscript_x_size, upm * 0.65))
+        .i16(get(&imp.open_type_os2_subscript_y_size, upm * 0.6))
+        .i16(get(&imp.open_type_os2_subscript_x_offset, 0.))
+        .i16(get(&imp.open_type_os2_subscript_y_offset, upm * 0.075))
+        .i16(get(&imp.open_type_os2_superscript_x_size, upm * 0.65))
+        .i16(get(&imp.open_type_os2_superscript_y_size, upm * 0.6))
+        .i16(get(&imp.open_type_os2_superscript_x_offset, 0.))
+        .i16(get(&imp.open_type_os2_superscript_y_offset, upm * 0.35))
+        .i16(get(&imp.open_type_os2_strikeout_size, underline_thickness))
+        .i16(get(
+            &imp.open_type_os2_strikeout_position,
+            info.x_height() * 0.6,
+        ))
+        .i16(family_class)
+        .bytes(&panose);
+    for r in unicode_ranges {
+        w.u32(r);
+    }
+    w.bytes(&vendor_id)
+        .u16(fs_selection as u16)
+        .u16(first_char)
+        .u16(last_char)
+        .i16(typo_ascender)
+        .i16(typo_descender)
+        .i16(typo_line_gap)
+        .u16(
+            imp.open_type_os2_win_ascent
+                .borrow()
+                .map(|v| v as u16)
+                .unwrap_or_else(|| to_u16((metrics.y_max as f64).max(info.ascender()))),
+        )
+        .u16(
+            imp.open_type_os2_win_descent
+                .borrow()
+                .map(|v| v as u16)
+                .unwrap_or_else(|| to_u16((metrics.y_min as f64).min(info.descender()).abs())),
+        )
+        .u32(code_page_ranges[0])
+        .u32(code_page_ranges[1])
+        .i16(to_i16(info.x_height()))
+        .i16(to_i16(info.cap_height()))
+        .u16(0)
+        .u16(0x20)
+        .u16(0);
+    w.0
+}
+
+/// `post` table. Version 2.0 with `glyph_names` for TrueType outlines, version 3.0 without
+/// glyph names for CFF outlines, which store them in the `CFF ` table instead. Fails if there are
+/// more glyph names than the table can index.
+pub fn post(
+    info: &FontInfo,
+    glyph_names: Option<&[&str]>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let imp = info.imp();
+    let upm = info.units_per_em();
+    let mut w = Writer::default();
+    w.u32(if glyph_names.is_some() {
+        0x00020000
+    } else {
+        0x00030000
+    })
+    .fixed(info.italic_angle())
+    .i16(to_i16(
+        imp.postscript_underline_position
+            .borrow()
+            .unwrap_or(-upm * 0.075),
+    ))
+    .i16(to_i16(
+        imp.postscript_underline_thickness
+            .borrow()
+            .unwrap_or(upm * 0.05),
+    ))
+    .u32(u32::from(
+        imp.postscript_is_fixed_pitch.borrow().unwrap_or(false),
+    ))
+    .u32(0)
+    .u32(0)
+    .u32(0)
+    .u32(0);
+    if let Some(names) = glyph_names {
+        /* Indices below 258 refer to the standard Macintosh glyph names; every name is stored
+         * in the table instead. */
+        const FIRST_CUSTOM_INDEX: usize = 258;
+        let max_names = u16::MAX as usize + 1 - FIRST_CUSTOM_INDEX;
+        if names.len() > max_names {
+            return Err(format!(
+                "The post table can name at most {} glyphs, the font has {}.",
+                max_names,
+                names.len()
+            )
+            .into());
+        }
+        w.u16(names.len() as u16);
+        for i in 0..names.len() {
+            w.u16((FIRST_CUSTOM_INDEX + i) as u16);
+        }
+        for name in names {
+            let bytes = name
+                .bytes()
+                .filter(u8::is_ascii)
+                .take(255)
+                .collect::<Vec<u8>>();
+            w.u8(bytes.len() as u8).bytes(&bytes);
+        }
+    }
+    Ok(w.0)
+}
+
+#[test]
+fn test_search_params() {
+    assert_eq!(search_params(1, 16), (16, 0, 0));
+    assert_eq!(search_params(10, 16), (128, 3, 32));
+    assert_eq!(search_params(39, 2), (64, 5, 14));
+    assert_eq!(checksum(&[0, 0, 0, 1, 0, 0, 1]), 257);
+}
+
+#[test]
+fn test_table_limits() {
+    let info = FontInfo::new();
+    let names = vec!["a"; 65278];
+    assert!(post(&info, Some(&names)).is_ok());
+    let names = vec!["a"; 65279];
+    assert!(post(&info, Some(&names)).is_err());
+    assert!(post(&info, None).is_ok());
+
+    /* Every other character, so that each one is a range of its own. */
+    let glyph = |n: u32| ExportGlyph {
+        unicode: (0..n)
+            .filter_map(|i| char::from_u32(0x100 + 2 * i))
+            .collect(),
+        ..ExportGlyph::default()
+    };
+    assert!(cmap(&[ExportGlyph::default(), glyph(8188)]).is_ok());
+    assert!(cmap(&[ExportGlyph::default(), glyph(8189)]).is_err());
+}
@@ -0,0 +1,364 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! SVG files: one standalone document per glyph, and an SVG font that also works as a sprite
+//! sheet.
+//!
+//! Glyph documents are one font unit per pixel, as wide as the advance and as tall as the
+//! ascender to descender distance, with the baseline at `ascender` from the top.
+
+use super::*;
+use crate::utils::xml_escape;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Format a coordinate with at most three decimals.
+fn number(v: f64) -> String {
+    let v = (v * 1000.).round() / 1000.;
+    if v == v.trunc() {
+        format!("{}", v as i64)
+    } else {
+        format!("{}", v)
+    }
+}
+
+/// SVG path data of `outline`, with every point mapped through `transform`.
+pub fn path_data(outline: &Outline, transform: Transform) -> String {
+    let mut ret = String::new();
+    let mut push = |cmd: &str, points: &[Point]| {
+        if !ret.is_empty() {
+            ret.push(' ');
+        }
+        ret.push_str(cmd);
+        for p in points {
+            let (x, y) = apply(transform, *p);
+            let _ = write!(ret, " {} {}", number(x), number(y));
+        }
+    };
+    for c in &outline.contours {
+        push("M", &[c.start]);
+        for s in &c.segments {
+            match *s {
+                Segment::Line(p) => push("L", &[p]),
+                Segment::Quad(q, p) => push("Q", &[q, p]),
+                Segment::Cubic(c1, c2, p) => push("C", &[c1, c2, p]),
+            }
+        }
+        push("Z", &[]);
+    }
+    ret
+}
+
+/// The part of the em box shown for a glyph: `(x, width, height)` in font units, and the
+/// transformation from font coordinates to SVG coordinates.
+fn glyph_box(
+    info: &FontInfo,
+    glyph: &ExportGlyph,
+    outline: &Outline,
+) -> (f64, f64, f64, Transform) {
+    let (ascender, descender) = (info.ascender(), info.descender());
+    let (ascender, height) = if ascender > descender {
+        (ascender, ascender - descender)
+    } else {
+        (info.units_per_em(), info.units_per_em())
+    };
+    /* Marks usually have no advance and may sit left of the origin, so widen the box to show
+     * them. */
+    let (x0, x1) = match outline.bounds() {
+        Some((x0, _, x1, _)) => (x0.min(0.).floor(), x1.max(glyph.advance).ceil()),
+        None => (0., glyph.advance),
+    };
+    (x0, x1 - x0, height, [1., 0., 0., -1., 0., ascender])
+}
+
+/// `U+0041 U+00C0` list of the glyph's code points.
+fn code_points(glyph: &ExportGlyph) -> String {
+    glyph
+        .unicode
+        .iter()
+        .map(|c| format!("U+{:04X}", *c as u32))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A standalone SVG document of `glyph`, whose components have already been decomposed into
+/// `outline`.
+pub fn glyph_svg(info: &FontInfo, glyph: &ExportGlyph, outline: &Outline) -> String {
+    let (x, width, height, transform) = glyph_box(info, glyph, outline);
+    let mut ret = String::new();
+    ret.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        ret,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"{x} 0 {w} {h}\">",
+        x = number(x),
+        w = number(width),
+        h = number(height),
+    );
+    let _ = writeln!(
+        ret,
+        "  <title>{}</title>",
+        xml_escape(format!("{} {}", glyph.name, code_points(glyph)).trim_end())
+    );
+    if !outline.is_empty() {
+        let _ = writeln!(ret, "  <path d=\"{}\"/>", path_data(outline, transform));
+    }
+    ret.push_str("</svg>\n");
+    ret
+}
+
+/// `id` of a glyph's `<symbol>` in the SVG font. Characters that can't appear in an XML ID are
+/// written as `_xxxx_` with their hexadecimal code point, so distinct names get distinct IDs.
+pub fn symbol_id(name: &str) -> String {
+    let mut ret = String::from("glyph-");
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+            ret.push(c);
+        } else {
+            let _ = write!(ret, "_{:x}_", c as u32);
+        }
+    }
+    ret
+}
+
+/// An SVG font of `glyphs`, in the order given. The first glyph is used as the missing glyph if
+/// it's called `.notdef`.
+///
+/// Besides the `<font>` element, which maps code points to glyphs and gives their advances, every
+/// glyph is also defined as a `<symbol>` in the same coordinates as [`glyph_svg`], so the file can
+/// be used as a sprite sheet with `<use href="font.svg#glyph-A"/>`. See [`symbol_id`].
+pub fn svg_font(info: &FontInfo, glyphs: &[ExportGlyph]) -> String {
+    let by_name = glyphs
+        .iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<&str, &ExportGlyph>>();
+    let ascender = info.ascender();
+    let descender = info.descender();
+    let mut font = String::new();
+    let mut symbols = String::new();
+    let _ = writeln!(
+        font,
+        "    <font id=\"{}\" horiz-adv-x=\"{}\">",
+        xml_escape(&postscript_font_name(info)),
+        number(info.units_per_em() / 2.)
+    );
+    let _ = writeln!(
+        font,
+        "      <font-face font-family=\"{}\" units-per-em=\"{}\" ascent=\"{}\" descent=\"{}\" cap-height=\"{}\" x-height=\"{}\"/>",
+        xml_escape(&info.family_name()),
+        number(info.units_per_em()),
+        number(ascender),
+        number(descender),
+        number(info.cap_height()),
+        number(info.x_height()),
+    );
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let outline = glyph.decomposed(&by_name);
+        let d = match path_data(&outline, IDENTITY) {
+            d if d.is_empty() => String::new(),
+            d => format!(" d=\"{}\"", d),
+        };
+        let advance = number(glyph.advance);
+        let name = xml_escape(&glyph.name);
+        if i == 0 && glyph.name == ".notdef" {
+            let _ = writeln!(
+                font,
+                "      <missing-glyph horiz-adv-x=\"{}\"{}/>",
+                advance, d
+            );
+        } else if glyph.unicode.is_empty() {
+            let _ = writeln!(
+                font,
+                "      <glyph glyph-name=\"{}\" horiz-adv-x=\"{}\"{}/>",
+                name, advance, d
+            );
+        }
+        /* An SVG font glyph has a single `unicode` string, which is a ligature if it has more
+         * than one character, so glyphs with several code points are repeated. */
+        for c in &glyph.unicode {
+            let _ = writeln!(
+                font,
+                "      <glyph glyph-name=\"{}\" unicode=\"&#x{:X};\" horiz-adv-x=\"{}\"{}/>",
+                name, *c as u32, advance, d
+            );
+        }
+        let (x, width, height, transform) = glyph_box(info, glyph, &outline);
+        let _ = writeln!(
+            symbols,
+            "    <symbol id=\"{}\" viewBox=\"{} 0 {} {}\">",
+            symbol_id(&glyph.name),
+            number(x),
+            number(width),
+            number(height)
+        );
+        let _ = writeln!(
+            symbols,
+            "      <title>{}</title>",
+            xml_escape(format!("{} {}", glyph.name, code_points(glyph)).trim_end())
+        );
+        if !outline.is_empty() {
+            let _ = writeln!(
+                symbols,
+                "      <path d=\"{}\"/>",
+                path_data(&outline, transform)
+            );
+        }
+        symbols.push_str("    </symbol>\n");
+    }
+    font.push_str("    </font>\n");
+
+    let mut ret = String::new();
+    ret.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    ret.push_str("<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\">\n");
+    ret.push_str("  <defs>\n");
+    ret.push_str(&font);
+    ret.push_str(&symbols);
+    ret.push_str("  </defs>\n");
+    ret.push_str("</svg>\n");
+    ret
+}
+
+/// Write an SVG file for each glyph of `project`, or only for the glyphs named in `selection`,
+/// into `dir`, along with an SVG font of the same glyphs named after the PostScript font name.
+/// Returns the number of glyph files written.
+pub fn export_svg(
+    project: &Project,
+    dir: &Path,
+    selection: Option<&[String]>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)
+        .map_err(|err| format!("Could not create {}: {}", dir.display(), err))?;
+    let info = project.fontinfo();
+    let all = export_glyphs(project);
+    let by_name = all
+        .iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<&str, &ExportGlyph>>();
+    let glyphs = project.imp().glyphs.borrow();
+    let selected = all
+        .iter()
+        .filter(|g| glyphs.contains_key(&g.name))
+        .filter(|g| selection.map(|s| s.contains(&g.name)).unwrap_or(true))
+        .collect::<Vec<&ExportGlyph>>();
+
+    let font_file_name = format!("{}.svg", postscript_font_name(&info));
+    let mut used = HashSet::new();
+    used.insert(font_file_name.to_lowercase());
+    for glyph in &selected {
+        let file_name =
+            crate::utils::filenames::user_name_to_file_name(&glyph.name, &used, "", ".svg");
+        used.insert(file_name.to_lowercase());
+        let svg = glyph_svg(&info, glyph, &glyph.decomposed(&by_name));
+        std::fs::write(dir.join(&file_name), svg)
+            .map_err(|err| format!("Could not write {}: {}", file_name, err))?;
+    }
+
+    /* Keep the font's missing glyph even if it wasn't selected. */
+    let mut font_glyphs = Vec::with_capacity(selected.len() + 1);
+    if selected
+        .first()
+        .map(|g| g.name != ".notdef")
+        .unwrap_or(true)
+    {
+        font_glyphs.push(all[0].clone());
+    }
+    font_glyphs.extend(selected.iter().map(|g| (*g).clone()));
+    /* Components of the selected glyphs are decomposed, so their bases are only needed for
+     * that and aren't written as glyphs of their own. */
+    let outlines = font_glyphs
+        .iter()
+        .map(|g| ExportGlyph {
+            outline: g.decomposed(&by_name),
+            components: vec![],
+            ..g.clone()
+        })
+        .collect::<Vec<_>>();
+    std::fs::write(dir.join(&font_file_name), svg_font(&info, &outlines))
+        .map_err(|err| format!("Could not write {}: {}", font_file_name, err))?;
+    Ok(selected.len())
+}
+
+#[test]
+fn test_svg() {
+    let info = FontInfo::new();
+    *info.imp().family_name.borrow_mut() = Some("Test & Sans".to_string());
+    *info.imp().ascender.borrow_mut() = Some(800.);
+    *info.imp().descender.borrow_mut() = Some(-200.);
+
+    let bar = ExportGlyph {
+        name: "bar".to_string(),
+        unicode: vec!['|'],
+        advance: 200.,
+        outline: Outline {
+            contours: vec![PathContour {
+                start: (50., -100.),
+                segments: vec![
+                    Segment::Line((150., -100.)),
+                    Segment::Quad((150., 700.), (100., 700.)),
+                    Segment::Cubic((75., 700.), (50., 650.), (50., 600.)),
+                ],
+            }],
+        },
+        components: vec![],
+    };
+    let doubled = ExportGlyph {
+        name: "bar.double".to_string(),
+        unicode: vec!['\u{2016}', '\u{2225}'],
+        advance: 400.,
+        outline: Outline::default(),
+        components: vec![
+            ("bar".to_string(), IDENTITY),
+            ("bar".to_string(), [1., 0., 0., 1., 200.5, 0.]),
+        ],
+    };
+    let by_name = [&bar, &doubled]
+        .into_iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<&str, &ExportGlyph>>();
+
+    assert_eq!(
+        path_data(&bar.outline, IDENTITY),
+        "M 50 -100 L 150 -100 Q 150 700 100 700 C 75 700 50 650 50 600 Z"
+    );
+    let svg = glyph_svg(&info, &bar, &bar.outline);
+    assert!(svg.contains("width=\"200\" height=\"1000\" viewBox=\"0 0 200 1000\""));
+    assert!(svg.contains("<title>bar U+007C</title>"));
+    /* y is flipped and the baseline is 800 units from the top. */
+    assert!(svg.contains("<path d=\"M 50 900 L 150 900 Q 150 100 100 100 "));
+
+    let outline = doubled.decomposed(&by_name);
+    assert_eq!(outline.contours.len(), 2);
+    let svg = glyph_svg(&info, &doubled, &outline);
+    assert!(svg.contains("M 250.5 900 "));
+
+    let font = svg_font(&info, &[bar.clone(), doubled.clone()]);
+    assert!(font.contains(
+        "font-family=\"Test &amp; Sans\" units-per-em=\"1000\" ascent=\"800\" descent=\"-200\""
+    ));
+    assert!(font.contains(
+        "<glyph glyph-name=\"bar\" unicode=\"&#x7C;\" horiz-adv-x=\"200\" d=\"M 50 -100 "
+    ));
+    assert!(font.contains("<glyph glyph-name=\"bar.double\" unicode=\"&#x2016;\""));
+    assert!(font.contains("<glyph glyph-name=\"bar.double\" unicode=\"&#x2225;\""));
+    assert!(!font.contains("missing-glyph"));
+    assert!(font.contains("<symbol id=\"glyph-bar.double\" viewBox=\"0 0 400 1000\">"));
+    assert_eq!(symbol_id("A_b c"), "glyph-A_5f_b_20_c");
+}
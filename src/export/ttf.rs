@@ -0,0 +1,504 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! TrueType outlines: the `glyf`, `loca` and `maxp` tables.
+//!
+//! See <https://docs.microsoft.com/en-us/typography/opentype/spec/glyf>.
+
+use std::collections::HashMap;
+
+use super::sfnt::{self, to_i16, to_u16, FontMetrics, GlyphMetrics, Writer};
+use super::*;
+
+/// Maximum distance of the quadratic approximation of a cubic curve from the curve, in em units.
+pub const CUBIC_TOLERANCE_EM: f64 = 0.001;
+
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const ROUND_XY_TO_GRID: u16 = 0x0004;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// A contour as TrueType points `(x, y, on_curve)`.
+type TrueTypeContour = Vec<(i16, i16, bool)>;
+
+/// Convert `outline` to quadratic contours, approximating cubic curves within `tolerance`. The
+/// contours are reversed, since TrueType outlines run clockwise where PostScript outlines run
+/// counter-clockwise.
+fn quadratic_contours(outline: &Outline, tolerance: f64) -> Vec<TrueTypeContour> {
+    let point = |(x, y): Point, on_curve: bool| (to_i16(x), to_i16(y), on_curve);
+    let mut ret = vec![];
+    for contour in &outline.contours {
+        let mut points = vec![point(contour.start, true)];
+        let mut current = contour.start;
+        for segment in &contour.segments {
+            match *segment {
+                Segment::Line(p) => points.push(point(p, true)),
+                Segment::Quad(c, p) => {
+                    points.push(point(c, false));
+                    points.push(point(p, true));
+                }
+                Segment::Cubic(c1, c2, p) => {
+                    for (c, p) in cubic_to_quadratics(current, c1, c2, p, tolerance) {
+                        points.push(point(c, false));
+                        points.push(point(p, true));
+                    }
+                }
+            }
+            current = segment.end();
+        }
+        if points.len() > 1 && points.last() == points.first() {
+            points.pop();
+        }
+        points[1..].reverse();
+        ret.push(points);
+    }
+    ret
+}
+
+fn contour_bounds(contours: &[TrueTypeContour]) -> Option<(i16, i16, i16, i16)> {
+    let mut points = contours.iter().flatten();
+    let &(x, y, _) = points.next()?;
+    Some(points.fold((x, y, x, y), |(x0, y0, x1, y1), &(x, y, _)| {
+        (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+    }))
+}
+
+fn simple_glyph(contours: &[TrueTypeContour], bounds: (i16, i16, i16, i16)) -> Vec<u8> {
+    let mut w = Writer::default();
+    w.i16(contours.len() as i16)
+        .i16(bounds.0)
+        .i16(bounds.1)
+        .i16(bounds.2)
+        .i16(bounds.3);
+    let mut end = 0_usize;
+    for c in contours {
+        end += c.len();
+        w.u16((end - 1) as u16);
+    }
+    /* No instructions */
+    w.u16(0);
+    let mut flags = vec![];
+    let mut xs = Writer::default();
+    let mut ys = Writer::default();
+    let (mut prev_x, mut prev_y) = (0_i32, 0_i32);
+    for &(x, y, on_curve) in contours.iter().flatten() {
+        let mut flag = if on_curve { ON_CURVE_POINT } else { 0 };
+        for (delta, coords, short, same_or_positive) in [
+            (
+                x as i32 - prev_x,
+                &mut xs,
+                X_SHORT_VECTOR,
+                X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+            ),
+            (
+                y as i32 - prev_y,
+                &mut ys,
+                Y_SHORT_VECTOR,
+                Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+            ),
+        ] {
+            if delta == 0 {
+                flag |= same_or_positive;
+            } else if delta.abs() < 256 {
+                flag |= short;
+                if delta > 0 {
+                    flag |= same_or_positive;
+                }
+                coords.u8(delta.unsigned_abs() as u8);
+            } else {
+                coords.i16(delta as i16);
+            }
+        }
+        flags.push(flag);
+        prev_x = x as i32;
+        prev_y = y as i32;
+    }
+    w.bytes(&flags).bytes(&xs.0).bytes(&ys.0);
+    w.0
+}
+
+/// Whether `v` can be stored as a 2.14 fixed point number.
+fn fits_f2dot14(v: f64) -> bool {
+    (-2.0..2.0).contains(&v)
+}
+
+fn f2dot14(v: f64) -> i16 {
+    (v * 16384.).round() as i16
+}
+
+fn composite_glyph(components: &[(u16, Transform)], bounds: (i16, i16, i16, i16)) -> Vec<u8> {
+    let mut w = Writer::default();
+    w.i16(-1)
+        .i16(bounds.0)
+        .i16(bounds.1)
+        .i16(bounds.2)
+        .i16(bounds.3);
+    for (i, &(glyph_index, t)) in components.iter().enumerate() {
+        let (dx, dy) = (to_i16(t[4]), to_i16(t[5]));
+        let mut flags = ARGS_ARE_XY_VALUES | ROUND_XY_TO_GRID;
+        let words = i8::try_from(dx).is_err() || i8::try_from(dy).is_err();
+        if words {
+            flags |= ARG_1_AND_2_ARE_WORDS;
+        }
+        if i + 1 < components.len() {
+            flags |= MORE_COMPONENTS;
+        }
+        let scale: &[f64] = if t[1] != 0. || t[2] != 0. {
+            flags |= WE_HAVE_A_TWO_BY_TWO;
+            &t[0..4]
+        } else if t[0] != t[3] {
+            flags |= WE_HAVE_AN_X_AND_Y_SCALE;
+            &[t[0], t[3]]
+        } else if t[0] != 1. {
+            flags |= WE_HAVE_A_SCALE;
+            &t[0..1]
+        } else {
+            &[]
+        };
+        w.u16(flags).u16(glyph_index);
+        if words {
+            w.i16(dx).i16(dy);
+        } else {
+            w.u8(dx as i8 as u8).u8(dy as i8 as u8);
+        }
+        for &s in scale {
+            w.i16(f2dot14(s));
+        }
+    }
+    w.0
+}
+
+/// How deeply nested `glyph` is if it's written as a composite glyph: `Some(0)` for simple
+/// glyphs and `None` if it has to be decomposed, because it has both contours and components,
+/// because a base glyph is missing or a transformation can't be stored, or because its
+/// components refer back to it.
+fn composite_depth(
+    glyph: &ExportGlyph,
+    by_name: &HashMap<&str, &ExportGlyph>,
+    level: usize,
+) -> Option<usize> {
+    if glyph.components.is_empty() {
+        return Some(0);
+    }
+    if level >= MAX_COMPONENT_DEPTH || !glyph.outline.is_empty() {
+        return None;
+    }
+    let mut depth = 0;
+    for (base, t) in &glyph.components {
+        if !t[..4].iter().all(|&s| fits_f2dot14(s)) {
+            return None;
+        }
+        let base = by_name.get(base.as_str())?;
+        depth = depth.max(composite_depth(base, by_name, level + 1)?);
+    }
+    Some(depth + 1)
+}
+
+/// Build a TrueType font out of `glyphs`, whose first glyph must be `.notdef`.
+pub fn compile_ttf(
+    info: &FontInfo,
+    glyphs: &[ExportGlyph],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if glyphs.len() > u16::MAX as usize {
+        return Err(format!(
+            "A font can have at most {} glyphs, the project has {}.",
+            u16::MAX,
+            glyphs.len()
+        )
+        .into());
+    }
+    let tolerance = info.units_per_em() * CUBIC_TOLERANCE_EM;
+    let by_name = glyphs
+        .iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<&str, &ExportGlyph>>();
+    let glyph_index = glyphs
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.name.as_str(), i as u16))
+        .collect::<HashMap<&str, u16>>();
+
+    let mut glyf = Writer::default();
+    let mut loca = vec![];
+    let mut metrics = Vec::with_capacity(glyphs.len());
+    /* maxp statistics */
+    let (mut max_points, mut max_contours) = (0, 0);
+    let (mut max_composite_points, mut max_composite_contours) = (0, 0);
+    let (mut max_component_elements, mut max_component_depth) = (0, 0);
+    for glyph in glyphs {
+        loca.push(glyf.len());
+        let depth = composite_depth(glyph, &by_name, 0);
+        let outline = if glyph.components.is_empty() {
+            glyph.outline.clone()
+        } else {
+            glyph.decomposed(&by_name)
+        };
+        let contours = quadratic_contours(&outline, tolerance);
+        let bounds = contour_bounds(&contours);
+        let num_points = contours.iter().map(Vec::len).sum::<usize>();
+        metrics.push(GlyphMetrics {
+            advance: to_u16(glyph.advance),
+            bounds,
+        });
+        let bounds = match bounds {
+            Some(b) => b,
+            None => continue,
+        };
+        match depth {
+            Some(depth) if depth > 0 => {
+                let components = glyph
+                    .components
+                    .iter()
+                    .map(|(base, t)| (glyph_index[base.as_str()], *t))
+                    .collect::<Vec<_>>();
+                glyf.bytes(&composite_glyph(&components, bounds));
+                max_composite_points = max_composite_points.max(num_points);
+                max_composite_contours = max_composite_contours.max(contours.len());
+                max_component_elements = max_component_elements.max(components.len());
+                max_component_depth = max_component_depth.max(depth);
+            }
+            _ => {
+                glyf.bytes(&simple_glyph(&contours, bounds));
+                max_points = max_points.max(num_points);
+                max_contours = max_contours.max(contours.len());
+            }
+        }
+        glyf.align(2);
+    }
+    loca.push(glyf.len());
+
+    let long_loca = *loca.last().unwrap() > 2 * u16::MAX as usize;
+    let mut loca_table = Writer::default();
+    for &offset in &loca {
+        if long_loca {
+            loca_table.u32(offset as u32);
+        } else {
+            loca_table.u16((offset / 2) as u16);
+        }
+    }
+
+    let mut maxp = Writer::default();
+    maxp.u32(0x00010000)
+        .u16(glyphs.len() as u16)
+        .u16(max_points as u16)
+        .u16(max_contours as u16)
+        .u16(max_composite_points as u16)
+        .u16(max_composite_contours as u16)
+        /* maxZones, maxTwilightPoints, maxStorage, maxFunctionDefs, maxInstructionDefs,
+         * maxStackElements, maxSizeOfInstructions */
+        .u16(2)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(max_component_elements as u16)
+        .u16(max_component_depth as u16);
+
+    let font_metrics = FontMetrics::new(&metrics);
+    let glyph_names = glyphs.iter().map(|g| g.name.as_str()).collect::<Vec<_>>();
+    Ok(sfnt::assemble(
+        0x00010000,
+        vec![
+            (*b"head", sfnt::head(info, &font_metrics, long_loca)),
+            (
+                *b"hhea",
+                sfnt::hhea(info, &font_metrics, glyphs.len() as u16),
+            ),
+            (*b"maxp", maxp.0),
+            (*b"OS/2", sfnt::os2(info, &font_metrics, glyphs)),
+            (*b"hmtx", sfnt::hmtx(&metrics)),
+            (*b"cmap", sfnt::cmap(glyphs)?),
+            (*b"loca", loca_table.0),
+            (*b"glyf", glyf.0),
+            (*b"name", sfnt::name(info)),
+            (*b"post", sfnt::post(info, Some(&glyph_names))?),
+        ],
+    ))
+}
+
+/// Compile `project` to a TrueType font file.
+pub fn export_ttf(project: &Project) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    compile_ttf(&project.fontinfo(), &export_glyphs(project))
+}
+
+#[test]
+fn test_compile_ttf() {
+    use glib::subclass::prelude::*;
+
+    let info = FontInfo::new();
+    *info.imp().family_name.borrow_mut() = Some("Test Sans".to_string());
+    *info.imp().style_name.borrow_mut() = Some("Regular".to_string());
+    *info.imp().version_major.borrow_mut() = Some(2);
+    *info.imp().version_minor.borrow_mut() = Some(5);
+
+    let o = ExportGlyph {
+        name: "o".to_string(),
+        unicode: vec!['o'],
+        advance: 500.,
+        outline: Outline {
+            contours: vec![PathContour {
+                start: (250., 0.),
+                segments: vec![
+                    Segment::Cubic((388., 0.), (500., 112.), (500., 250.)),
+                    Segment::Cubic((500., 388.), (388., 500.), (250., 500.)),
+                    Segment::Cubic((112., 500.), (0., 388.), (0., 250.)),
+                    Segment::Cubic((0., 112.), (112., 0.), (250., 0.)),
+                ],
+            }],
+        },
+        components: vec![],
+    };
+    let degree = ExportGlyph {
+        name: "degree".to_string(),
+        unicode: vec!['°'],
+        advance: 300.,
+        outline: Outline::default(),
+        components: vec![("o".to_string(), [0.5, 0., 0., 0.5, 25., 450.])],
+    };
+    let emoji = ExportGlyph {
+        name: "u1F600".to_string(),
+        unicode: vec!['\u{1F600}'],
+        advance: 1000.,
+        outline: Outline::default(),
+        /* Scales that don't fit a composite glyph, decomposed instead. */
+        components: vec![("o".to_string(), [2., 0., 0., 2., 0., 0.])],
+    };
+    let space = ExportGlyph {
+        name: "space".to_string(),
+        unicode: vec![' '],
+        advance: 250.,
+        ..ExportGlyph::default()
+    };
+    let glyphs = vec![ExportGlyph::notdef(&info), space, o, degree, emoji];
+    let data = compile_ttf(&info, &glyphs).unwrap();
+
+    let face = ttf_parser::Face::from_slice(&data, 0).unwrap();
+    assert_eq!(face.number_of_glyphs(), 5);
+    assert_eq!(face.units_per_em(), 1000);
+    assert_eq!(face.ascender(), 700);
+    assert_eq!(face.descender(), -200);
+    let family = face
+        .names()
+        .into_iter()
+        .find(|n| n.name_id == ttf_parser::name_id::FAMILY)
+        .and_then(|n| n.to_string());
+    assert_eq!(family.as_deref(), Some("Test Sans"));
+    let version = face
+        .names()
+        .into_iter()
+        .find(|n| n.name_id == ttf_parser::name_id::VERSION)
+        .and_then(|n| n.to_string());
+    assert_eq!(version.as_deref(), Some("Version 2.005"));
+
+    struct Counter(usize);
+    impl ttf_parser::OutlineBuilder for Counter {
+        fn move_to(&mut self, _: f32, _: f32) {}
+        fn line_to(&mut self, _: f32, _: f32) {
+            self.0 += 1;
+        }
+        fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {
+            self.0 += 1;
+        }
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {
+            self.0 += 1;
+        }
+        fn close(&mut self) {}
+    }
+    /* Twice the signed area of the polygon of the end points, negative if the contours run
+     * clockwise. */
+    #[derive(Default)]
+    struct Area {
+        start: (f32, f32),
+        last: (f32, f32),
+        sum: f32,
+    }
+    impl Area {
+        fn add(&mut self, x: f32, y: f32) {
+            self.sum += self.last.0 * y - x * self.last.1;
+            self.last = (x, y);
+        }
+    }
+    impl ttf_parser::OutlineBuilder for Area {
+        fn move_to(&mut self, x: f32, y: f32) {
+            self.start = (x, y);
+            self.last = (x, y);
+        }
+        fn line_to(&mut self, x: f32, y: f32) {
+            self.add(x, y);
+        }
+        fn quad_to(&mut self, _: f32, _: f32, x: f32, y: f32) {
+            self.add(x, y);
+        }
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, x: f32, y: f32) {
+            self.add(x, y);
+        }
+        fn close(&mut self) {
+            let (x, y) = self.start;
+            self.add(x, y);
+        }
+    }
+    /* TrueType outer contours run clockwise, counters counter-clockwise. */
+    for id in [0, 2] {
+        let mut area = Area::default();
+        face.outline_glyph(ttf_parser::GlyphId(id), &mut area);
+        assert!(area.sum < 0., "{}: {}", id, area.sum);
+    }
+
+    for (c, gid, advance, bbox) in [
+        (' ', 1, 250, None),
+        ('o', 2, 500, Some((0, 0, 500, 500))),
+        ('°', 3, 300, Some((25, 450, 275, 700))),
+        ('\u{1F600}', 4, 1000, Some((0, 0, 1000, 1000))),
+    ] {
+        let id = face.glyph_index(c).unwrap();
+        assert_eq!(id.0, gid);
+        assert_eq!(face.glyph_hor_advance(id), Some(advance));
+        assert_eq!(
+            face.glyph_name(id),
+            Some(glyphs[gid as usize].name.as_str())
+        );
+        let mut counter = Counter(0);
+        let rect = face.outline_glyph(id, &mut counter);
+        assert_eq!(rect.is_some(), bbox.is_some(), "{:?}", c);
+        if let (Some(r), Some((x0, y0, x1, y1))) = (rect, bbox) {
+            /* Off-curve points may lie slightly outside the curve. */
+            for (a, b) in [(r.x_min, x0), (r.y_min, y0), (r.x_max, x1), (r.y_max, y1)] {
+                assert!((a - b).abs() <= 2, "{:?}: {:?}", c, r);
+            }
+            assert!(counter.0 >= 4);
+        }
+    }
+    assert!(face.glyph_index('x').is_none());
+    assert!(face
+        .outline_glyph(ttf_parser::GlyphId(0), &mut Counter(0))
+        .is_some());
+}
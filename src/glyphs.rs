@@ -25,21 +25,32 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::rc::{Rc, Weak};
 
+use crate::project::{LoadError, LoadErrorKind, LoadReport};
 use crate::unicode::names::CharName;
 use crate::utils::curves::*;
+use crate::utils::plist::DictValue;
 
-use gtk::cairo::{Context, Matrix};
+#[cfg(feature = "cairo")]
+use cairo::{Context, Matrix};
 
-use gtk::subclass::prelude::*;
+use glib::subclass::prelude::*;
+
+mod anchors;
+pub use anchors::*;
 
 mod guidelines;
 pub use guidelines::*;
 
-mod glif;
+pub mod glif;
 
+mod boolean;
+pub use boolean::*;
 mod contours;
 pub use contours::*;
 
+/// Components nested deeper than this are ignored.
+pub const MAX_COMPONENT_DEPTH: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct Component {
     base_name: String,
@@ -52,6 +63,30 @@ pub struct Component {
     y_scale: f64,
 }
 
+impl Component {
+    pub fn base_name(&self) -> &str {
+        &self.base_name
+    }
+
+    /// The base glyph, if it has been linked and is still part of the project.
+    pub fn base(&self) -> Option<Rc<RefCell<Glyph>>> {
+        self.base.upgrade()
+    }
+
+    /// The component's affine transformation as `[xScale, xyScale, yxScale, yScale, xOffset,
+    /// yOffset]`, in the order of the GLIF attributes.
+    pub fn transform(&self) -> [f64; 6] {
+        [
+            self.x_scale,
+            self.xy_scale,
+            self.yx_scale,
+            self.y_scale,
+            self.x_offset,
+            self.y_offset,
+        ]
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum GlyphKind {
     Char(char),
@@ -63,11 +98,22 @@ pub struct Glyph {
     pub name: Cow<'static, str>,
     pub name2: Option<crate::unicode::names::Name>,
     pub kind: GlyphKind,
+    /// All code points mapped to this glyph, in the order they appear in the `.glif` file.
+    pub unicode: Vec<char>,
     pub width: Option<f64>,
     pub contours: Vec<Contour>,
     pub components: Vec<Component>,
     pub guidelines: Vec<Guideline>,
+    pub anchors: Vec<Anchor>,
+    /// The `<lib>` dictionary of the `.glif` file, where applications store their own data.
+    pub lib: HashMap<String, DictValue>,
     pub glif_source: String,
+    /// Name of the `.glif` file inside the glyphs directory, if the glyph has been loaded from or
+    /// saved to disk.
+    pub filename: Option<String>,
+    /// Whether the glyph has been edited since it was loaded. Unmodified glyphs are written back
+    /// using `glif_source` verbatim.
+    pub modified: bool,
 }
 
 impl Ord for Glyph {
@@ -107,6 +153,7 @@ impl Default for Glyph {
     }
 }
 
+#[cfg(feature = "cairo")]
 #[derive(Clone, Copy)]
 pub struct GlyphDrawingOptions {
     pub outline: (f64, f64, f64, f64),
@@ -117,6 +164,7 @@ pub struct GlyphDrawingOptions {
     pub line_width: f64,
 }
 
+#[cfg(feature = "cairo")]
 impl Default for GlyphDrawingOptions {
     fn default() -> Self {
         Self {
@@ -133,84 +181,376 @@ impl Default for GlyphDrawingOptions {
 impl Glyph {
     pub fn from_ufo(
         path: &str,
-    ) -> Result<HashMap<String, Rc<RefCell<Glyph>>>, Box<dyn std::error::Error>> {
-        use std::path::Path;
-
+        report: &mut LoadReport,
+    ) -> Result<HashMap<String, Rc<RefCell<Glyph>>>, LoadError> {
         //assert!(path.ends_with(".ufo"));
-        let mut ret: HashMap<String, Rc<RefCell<Glyph>>> = HashMap::default();
+        Self::from_glyphs_dir(&std::path::Path::new(path).join("glyphs"), report)
+    }
 
-        let mut glyphs_with_refs: Vec<Rc<_>> = vec![];
-        let path = Path::new(path);
-        let path = path.join("glyphs");
+    /// Load the glyph set directory `path`, i.e. the directory of a single layer, using its
+    /// `contents.plist` to map glyph names to `.glif` files. Missing, unlisted or unparseable
+    /// files don't stop loading; they are recorded in `report` instead.
+    pub fn from_glyphs_dir(
+        path: &std::path::Path,
+        report: &mut LoadReport,
+    ) -> Result<HashMap<String, Rc<RefCell<Glyph>>>, LoadError> {
+        use crate::utils::plist::Plist;
 
-        for entry in path
+        let mut ret: HashMap<String, Rc<RefCell<Glyph>>> = HashMap::default();
+
+        let glif_files = path
             .read_dir()
-            .map_err(|err| format!("Reading directory {} failed: {}", path.display(), err))?
+            .map_err(|err| LoadError::new(path, LoadErrorKind::Io(err)))?
             .flatten()
-        {
-            use std::fs::File;
-            use std::io::prelude::*;
-            let mut file = match File::open(&entry.path()) {
-                Err(err) => {
-                    return Err(format!("Couldn't open {}: {}", entry.path().display(), err).into())
+            .filter_map(|e| e.file_name().to_str().map(str::to_string))
+            .filter(|f| f.ends_with(".glif"))
+            .collect::<std::collections::BTreeSet<String>>();
+        let contents_path = path.join("contents.plist");
+        let contents: Vec<(String, String)> = if contents_path.exists() {
+            let s = std::fs::read_to_string(&contents_path)
+                .map_err(|err| LoadError::new(&contents_path, LoadErrorKind::Io(err)))?;
+            let plist = Plist::from_str(&s)
+                .map_err(|err| LoadError::new(&contents_path, LoadErrorKind::Plist(err)))?;
+            let mut contents = vec![];
+            for (name, file_name) in plist.dict {
+                match file_name {
+                    DictValue::String(file_name) => contents.push((name, file_name)),
+                    other => {
+                        let error = LoadError::invalid(
+                            &contents_path,
+                            format!("File name of glyph {:?} is not a string: {:?}", name, other),
+                        );
+                        report.warn_glyph(&name, true, error);
+                    }
                 }
-                Ok(file) => file,
-            };
+            }
+            contents.sort();
+            contents
+        } else {
+            report.warn(LoadError::invalid(
+                &contents_path,
+                "File does not exist, glyph names were read from the .glif files instead."
+                    .to_string(),
+            ));
+            glif_files
+                .iter()
+                .map(|f| (String::new(), f.clone()))
+                .collect()
+        };
+        let listed = contents
+            .iter()
+            .map(|(_, file_name)| file_name.as_str())
+            .collect::<std::collections::HashSet<&str>>();
+        for f in glif_files.iter().filter(|f| !listed.contains(f.as_str())) {
+            report.warn(LoadError::invalid(
+                &path.join(f),
+                "File is not listed in contents.plist and was ignored.".to_string(),
+            ));
+        }
 
-            let mut s = String::new();
-            if let Err(err) = file.read_to_string(&mut s) {
-                return Err(format!("Couldn't read {}: {}", entry.path().display(), err).into());
+        for (name, file_name) in contents {
+            let file_path = path.join(&file_name);
+            /* Without contents.plist, the file name stands in for the glyph name in reports. */
+            let report_name = if name.is_empty() { &file_name } else { &name };
+            if !glif_files.contains(&file_name) {
+                report.warn_glyph(
+                    report_name,
+                    true,
+                    LoadError::invalid(
+                        &file_path,
+                        format!(
+                            "File of glyph {:?}, listed in contents.plist, does not exist.",
+                            name
+                        ),
+                    ),
+                );
+                continue;
             }
-            let g: Result<glif::Glif, _> = glif::Glif::from_str(&s);
-            match g {
+            let s = match std::fs::read_to_string(&file_path) {
+                Ok(s) => s,
                 Err(err) => {
-                    eprintln!("couldn't parse {}: {}", entry.path().display(), err);
+                    report.warn_glyph(
+                        report_name,
+                        true,
+                        LoadError::new(&file_path, LoadErrorKind::Io(err)),
+                    );
+                    continue;
                 }
-                Ok(g) => {
-                    for mut g in g.into_iter() {
+            };
+            match glif::Glif::from_str(&s)
+                .and_then(|g| g.into_iter().collect::<Result<Vec<Glyph>, _>>())
+            {
+                Err(err) => {
+                    report.warn_glyph(
+                        report_name,
+                        true,
+                        LoadError::new(&file_path, LoadErrorKind::Glif(err)),
+                    );
+                }
+                Ok(glyphs) => {
+                    for mut g in glyphs {
+                        if name.is_empty() {
+                            /* No contents.plist, use the name in the .glif file. */
+                        } else if g.name != name {
+                            report.warn_glyph(
+                                &name,
+                                false,
+                                LoadError::invalid(
+                                    &file_path,
+                                    format!("Glyph {:?} is named {:?} in its file.", name, g.name),
+                                ),
+                            );
+                            g.name = name.clone().into();
+                        }
                         g.glif_source = s.clone();
-                        let has_components = !g.components.is_empty();
+                        g.filename = Some(file_name.clone());
                         let name = g.name.clone();
-                        let g = Rc::new(RefCell::new(g));
-                        if has_components {
-                            glyphs_with_refs.push(g.clone());
-                        }
-                        ret.insert(name.into(), g);
+                        ret.insert(name.into(), Rc::new(RefCell::new(g)));
                     }
                 }
             }
         }
 
-        for g in glyphs_with_refs {
+        Self::link_components(&ret);
+        Ok(ret)
+    }
+
+    /// Resolve the base glyph of every component in `glyphs` by name. Components whose base glyph
+    /// is missing are left without one.
+    pub fn link_components(glyphs: &HashMap<String, Rc<RefCell<Glyph>>>) {
+        for g in glyphs.values() {
             let mut deref = g.borrow_mut();
             for c in deref.components.iter_mut() {
-                if let Some(o) = ret.get(&c.base_name) {
-                    c.base = Rc::downgrade(o);
+                c.base = glyphs
+                    .get(&c.base_name)
+                    .map(Rc::downgrade)
+                    .unwrap_or_default();
+            }
+        }
+    }
+
+    /// Make components that use glyph `old_name` as their base use `new_name` instead. Returns
+    /// whether any component changed.
+    pub fn rename_component_base(&mut self, old_name: &str, new_name: &str) -> bool {
+        let mut ret = false;
+        for c in self.components.iter_mut() {
+            if c.base_name == old_name {
+                c.base_name = new_name.to_string();
+                ret = true;
+            }
+        }
+        ret
+    }
+
+    /// Make components use the base glyphs that `names` maps their current bases to. Unlike
+    /// repeated calls to [`Glyph::rename_component_base`], all names are replaced at once, so
+    /// chains such as `a` → `a.001` → `a.002` are not followed. Returns whether any component
+    /// changed.
+    pub fn rename_component_bases(&mut self, names: &HashMap<String, String>) -> bool {
+        let mut ret = false;
+        for c in self.components.iter_mut() {
+            if let Some(new_name) = names.get(&c.base_name) {
+                c.base_name = new_name.clone();
+                ret = true;
+            }
+        }
+        ret
+    }
+
+    /// Scale outlines, advance width, component offsets, anchors and guidelines by `factor`, for
+    /// example to move the glyph to a font with different units per em.
+    pub fn scale(&mut self, factor: f64) {
+        let scale = |v: i64| (v as f64 * factor).round() as i64;
+        for contour in self.contours.iter() {
+            for curve in contour.curves().borrow().iter() {
+                for p in curve.points().borrow_mut().iter_mut() {
+                    *p = (scale(p.0), scale(p.1));
                 }
             }
         }
-        Ok(ret)
+        self.width = self.width.map(|w| w * factor);
+        for c in self.components.iter_mut() {
+            c.x_offset *= factor;
+            c.y_offset *= factor;
+        }
+        for a in self.anchors.iter() {
+            *a.imp().x.borrow_mut() *= factor;
+            *a.imp().y.borrow_mut() *= factor;
+        }
+        for g in self.guidelines.iter() {
+            let (x, y) = (g.x(), g.y());
+            *g.imp().x.borrow_mut() = scale(x);
+            *g.imp().y.borrow_mut() = scale(y);
+        }
+        self.modified = true;
+    }
+
+    /// Copy of this glyph under a new name and with no code points. Unlike `clone()`, contours and
+    /// guidelines are not shared with the original.
+    pub fn duplicate(&self, name: String) -> Self {
+        let contours = self.contours.iter().map(Contour::duplicate).collect();
+        let guidelines = self
+            .guidelines
+            .iter()
+            .map(|g| {
+                Guideline::builder()
+                    .name(g.name())
+                    .identifier(g.identifier())
+                    .color(g.imp().color.borrow().clone())
+                    .angle(g.angle())
+                    .x(g.x())
+                    .y(g.y())
+                    .build()
+            })
+            .collect();
+        let anchors = self.anchors.iter().map(Anchor::duplicate).collect();
+        Glyph {
+            name: name.into(),
+            name2: None,
+            kind: GlyphKind::Component,
+            unicode: vec![],
+            contours,
+            guidelines,
+            anchors,
+            glif_source: String::new(),
+            filename: None,
+            modified: true,
+            ..self.clone()
+        }
     }
 
-    pub fn new(name: &'static str, char: char, curves: Vec<Bezier>) -> Self {
+    pub fn new(name: impl Into<Cow<'static, str>>, char: char, curves: Vec<Bezier>) -> Self {
         let contour = Contour::new();
         *contour.imp().curves.borrow_mut() = curves;
         Glyph {
             name: name.into(),
             name2: char.char_name(),
             kind: GlyphKind::Char(char),
+            unicode: vec![char],
             contours: vec![contour],
             components: vec![],
             guidelines: vec![],
+            anchors: vec![],
+            lib: HashMap::default(),
             width: None,
             glif_source: String::new(),
+            filename: None,
+            modified: false,
         }
     }
 
-    pub fn new_empty(name: &'static str, char: char) -> Self {
+    pub fn new_empty(name: impl Into<Cow<'static, str>>, char: char) -> Self {
         Glyph::new(name, char, vec![])
     }
 
+    /// Default glyph name for `c`: the Adobe Glyph List name for ASCII characters, `uniXXXX`
+    /// otherwise.
+    pub fn default_name(c: char) -> String {
+        const ASCII_NAMES: [&str; 95] = [
+            "space",
+            "exclam",
+            "quotedbl",
+            "numbersign",
+            "dollar",
+            "percent",
+            "ampersand",
+            "quotesingle",
+            "parenleft",
+            "parenright",
+            "asterisk",
+            "plus",
+            "comma",
+            "hyphen",
+            "period",
+            "slash",
+            "zero",
+            "one",
+            "two",
+            "three",
+            "four",
+            "five",
+            "six",
+            "seven",
+            "eight",
+            "nine",
+            "colon",
+            "semicolon",
+            "less",
+            "equal",
+            "greater",
+            "question",
+            "at",
+            "A",
+            "B",
+            "C",
+            "D",
+            "E",
+            "F",
+            "G",
+            "H",
+            "I",
+            "J",
+            "K",
+            "L",
+            "M",
+            "N",
+            "O",
+            "P",
+            "Q",
+            "R",
+            "S",
+            "T",
+            "U",
+            "V",
+            "W",
+            "X",
+            "Y",
+            "Z",
+            "bracketleft",
+            "backslash",
+            "bracketright",
+            "asciicircum",
+            "underscore",
+            "grave",
+            "a",
+            "b",
+            "c",
+            "d",
+            "e",
+            "f",
+            "g",
+            "h",
+            "i",
+            "j",
+            "k",
+            "l",
+            "m",
+            "n",
+            "o",
+            "p",
+            "q",
+            "r",
+            "s",
+            "t",
+            "u",
+            "v",
+            "w",
+            "x",
+            "y",
+            "z",
+            "braceleft",
+            "bar",
+            "braceright",
+            "asciitilde",
+        ];
+        match c as u32 {
+            n @ 0x20..=0x7E => ASCII_NAMES[(n - 0x20) as usize].to_string(),
+            n if n <= 0xFFFF => format!("uni{:04X}", n),
+            n => format!("u{:05X}", n),
+        }
+    }
+
+    #[cfg(feature = "cairo")]
     pub fn draw(&self, cr: &Context, options: GlyphDrawingOptions) {
         if self.is_empty() {
             return;
@@ -453,24 +793,59 @@ impl Glyph {
         }
     }
 
-    #[cfg(feature = "svg")]
-    pub fn save_to_svg<P: AsRef<std::path::Path>>(
-        &self,
-        path: P,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let surface = gtk::cairo::SvgSurface::new(self.width.unwrap_or(500.0), 1000., Some(path))?;
-        let ctx = gtk::cairo::Context::new(&surface)?;
+    /// Exact bounding box `(x_min, y_min, x_max, y_max)` of the glyph's contours and components,
+    /// or `None` if it has no points.
+    pub fn bbox(&self) -> Option<(f64, f64, f64, f64)> {
+        self.transformed_bbox([1., 0., 0., 1., 0., 0.], 0)
+    }
 
-        let options = GlyphDrawingOptions {
-            outline: (0., 0., 0., 1.),
-            inner_fill: None,
-            highlight: None,
-            ..Default::default()
-        };
-        self.draw(&ctx, options);
-        surface.flush();
-        surface.finish();
-        Ok(())
+    /// The bounding box of the glyph after applying `transform`, given in the order of
+    /// [`Component::transform`]. Curves are transformed before their extrema are found, so the
+    /// box stays exact under rotation and skew. `depth` is how deeply this glyph is nested as a
+    /// component; cyclic references stop at [`MAX_COMPONENT_DEPTH`].
+    fn transformed_bbox(&self, transform: [f64; 6], depth: usize) -> Option<(f64, f64, f64, f64)> {
+        let [xx, xy, yx, yy, dx, dy] = transform;
+        let mut ret = None;
+        for curve in self
+            .contours
+            .iter()
+            .flat_map(|c| c.curves().borrow().clone())
+        {
+            let points = curve
+                .float_points()
+                .into_iter()
+                .map(|(x, y)| (xx * x + yx * y + dx, xy * x + yy * y + dy))
+                .collect::<Vec<FloatPoint>>();
+            if !points.is_empty() {
+                ret = union_bbox(ret, Some(bounding_box(&points)));
+            }
+        }
+        if depth >= MAX_COMPONENT_DEPTH {
+            return ret;
+        }
+        for component in self.components.iter() {
+            if let Some(base) = component.base.upgrade() {
+                let [cxx, cxy, cyx, cyy, cdx, cdy] = component.transform();
+                /* The component's transformation followed by `transform`. */
+                let combined = [
+                    xx * cxx + yx * cxy,
+                    xy * cxx + yy * cxy,
+                    xx * cyx + yx * cyy,
+                    xy * cyx + yy * cyy,
+                    xx * cdx + yx * cdy + dx,
+                    xy * cdx + yy * cdy + dy,
+                ];
+                ret = union_bbox(ret, base.borrow().transformed_bbox(combined, depth + 1));
+            }
+        }
+        ret
+    }
+
+    /// Left and right sidebearings: the distance from the origin to the left of the bounding
+    /// box, and from its right to the advance width. `None` if the glyph has no points.
+    pub fn sidebearings(&self) -> Option<(f64, f64)> {
+        let (x_min, _, x_max, _) = self.bbox()?;
+        Some((x_min, self.width.unwrap_or(0.) - x_max))
     }
 
     pub fn is_empty(&self) -> bool {
@@ -482,14 +857,14 @@ impl Glyph {
             && self.components.is_empty()
     }
 
-    pub fn name_markup(&self) -> gtk::glib::GString {
+    pub fn name_markup(&self) -> glib::GString {
         match self.kind {
             GlyphKind::Char(c) => {
                 let mut b = [0; 4];
-                gtk::glib::markup_escape_text(c.encode_utf8(&mut b).replace('\0', "").trim())
+                glib::markup_escape_text(c.encode_utf8(&mut b).replace('\0', "").trim())
             }
             GlyphKind::Component => {
-                gtk::glib::markup_escape_text(self.name.as_ref().replace('\0', "").trim())
+                glib::markup_escape_text(self.name.as_ref().replace('\0', "").trim())
             }
         }
     }
@@ -0,0 +1,247 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::*;
+use glib::prelude::*;
+use glib::{ParamFlags, ParamSpec, ParamSpecDouble, ParamSpecString, Value};
+
+use std::cell::RefCell;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct Anchor {
+        pub name: RefCell<String>,
+        pub identifier: RefCell<Option<String>>,
+        pub color: RefCell<Option<String>>,
+        pub x: RefCell<f64>,
+        pub y: RefCell<f64>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for Anchor {
+        const NAME: &'static str = "GlyphAnchor";
+        type Type = super::Anchor;
+        type ParentType = glib::Object;
+        type Interfaces = ();
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for Anchor {
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: once_cell::sync::Lazy<Vec<ParamSpec>> =
+                once_cell::sync::Lazy::new(|| {
+                    vec![
+                        ParamSpecString::new("name", "name", "name", None, ParamFlags::READWRITE),
+                        ParamSpecString::new(
+                            "identifier",
+                            "identifier",
+                            "identifier",
+                            None,
+                            ParamFlags::READWRITE,
+                        ),
+                        ParamSpecString::new(
+                            "color",
+                            "color",
+                            "color",
+                            None,
+                            ParamFlags::READWRITE,
+                        ),
+                        ParamSpecDouble::new(
+                            "x",
+                            "x",
+                            "x",
+                            f64::MIN,
+                            f64::MAX,
+                            0.,
+                            ParamFlags::READWRITE,
+                        ),
+                        ParamSpecDouble::new(
+                            "y",
+                            "y",
+                            "y",
+                            f64::MIN,
+                            f64::MAX,
+                            0.,
+                            ParamFlags::READWRITE,
+                        ),
+                    ]
+                });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _obj: &Self::Type, _id: usize, pspec: &ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "name" => self.name.borrow().to_value(),
+                "identifier" => self.identifier.borrow().to_value(),
+                "color" => self.color.borrow().to_value(),
+                "x" => self.x.borrow().to_value(),
+                "y" => self.y.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn set_property(&self, _obj: &Self::Type, _id: usize, value: &Value, pspec: &ParamSpec) {
+            match pspec.name() {
+                "name" => {
+                    *self.name.borrow_mut() =
+                        value.get::<Option<String>>().unwrap().unwrap_or_default();
+                }
+                "identifier" => {
+                    *self.identifier.borrow_mut() = value.get().unwrap();
+                }
+                "color" => {
+                    *self.color.borrow_mut() = value.get().unwrap();
+                }
+                "x" => {
+                    *self.x.borrow_mut() = value.get().unwrap();
+                }
+                "y" => {
+                    *self.y.borrow_mut() = value.get().unwrap();
+                }
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl Anchor {
+        /// Draw a cross-hair marker with the anchor's name. `matrix` transforms glyph units to
+        /// surface coordinates; the marker itself has a constant size regardless of zoom.
+        #[cfg(feature = "cairo")]
+        pub fn draw(&self, cr: &Context, matrix: Matrix, highlight: bool) {
+            const SIZE: f64 = 5.;
+            cr.save().unwrap();
+            if highlight {
+                cr.set_source_rgba(1., 0., 0., 0.9);
+                cr.set_line_width(2.0);
+            } else {
+                cr.set_source_rgba(0.6, 0., 0.6, 0.9);
+                cr.set_line_width(1.5);
+            }
+            let p = matrix.transform_point(*self.x.borrow(), *self.y.borrow());
+            cr.move_to(p.0 - SIZE, p.1);
+            cr.line_to(p.0 + SIZE, p.1);
+            cr.move_to(p.0, p.1 - SIZE);
+            cr.line_to(p.0, p.1 + SIZE);
+            cr.stroke().unwrap();
+            cr.arc(p.0, p.1, SIZE / 2., 0., 2.0 * std::f64::consts::PI);
+            cr.stroke().unwrap();
+            cr.move_to(p.0 + SIZE, p.1 - SIZE);
+            cr.show_text(&self.name.borrow()).unwrap();
+            cr.restore().unwrap();
+        }
+
+        /// Position rounded to integer glyph units, as used for hit testing.
+        pub fn position(&self) -> Point {
+            (
+                self.x.borrow().round() as i64,
+                self.y.borrow().round() as i64,
+            )
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct Anchor(ObjectSubclass<imp::Anchor>);
+}
+
+impl Anchor {
+    pub fn new() -> Self {
+        let ret: Self = glib::Object::new::<Self>(&[]).unwrap();
+        ret
+    }
+    pub fn name(&self) -> String {
+        self.imp().name.borrow().clone()
+    }
+    pub fn identifier(&self) -> Option<String> {
+        self.property("identifier")
+    }
+    pub fn color(&self) -> Option<String> {
+        self.property("color")
+    }
+    pub fn x(&self) -> f64 {
+        self.property("x")
+    }
+    pub fn y(&self) -> f64 {
+        self.property("y")
+    }
+
+    pub fn builder() -> AnchorBuilder {
+        AnchorBuilder::new()
+    }
+
+    /// A new anchor with the same values.
+    pub fn duplicate(&self) -> Self {
+        Self::builder()
+            .name(self.name())
+            .identifier(self.identifier())
+            .color(self.color())
+            .x(self.x())
+            .y(self.y())
+            .build()
+    }
+}
+
+impl Default for Anchor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default)]
+pub struct AnchorBuilder(Anchor);
+
+impl AnchorBuilder {
+    pub fn new() -> Self {
+        AnchorBuilder(Anchor::new())
+    }
+
+    pub fn name(self, name: String) -> Self {
+        *self.0.imp().name.borrow_mut() = name;
+        self
+    }
+
+    pub fn identifier(self, identifier: Option<String>) -> Self {
+        *self.0.imp().identifier.borrow_mut() = identifier;
+        self
+    }
+
+    pub fn color(self, color: Option<String>) -> Self {
+        *self.0.imp().color.borrow_mut() = color;
+        self
+    }
+
+    pub fn x(self, x: f64) -> Self {
+        *self.0.imp().x.borrow_mut() = x;
+        self
+    }
+
+    pub fn y(self, y: f64) -> Self {
+        *self.0.imp().y.borrow_mut() = y;
+        self
+    }
+
+    pub fn build(self) -> Anchor {
+        self.0
+    }
+}
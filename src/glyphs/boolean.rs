@@ -0,0 +1,453 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Boolean operations on closed contours.
+//!
+//! Every curve is split where it meets another, and each piece is kept if the area the operation
+//! produces is on exactly one of its sides. Kept pieces are turned so that the filled area is on
+//! their left, i.e. outer contours run counter-clockwise, and joined end to end into new contours.
+//! Areas are filled with the non-zero winding rule.
+
+use super::*;
+use crate::utils::curves::{evaluate, intersections, round_points, split_at, tangent, winding};
+
+/// Distance below which points are considered the same.
+const TOLERANCE: f64 = 1e-3;
+/// How far from a curve its sides are sampled to tell what is on them.
+const SIDE_OFFSET: f64 = 1e-2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOperation {
+    /// The area covered by either operand.
+    Union,
+    /// The area of the first operand that the second doesn't cover.
+    Subtract,
+    /// The area covered by both operands.
+    Intersect,
+    /// The area covered by exactly one of the operands.
+    Exclude,
+}
+
+impl BooleanOperation {
+    fn contains(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BooleanOperation::Union => in_a || in_b,
+            BooleanOperation::Subtract => in_a && !in_b,
+            BooleanOperation::Intersect => in_a && in_b,
+            BooleanOperation::Exclude => in_a != in_b,
+        }
+    }
+}
+
+/// A curve of one of the operands, with floating point control points.
+#[derive(Debug, Clone)]
+struct Segment {
+    operand: usize,
+    /// Index of the contour the segment belongs to in its operand.
+    contour: usize,
+    smooth: bool,
+    points: Vec<(f64, f64)>,
+}
+
+/// The curves of the closed contours in `contours`, including the line that closes a contour that
+/// doesn't end where it started. Open contours have no area and are ignored.
+fn segments(contours: &[Contour], operand: usize) -> Vec<Segment> {
+    let mut ret = vec![];
+    for (contour_index, contour) in contours
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !*c.open().borrow())
+    {
+        let curves = contour.curves().borrow();
+        let mut contour_segments = curves
+            .iter()
+            .map(|c| Segment {
+                operand,
+                contour: contour_index,
+                smooth: *c.smooth().borrow(),
+                points: c.float_points(),
+            })
+            .filter(|s| s.points.len() > 1)
+            .collect::<Vec<Segment>>();
+        if let (Some(first), Some(last)) = (contour_segments.first(), contour_segments.last()) {
+            let (start, end) = (first.points[0], last.points[last.points.len() - 1]);
+            if start != end {
+                contour_segments.push(Segment {
+                    operand,
+                    contour: contour_index,
+                    smooth: false,
+                    points: vec![end, start],
+                });
+            }
+        }
+        ret.extend(
+            contour_segments
+                .into_iter()
+                .filter(|s| s.points.iter().any(|p| *p != s.points[0])),
+        );
+    }
+    ret
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Split every segment where it meets another one.
+fn split_segments(segments: &[Segment]) -> Vec<Segment> {
+    let mut splits: Vec<Vec<(f64, (f64, f64))>> = vec![vec![]; segments.len()];
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            for (t, u) in intersections(&segments[i].points, &segments[j].points) {
+                let p = evaluate(&segments[i].points, t);
+                let q = evaluate(&segments[j].points, u);
+                let meeting_point = ((p.0 + q.0) / 2., (p.1 + q.1) / 2.);
+                splits[i].push((t, meeting_point));
+                splits[j].push((u, meeting_point));
+            }
+        }
+    }
+    let mut ret = vec![];
+    for (segment, mut splits) in segments.iter().zip(splits) {
+        splits.retain(|(t, _)| *t > 1e-9 && *t < 1. - 1e-9);
+        splits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        splits.dedup_by(|a, b| distance(a.1, b.1) < TOLERANCE);
+        let mut rest = segment.points.clone();
+        let mut prev_t = 0.;
+        for (t, point) in splits {
+            let (mut left, mut right) = split_at(&rest, (t - prev_t) / (1. - prev_t));
+            *left.last_mut().unwrap() = point;
+            right[0] = point;
+            ret.push(Segment {
+                points: left,
+                ..segment.clone()
+            });
+            rest = right;
+            prev_t = t;
+        }
+        ret.push(Segment {
+            points: rest,
+            ..segment.clone()
+        });
+    }
+    ret.retain(|s| {
+        s.points
+            .windows(2)
+            .map(|w| distance(w[0], w[1]))
+            .sum::<f64>()
+            > TOLERANCE
+    });
+    ret
+}
+
+/// Whether `point` is inside the area of the segments of `operand`.
+fn inside(segments: &[Segment], operand: usize, point: (f64, f64)) -> bool {
+    segments
+        .iter()
+        .filter(|s| s.operand == operand)
+        .map(|s| winding(&s.points, point))
+        .sum::<i32>()
+        != 0
+}
+
+/// Join pieces that each start where another ends into closed contours. Each contour is returned
+/// with the lowest index of the contours its pieces came from.
+fn join(mut pieces: Vec<Segment>) -> Vec<(usize, Contour)> {
+    let mut ret = vec![];
+    while let Some(first) = pieces.pop() {
+        let start = first.points[0];
+        let mut chain = vec![first];
+        loop {
+            let end = *chain.last().unwrap().points.last().unwrap();
+            if distance(end, start) < TOLERANCE * 10. {
+                break;
+            }
+            match pieces
+                .iter()
+                .position(|p| distance(p.points[0], end) < TOLERANCE * 10.)
+            {
+                Some(i) => chain.push(pieces.swap_remove(i)),
+                /* A dangling chain; it is closed with a line. */
+                None => break,
+            }
+        }
+        let origin = chain.iter().map(|p| p.contour).min().unwrap();
+        let contour = Contour::new();
+        {
+            let mut curves = contour.curves().borrow_mut();
+            for piece in chain {
+                let mut points = round_points(&piece.points);
+                if let Some(prev_end) = curves
+                    .last()
+                    .and_then(|c: &Bezier| c.points().borrow().last().copied())
+                {
+                    points[0] = prev_end;
+                }
+                if points.iter().all(|p| *p == points[0]) {
+                    continue;
+                }
+                /* Merge consecutive collinear lines. */
+                if let (2, Some(prev)) = (points.len(), curves.last()) {
+                    let prev_points = prev.points().borrow().clone();
+                    if prev_points.len() == 2 {
+                        let (a, b, c) = (prev_points[0], prev_points[1], points[1]);
+                        let cross = (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0);
+                        let dot = (b.0 - a.0) * (c.0 - b.0) + (b.1 - a.1) * (c.1 - b.1);
+                        if cross == 0 && dot > 0 {
+                            prev.points().borrow_mut()[1] = c;
+                            continue;
+                        }
+                    }
+                }
+                curves.push(Bezier::new(piece.smooth, points));
+            }
+            /* A line across the start of the chain may have been split in two. */
+            if curves.len() > 2 {
+                let first = curves[0].points().borrow().clone();
+                let last = curves[curves.len() - 1].points().borrow().clone();
+                if first.len() == 2 && last.len() == 2 && last[1] == first[0] {
+                    let (a, b, c) = (last[0], first[0], first[1]);
+                    let cross = (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0);
+                    let dot = (b.0 - a.0) * (c.0 - b.0) + (b.1 - a.1) * (c.1 - b.1);
+                    if cross == 0 && dot > 0 {
+                        curves[0].points().borrow_mut()[0] = a;
+                        curves.pop();
+                    }
+                }
+            }
+            /* The contour ends exactly where it starts. */
+            if let Some(start) = curves.first().map(|c| c.points().borrow()[0]) {
+                let end = *curves.last().unwrap().points().borrow().last().unwrap();
+                if end != start {
+                    curves.push(Bezier::new(false, vec![end, start]));
+                }
+            }
+        }
+        if contour.curves().borrow().len() > 1 {
+            ret.push((origin, contour));
+        }
+    }
+    ret.reverse();
+    ret
+}
+
+/// Apply `operation` to the areas of the closed contours `a` and `b`. Open contours are ignored.
+/// Returns new contours; the operands are not changed.
+pub fn boolean_operation(
+    a: &[Contour],
+    b: &[Contour],
+    operation: BooleanOperation,
+) -> Vec<Contour> {
+    let mut all_segments = segments(a, 0);
+    all_segments.extend(segments(b, 1));
+    let pieces = split_segments(&all_segments);
+    join(kept_pieces(&all_segments, pieces, operation))
+        .into_iter()
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// The `pieces` of `all_segments` that border the area `operation` produces, turned so that the
+/// area is on their left.
+fn kept_pieces(
+    all_segments: &[Segment],
+    pieces: Vec<Segment>,
+    operation: BooleanOperation,
+) -> Vec<Segment> {
+    let mut kept: Vec<Segment> = vec![];
+    for mut piece in pieces {
+        let middle = evaluate(&piece.points, 0.5);
+        let (dx, dy) = tangent(&piece.points, 0.5);
+        let length = dx.hypot(dy);
+        if length < 1e-12 {
+            continue;
+        }
+        let normal = (-dy / length * SIDE_OFFSET, dx / length * SIDE_OFFSET);
+        let left = (middle.0 + normal.0, middle.1 + normal.1);
+        let right = (middle.0 - normal.0, middle.1 - normal.1);
+        let filled = |p| operation.contains(inside(all_segments, 0, p), inside(all_segments, 1, p));
+        match (filled(left), filled(right)) {
+            (true, false) => {}
+            (false, true) => piece.points.reverse(),
+            _ => continue,
+        }
+        /* Edges shared by both operands appear twice. */
+        if kept.iter().any(|k| {
+            k.points.len() == piece.points.len()
+                && k.points
+                    .iter()
+                    .zip(piece.points.iter())
+                    .all(|(p, q)| distance(*p, *q) < TOLERANCE * 10.)
+        }) {
+            continue;
+        }
+        kept.push(piece);
+    }
+    kept
+}
+
+/// `contours` with the overlapping closed contours, and the overlapping parts of self-intersecting
+/// ones, merged so that no area is covered twice. Contours that don't meet any other and aren't
+/// covered by another are returned unchanged, and each merged contour takes the place of the first
+/// contour it was made from, so the order of the contours is kept.
+pub fn remove_overlaps(contours: &[Contour]) -> Vec<Contour> {
+    let all_segments = segments(contours, 0);
+    let pieces = split_segments(&all_segments);
+    let piece_counts = |segments: &[Segment]| {
+        let mut ret = vec![0; contours.len()];
+        for s in segments {
+            ret[s.contour] += 1;
+        }
+        ret
+    };
+    let (segment_count, piece_count) = (piece_counts(&all_segments), piece_counts(&pieces));
+    let kept = kept_pieces(&all_segments, pieces, BooleanOperation::Union);
+    let kept_count = piece_counts(&kept);
+    /* Contours whose curves were neither split nor dropped are the same after the union, apart
+     * from their direction and start point. */
+    let unchanged =
+        |i: usize| segment_count[i] == piece_count[i] && piece_count[i] == kept_count[i];
+    let mut ret = contours
+        .iter()
+        .enumerate()
+        .filter(|(i, c)| *c.open().borrow() || unchanged(*i))
+        .map(|(i, c)| (i, c.duplicate()))
+        .collect::<Vec<(usize, Contour)>>();
+    ret.extend(join(
+        kept.into_iter().filter(|p| !unchanged(p.contour)).collect(),
+    ));
+    ret.sort_by_key(|(i, _)| *i);
+    ret.into_iter().map(|(_, c)| c).collect()
+}
+
+impl Glyph {
+    /// Merge overlapping contours with [`remove_overlaps`].
+    pub fn remove_overlaps(&mut self) {
+        self.contours = remove_overlaps(&self.contours);
+        self.modified = true;
+    }
+}
+
+#[test]
+fn test_boolean_operations() {
+    let square = |x: i64, y: i64, size: i64| {
+        let contour = Contour::new();
+        *contour.curves().borrow_mut() = vec![
+            Bezier::new(false, vec![(x, y), (x + size, y)]),
+            Bezier::new(false, vec![(x + size, y), (x + size, y + size)]),
+            Bezier::new(false, vec![(x + size, y + size), (x, y + size)]),
+            Bezier::new(false, vec![(x, y + size), (x, y)]),
+        ];
+        contour
+    };
+    /* Twice the signed area of contours made of lines. */
+    let area = |contours: &[Contour]| -> i64 {
+        contours
+            .iter()
+            .flat_map(|c| c.curves().borrow().clone())
+            .map(|b| {
+                let points = b.points().borrow();
+                let (p, q) = (points[0], points[points.len() - 1]);
+                p.0 * q.1 - q.0 * p.1
+            })
+            .sum()
+    };
+    let a = [square(0, 0, 100)];
+    let b = [square(50, 50, 100)];
+
+    let union = boolean_operation(&a, &b, BooleanOperation::Union);
+    assert_eq!(union.len(), 1);
+    assert_eq!(union[0].curves().borrow().len(), 8);
+    assert_eq!(area(&union), 2 * 17500);
+    let subtract = boolean_operation(&a, &b, BooleanOperation::Subtract);
+    assert_eq!(subtract.len(), 1);
+    assert_eq!(area(&subtract), 2 * 7500);
+    let intersect = boolean_operation(&a, &b, BooleanOperation::Intersect);
+    assert_eq!(intersect.len(), 1);
+    assert_eq!(area(&intersect), 2 * 2500);
+    let exclude = boolean_operation(&a, &b, BooleanOperation::Exclude);
+    assert_eq!(area(&exclude), 2 * 15000);
+
+    /* A hole. */
+    let subtract = boolean_operation(&a, &[square(25, 25, 50)], BooleanOperation::Subtract);
+    assert_eq!(subtract.len(), 2);
+    assert_eq!(area(&subtract), 2 * 7500);
+    /* Disjoint operands. */
+    let union = boolean_operation(&a, &[square(200, 0, 10)], BooleanOperation::Union);
+    assert_eq!(union.len(), 2);
+    assert!(boolean_operation(&a, &[square(200, 0, 10)], BooleanOperation::Intersect).is_empty());
+    /* Squares sharing an edge. */
+    let union = boolean_operation(&a, &[square(100, 0, 100)], BooleanOperation::Union);
+    assert_eq!(union.len(), 1);
+    assert_eq!(area(&union), 2 * 20000);
+    assert_eq!(union[0].curves().borrow().len(), 4);
+
+    let mut glyph = Glyph::new_empty("a", 'a');
+    let open = Contour::new();
+    *open.open().borrow_mut() = true;
+    open.curves()
+        .borrow_mut()
+        .push(Bezier::new(false, vec![(0, 0), (10, 10)]));
+    let disjoint = square(300, 0, 10);
+    glyph.contours = vec![
+        open,
+        square(0, 0, 100),
+        disjoint.clone(),
+        square(50, 50, 100),
+    ];
+    glyph.remove_overlaps();
+    assert_eq!(glyph.contours.len(), 3);
+    assert!(*glyph.contours[0].open().borrow());
+    assert_eq!(area(&glyph.contours[1..2]), 2 * 17500);
+    let points = |c: &Contour| {
+        c.curves()
+            .borrow()
+            .iter()
+            .map(|b| b.points().borrow().clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(points(&glyph.contours[2]), points(&disjoint));
+    /* Nothing overlaps. */
+    let contours = [square(0, 0, 10), square(20, 0, 10)];
+    let removed = remove_overlaps(&contours);
+    assert_eq!(removed.len(), 2);
+    for (a, b) in removed.iter().zip(contours.iter()) {
+        assert_eq!(points(a), points(b));
+    }
+
+    /* Curves: a circle-ish shape cut in half by a rectangle. */
+    let blob = Contour::new();
+    *blob.curves().borrow_mut() = vec![
+        Bezier::new(true, vec![(0, -100), (55, -100), (100, -55), (100, 0)]),
+        Bezier::new(true, vec![(100, 0), (100, 55), (55, 100), (0, 100)]),
+        Bezier::new(true, vec![(0, 100), (-55, 100), (-100, 55), (-100, 0)]),
+        Bezier::new(true, vec![(-100, 0), (-100, -55), (-55, -100), (0, -100)]),
+    ];
+    let half = boolean_operation(&[blob], &[square(0, -200, 400)], BooleanOperation::Subtract);
+    assert_eq!(half.len(), 1);
+    for curve in half[0].curves().borrow().iter() {
+        assert!(
+            curve.points().borrow().iter().all(|p| p.0 <= 0),
+            "{:?}",
+            curve
+        );
+    }
+}
@@ -21,7 +21,6 @@
 
 use super::*;
 use glib::{ParamSpec, Value};
-use gtk::glib;
 
 glib::wrapper! {
     pub struct Contour(ObjectSubclass<imp::Contour>);
@@ -40,6 +39,350 @@ impl Contour {
     pub fn curves(&self) -> &RefCell<Vec<Bezier>> {
         &self.imp().curves
     }
+
+    /// A new contour with copies of this contour's curves. Unlike `clone()`, the curves are not
+    /// shared with the original.
+    pub fn duplicate(&self) -> Self {
+        let ret = Self::new();
+        *ret.open().borrow_mut() = *self.open().borrow();
+        *ret.curves().borrow_mut() = self
+            .curves()
+            .borrow()
+            .iter()
+            .map(|b| Bezier::new(*b.smooth().borrow(), b.points().borrow().clone()))
+            .collect();
+        ret
+    }
+
+    /// The control points of every curve, including the line that closes a closed contour that
+    /// doesn't end where it started.
+    fn outline(&self) -> Vec<Vec<FloatPoint>> {
+        let mut ret = self
+            .curves()
+            .borrow()
+            .iter()
+            .map(Bezier::float_points)
+            .filter(|p| p.len() > 1)
+            .collect::<Vec<_>>();
+        if !*self.open().borrow() {
+            if let (Some(first), Some(last)) = (ret.first(), ret.last()) {
+                let (start, end) = (first[0], last[last.len() - 1]);
+                if start != end {
+                    ret.push(vec![end, start]);
+                }
+            }
+        }
+        ret
+    }
+
+    /// The area enclosed by the contour, positive if it runs counter-clockwise and negative if
+    /// it runs clockwise. Open contours enclose nothing and have zero area.
+    pub fn signed_area(&self) -> f64 {
+        if *self.open().borrow() {
+            return 0.;
+        }
+        self.outline().iter().map(|p| signed_area(p)).sum::<f64>() / 2.
+    }
+
+    pub fn is_clockwise(&self) -> bool {
+        self.signed_area() < 0.
+    }
+
+    /// Whether `point` is inside the contour, by the nonzero winding rule.
+    pub fn contains(&self, point: FloatPoint) -> bool {
+        !*self.open().borrow()
+            && self
+                .outline()
+                .iter()
+                .map(|p| winding(p, point))
+                .sum::<i32>()
+                != 0
+    }
+
+    /// Add the line that closes a closed contour which doesn't end where it started as a curve of
+    /// its own, so that every on-curve point starts a curve.
+    fn close_explicitly(&self) {
+        if *self.open().borrow() {
+            return;
+        }
+        let mut curves = self.curves().borrow_mut();
+        let start = curves
+            .first()
+            .and_then(|b| b.points().borrow().first().copied());
+        let end = curves
+            .last()
+            .and_then(|b| b.points().borrow().last().copied());
+        if let (Some(start), Some(end)) = (start, end) {
+            if start != end {
+                curves.push(Bezier::new(false, vec![end, start]));
+            }
+        }
+    }
+
+    /* The operations below refer to on-curve points by index: point `i` is where curve `i`
+     * starts, and in open contours point `curves.len()` is where the last curve ends. The point
+     * where a closed contour's implicit closing line starts is `curves.len()` as well. */
+
+    /// The number of on-curve points, i.e. one past the highest valid point index.
+    fn point_count(&self) -> usize {
+        let curves = self.curves().borrow();
+        let (start, end) = match (curves.first(), curves.last()) {
+            (Some(first), Some(last)) => (
+                first.points().borrow().first().copied(),
+                last.points().borrow().last().copied(),
+            ),
+            _ => return 0,
+        };
+        if *self.open().borrow() || start != end {
+            curves.len() + 1
+        } else {
+            curves.len()
+        }
+    }
+
+    /// Split curve `curve_index` at `t`, adding an on-curve point there. Returns whether the
+    /// curve was split; splits that would round to one of the curve's end points are refused.
+    pub fn split_curve(&self, curve_index: usize, t: f64) -> bool {
+        let mut curves = self.curves().borrow_mut();
+        let curve = match curves.get(curve_index) {
+            Some(c) if c.points().borrow().len() > 1 && t > 0. && t < 1. => c,
+            _ => return false,
+        };
+        let (left, right) = curve.split(t);
+        let point = right.points().borrow()[0];
+        if point == curve.points().borrow()[0] || Some(&point) == curve.points().borrow().last() {
+            return false;
+        }
+        curves.splice(curve_index..=curve_index, [left, right]);
+        true
+    }
+
+    /// Remove on-curve point `index`, refitting the curves on either side of it as one with
+    /// [`Bezier::merge`]. Removing an end of an open contour drops the curve that ends there.
+    /// Returns whether the point was removed.
+    pub fn delete_point(&self, index: usize) -> bool {
+        if index >= self.point_count() {
+            return false;
+        }
+        self.close_explicitly();
+        let open = *self.open().borrow();
+        let mut curves = self.curves().borrow_mut();
+        let n = curves.len();
+        if open {
+            match index {
+                _ if n == 0 || index > n => return false,
+                0 => {
+                    curves.remove(0);
+                }
+                _ if index == n => {
+                    curves.pop();
+                }
+                _ => {
+                    let merged = curves[index - 1].merge(&curves[index]);
+                    curves.splice(index - 1..=index, [merged]);
+                }
+            }
+        } else {
+            if n < 2 || index > n {
+                return false;
+            }
+            let index = index % n;
+            if index == 0 {
+                let merged = curves[n - 1].merge(&curves[0]);
+                curves.pop();
+                curves[0] = merged;
+            } else {
+                let merged = curves[index - 1].merge(&curves[index]);
+                curves.splice(index - 1..=index, [merged]);
+            }
+        }
+        true
+    }
+
+    /// Make on-curve point `index` the start of a closed contour. Returns whether the start
+    /// point changed.
+    pub fn set_start_point(&self, index: usize) -> bool {
+        if *self.open().borrow() || index == 0 || index >= self.point_count() {
+            return false;
+        }
+        self.close_explicitly();
+        let mut curves = self.curves().borrow_mut();
+        if index >= curves.len() {
+            return false;
+        }
+        curves.rotate_left(index);
+        true
+    }
+
+    /// Open a closed contour at on-curve point `index`, which becomes both its start and its end.
+    /// Returns whether the contour was closed.
+    pub fn break_at(&self, index: usize) -> bool {
+        if *self.open().borrow() || index >= self.point_count() {
+            return false;
+        }
+        /* The closing line has to be a curve of its own before the contour is opened. */
+        self.close_explicitly();
+        self.set_start_point(index);
+        *self.open().borrow_mut() = true;
+        true
+    }
+
+    /// Exact bounding box `(x_min, y_min, x_max, y_max)` of the contour's curves, or `None` if
+    /// it has no points.
+    pub fn bbox(&self) -> Option<(f64, f64, f64, f64)> {
+        self.curves()
+            .borrow()
+            .iter()
+            .filter(|b| !b.points().borrow().is_empty())
+            .map(Bezier::bbox)
+            .fold(None, |acc, bbox| union_bbox(acc, Some(bbox)))
+    }
+
+    /// Split the contour's curves at their horizontal and vertical extrema, so that every
+    /// extremum is an on-curve point. Returns whether any curve was split.
+    pub fn add_extrema(&self) -> bool {
+        let mut curves = self.curves().borrow_mut();
+        let split = curves
+            .iter()
+            .flat_map(Bezier::split_at_extrema)
+            .collect::<Vec<Bezier>>();
+        if split.len() == curves.len() {
+            return false;
+        }
+        *curves = split;
+        true
+    }
+
+    /// Reverse the direction of the contour. The curves are replaced with new ones, so that
+    /// contours sharing them through `clone()` are not affected.
+    pub fn reverse(&self) {
+        let mut curves = self.curves().borrow_mut();
+        *curves = curves
+            .iter()
+            .rev()
+            .map(|b| {
+                let mut points = b.points().borrow().clone();
+                points.reverse();
+                Bezier::new(*b.smooth().borrow(), points)
+            })
+            .collect();
+    }
+}
+
+/// How many other closed contours of `contours` each closed contour lies within. Contours with an
+/// even depth are outlines and contours with an odd depth are counters. Open contours have no
+/// depth.
+pub fn contour_depths(contours: &[Contour]) -> Vec<Option<usize>> {
+    contours
+        .iter()
+        .enumerate()
+        .map(|(i, contour)| {
+            if *contour.open().borrow() {
+                return None;
+            }
+            /* Test a point in the middle of the first curve instead of an on-curve point, which is
+             * often shared with a neighbouring contour. */
+            let point = contour.outline().first().map(|p| evaluate(p, 0.5))?;
+            Some(
+                contours
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, other)| *j != i && other.contains(point))
+                    .count(),
+            )
+        })
+        .collect()
+}
+
+/// Reverse the contours of `contours` that don't run the way PostScript outlines expect: outlines
+/// counter-clockwise and counters clockwise. Returns whether any contour was reversed.
+pub fn correct_directions(contours: &[Contour]) -> bool {
+    let mut changed = false;
+    for (contour, depth) in contours.iter().zip(contour_depths(contours)) {
+        let depth = match depth {
+            Some(d) => d,
+            None => continue,
+        };
+        let area = contour.signed_area();
+        if area != 0. && (area < 0.) == (depth % 2 == 0) {
+            contour.reverse();
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Join an end of the open contour `first` to an end of the open contour `second`, choosing the
+/// start or the end of each with `first_at_start` and `second_at_start`, with a line unless the two
+/// ends are at the same place. The curves of `first` come first in the new open contour. If
+/// `first` and `second` are the same contour it is closed instead.
+pub fn join_contours(
+    first: &Contour,
+    first_at_start: bool,
+    second: &Contour,
+    second_at_start: bool,
+) -> Contour {
+    let ret = first.duplicate();
+    if first == second {
+        *ret.open().borrow_mut() = false;
+        ret.close_explicitly();
+        return ret;
+    }
+    let second = second.duplicate();
+    if first_at_start {
+        ret.reverse();
+    }
+    if !second_at_start {
+        second.reverse();
+    }
+    let mut curves = ret.curves().borrow_mut();
+    let end = curves
+        .last()
+        .and_then(|b| b.points().borrow().last().copied());
+    let start = second
+        .curves()
+        .borrow()
+        .first()
+        .and_then(|b| b.points().borrow().first().copied());
+    if let (Some(end), Some(start)) = (end, start) {
+        if end != start {
+            curves.push(Bezier::new(false, vec![end, start]));
+        }
+    }
+    curves.extend(second.curves().borrow().iter().cloned());
+    drop(curves);
+    *ret.open().borrow_mut() = true;
+    ret
+}
+
+/// Add on-curve points at the extrema of the curves of `contours` with [`Contour::add_extrema`].
+/// Returns whether any point was added.
+pub fn add_extrema(contours: &[Contour]) -> bool {
+    let mut changed = false;
+    for contour in contours {
+        changed |= contour.add_extrema();
+    }
+    changed
+}
+
+impl Glyph {
+    /// Fix the direction of the glyph's contours with [`correct_directions`].
+    pub fn correct_directions(&mut self) -> bool {
+        let changed = correct_directions(&self.contours);
+        if changed {
+            self.modified = true;
+        }
+        changed
+    }
+
+    /// Add on-curve points at the extrema of the glyph's curves with [`add_extrema`].
+    pub fn add_extrema(&mut self) -> bool {
+        let changed = add_extrema(&self.contours);
+        if changed {
+            self.modified = true;
+        }
+        changed
+    }
 }
 
 mod imp {
@@ -78,3 +421,253 @@ mod imp {
         }
     }
 }
+
+#[test]
+fn test_contour_directions() {
+    let square = |x: i64, y: i64, size: i64, clockwise: bool| {
+        let mut corners = [(x, y), (x + size, y), (x + size, y + size), (x, y + size)];
+        if clockwise {
+            corners.reverse();
+        }
+        let contour = Contour::new();
+        *contour.curves().borrow_mut() = (0..4)
+            .map(|i| Bezier::new(false, vec![corners[i], corners[(i + 1) % 4]]))
+            .collect();
+        contour
+    };
+    assert_eq!(square(0, 0, 100, false).signed_area(), 10000.);
+    assert_eq!(square(0, 0, 100, true).signed_area(), -10000.);
+
+    /* The closing line is implicit. */
+    let triangle = Contour::new();
+    *triangle.curves().borrow_mut() = vec![
+        Bezier::new(false, vec![(0, 0), (100, 0)]),
+        Bezier::new(false, vec![(100, 0), (0, 100)]),
+    ];
+    assert_eq!(triangle.signed_area(), 5000.);
+    *triangle.open().borrow_mut() = true;
+    assert_eq!(triangle.signed_area(), 0.);
+
+    /* Cubic and quadratic curves. */
+    let circle = Contour::new();
+    *circle.curves().borrow_mut() = vec![
+        Bezier::new(true, vec![(100, 0), (100, 55), (55, 100), (0, 100)]),
+        Bezier::new(true, vec![(0, 100), (-55, 100), (-100, 55), (-100, 0)]),
+        Bezier::new(true, vec![(-100, 0), (-100, -55), (-55, -100), (0, -100)]),
+        Bezier::new(true, vec![(0, -100), (55, -100), (100, -55), (100, 0)]),
+    ];
+    assert!((circle.signed_area() - std::f64::consts::PI * 10000.).abs() < 100.);
+    let lens = Contour::new();
+    *lens.curves().borrow_mut() = vec![
+        Bezier::new(true, vec![(0, 0), (50, -50), (100, 0)]),
+        Bezier::new(true, vec![(100, 0), (50, 50), (0, 0)]),
+    ];
+    /* Each half is 2/3 of its triangle of control points. */
+    assert!((lens.signed_area() - 2. * 2. / 3. * 2500.).abs() < 1e-6);
+    circle.reverse();
+    assert!(circle.is_clockwise());
+    assert_eq!(circle.curves().borrow()[0].points().borrow()[0], (100, 0));
+
+    /* An "o" drawn with both contours clockwise, next to a counter-clockwise "l". */
+    let mut glyph = Glyph::new_empty("o", 'o');
+    glyph.contours = vec![
+        square(0, 0, 300, true),
+        square(100, 100, 100, true),
+        square(400, 0, 100, false),
+    ];
+    assert_eq!(
+        contour_depths(&glyph.contours),
+        vec![Some(0), Some(1), Some(0)]
+    );
+    assert!(glyph.correct_directions());
+    assert!(!glyph.contours[0].is_clockwise());
+    assert!(glyph.contours[1].is_clockwise());
+    assert!(!glyph.contours[2].is_clockwise());
+    assert!(!glyph.correct_directions());
+}
+
+#[test]
+fn test_bbox_and_extrema() {
+    /* A circle whose curves start and end at 45 degrees, so that every extremum is in the middle
+     * of a curve. */
+    let circle = Contour::new();
+    *circle.curves().borrow_mut() = vec![
+        Bezier::new(true, vec![(71, 71), (32, 110), (-32, 110), (-71, 71)]),
+        Bezier::new(true, vec![(-71, 71), (-110, 32), (-110, -32), (-71, -71)]),
+        Bezier::new(true, vec![(-71, -71), (-32, -110), (32, -110), (71, -71)]),
+        Bezier::new(true, vec![(71, -71), (110, -32), (110, 32), (71, 71)]),
+    ];
+    let (x_min, y_min, x_max, y_max) = circle.bbox().unwrap();
+    assert!((x_max - 100.25).abs() < 1e-9, "{}", x_max);
+    assert!((x_min + x_max).abs() < 1e-9 && (y_min + y_max).abs() < 1e-9);
+    assert!((y_max - x_max).abs() < 1e-9);
+
+    let mut base = Glyph::new_empty("o", 'o');
+    base.contours = vec![circle.duplicate()];
+    base.width = Some(300.);
+    let base = Rc::new(RefCell::new(base));
+    /* Rotated by 45 degrees the circle's box grows only by the rounding of its control points. */
+    let (sin, cos) = std::f64::consts::FRAC_PI_4.sin_cos();
+    let mut composite = Glyph::new_empty("o.rotated", 'ö');
+    composite.width = Some(300.);
+    composite.components.push(Component {
+        base_name: "o".to_string(),
+        base: Rc::downgrade(&base),
+        x_offset: 150.,
+        y_offset: 0.,
+        x_scale: cos,
+        xy_scale: sin,
+        yx_scale: -sin,
+        y_scale: cos,
+    });
+    let (x_min, _, x_max, _) = composite.bbox().unwrap();
+    assert!(
+        (x_min - 50.).abs() < 1. && (x_max - 250.).abs() < 1.,
+        "{} {}",
+        x_min,
+        x_max
+    );
+    let (left, right) = composite.sidebearings().unwrap();
+    assert!((left - right).abs() < 1e-6);
+    /* A glyph that is its own component. */
+    let self_component = Component {
+        base_name: "o".to_string(),
+        base: Rc::downgrade(&base),
+        x_offset: 10.,
+        y_offset: 0.,
+        x_scale: 1.,
+        xy_scale: 0.,
+        yx_scale: 0.,
+        y_scale: 1.,
+    };
+    base.borrow_mut().components.push(self_component);
+    let (x_min, _, x_max, _) = base.borrow().bbox().unwrap();
+    assert!((x_min + 100.25).abs() < 1e-9, "{}", x_min);
+    assert!(
+        (x_max - 100.25 - 10. * MAX_COMPONENT_DEPTH as f64).abs() < 1e-9,
+        "{}",
+        x_max
+    );
+
+    assert!(add_extrema(std::slice::from_ref(&circle)));
+    let curves = circle.curves().borrow().clone();
+    assert_eq!(curves.len(), 8);
+    for pair in curves.windows(2) {
+        assert_eq!(pair[0].points().borrow()[3], pair[1].points().borrow()[0]);
+    }
+    assert!(curves.iter().any(|c| c.points().borrow()[0] == (0, 100)));
+    /* The on-curve points now reach the edges of the bounding box. */
+    assert_eq!(
+        curves.iter().map(|c| c.points().borrow()[0].0).max(),
+        Some(100)
+    );
+    drop(curves);
+    assert!(!circle.add_extrema());
+}
+
+#[test]
+fn test_contour_editing() {
+    let on_curve_points = |contour: &Contour| {
+        contour
+            .curves()
+            .borrow()
+            .iter()
+            .map(|b| b.points().borrow()[0])
+            .collect::<Vec<Point>>()
+    };
+    let arch = || {
+        let contour = Contour::new();
+        *contour.open().borrow_mut() = true;
+        *contour.curves().borrow_mut() = vec![Bezier::new(
+            true,
+            vec![(0, 0), (0, 400), (600, 400), (600, 0)],
+        )];
+        contour
+    };
+
+    /* Splitting and then deleting the new point gives back the same curve. */
+    let contour = arch();
+    assert!(!contour.split_curve(0, 0.));
+    assert!(contour.split_curve(0, 0.5));
+    assert_eq!(on_curve_points(&contour), vec![(0, 0), (300, 300)]);
+    assert!(contour.delete_point(1));
+    assert_eq!(contour.curves().borrow().len(), 1);
+    assert_eq!(
+        *contour.curves().borrow()[0].points().borrow(),
+        vec![(0, 0), (0, 400), (600, 400), (600, 0)]
+    );
+
+    /* Refitting an asymmetric split keeps the shape. */
+    let original = arch();
+    let contour = arch();
+    assert!(contour.split_curve(0, 0.3));
+    assert!(contour.delete_point(1));
+    for i in 0..=10 {
+        let t = i as f64 / 10.;
+        let p = contour.curves().borrow()[0].float_points();
+        let t_original = original.curves().borrow()[0].nearest(round_points(&[evaluate(&p, t)])[0]);
+        let q = evaluate(&original.curves().borrow()[0].float_points(), t_original);
+        let p = evaluate(&p, t);
+        assert!((p.0 - q.0).hypot(p.1 - q.1) < 2., "{:?} {:?}", p, q);
+    }
+
+    /* Lines merge into a line, and open contours lose their end curves. */
+    let square = Contour::new();
+    *square.curves().borrow_mut() = vec![
+        Bezier::new(false, vec![(0, 0), (100, 0)]),
+        Bezier::new(false, vec![(100, 0), (100, 100)]),
+        Bezier::new(false, vec![(100, 100), (0, 100)]),
+    ];
+    /* Points past the implicit closing line leave the contour untouched. */
+    assert!(!square.break_at(4));
+    assert!(!square.delete_point(4));
+    assert!(!square.set_start_point(4));
+    assert_eq!(square.curves().borrow().len(), 3);
+    assert!(!*square.open().borrow());
+    assert!(square.set_start_point(2));
+    assert_eq!(
+        on_curve_points(&square),
+        vec![(100, 100), (0, 100), (0, 0), (100, 0)]
+    );
+    assert!(!square.set_start_point(4));
+    /* The point before the deleted start point becomes the new start. */
+    assert!(square.delete_point(0));
+    assert_eq!(on_curve_points(&square), vec![(100, 0), (0, 100), (0, 0)]);
+    assert!(square.break_at(1));
+    assert!(*square.open().borrow());
+    assert_eq!(on_curve_points(&square), vec![(0, 100), (0, 0), (100, 0)]);
+    assert_eq!(square.curves().borrow()[2].points().borrow()[1], (0, 100));
+    assert!(!square.break_at(0));
+    assert!(square.delete_point(3));
+    assert!(square.delete_point(0));
+    assert_eq!(on_curve_points(&square), vec![(0, 0)]);
+
+    /* Opening a contour at its start keeps the implicit closing line. */
+    let square = Contour::new();
+    *square.curves().borrow_mut() = vec![
+        Bezier::new(false, vec![(0, 0), (100, 0)]),
+        Bezier::new(false, vec![(100, 0), (100, 100)]),
+        Bezier::new(false, vec![(100, 100), (0, 100)]),
+    ];
+    assert!(square.break_at(0));
+    assert!(*square.open().borrow());
+    assert_eq!(square.curves().borrow().len(), 4);
+    assert_eq!(
+        *square.curves().borrow()[3].points().borrow(),
+        vec![(0, 100), (0, 0)]
+    );
+
+    /* Joining the end of one arch to the end of another reverses the second. */
+    let other = arch();
+    let joined = join_contours(&arch(), false, &other, false);
+    assert!(*joined.open().borrow());
+    assert_eq!(on_curve_points(&joined), vec![(0, 0), (600, 0)]);
+    assert_eq!(
+        joined.curves().borrow().last().unwrap().points().borrow()[3],
+        (0, 0)
+    );
+    let closed = join_contours(&joined, false, &joined, true);
+    assert!(!*closed.open().borrow());
+    assert_eq!(closed.curves().borrow().len(), 2);
+    assert!(*joined.open().borrow());
+}
@@ -23,8 +23,11 @@ extern crate quick_xml;
 extern crate serde;
 
 use crate::unicode::names::CharName;
-use gtk::subclass::prelude::ObjectSubclassIsExt;
+use crate::utils::plist::DictValue;
+use crate::utils::{line_column, xml_escape};
+use glib::subclass::prelude::ObjectSubclassIsExt;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -57,21 +60,10 @@ struct Point {
 }
 
 impl Point {
-    #[inline(always)]
-    fn is_curve(&self) -> bool {
-        matches!(self.type_, PointKind::Curve)
-    }
-
-    /*
     #[inline(always)]
     fn is_offcurve(&self) -> bool {
-        if let PointKind::Offcurve = self.type_ {
-            true
-        } else {
-            false
-        }
+        matches!(self.type_, PointKind::Offcurve)
     }
-    */
 
     #[inline(always)]
     fn is_move(&self) -> bool {
@@ -90,6 +82,149 @@ struct Contour {
     point: Vec<Point>,
 }
 
+impl Contour {
+    /// Convert the points to curves, as the smoothness and control points of each. Returns
+    /// whether the contour is open along with them, or `None` if there are no points.
+    #[allow(clippy::type_complexity)]
+    fn curves(&self) -> Result<Option<(bool, Vec<(bool, Vec<(i64, i64)>)>)>, GlifError> {
+        let mut contour_acc = vec![];
+        let mut open = false;
+        let mut points = self
+            .point
+            .iter()
+            .collect::<std::collections::VecDeque<&_>>();
+        if points.is_empty() {
+            return Ok(None);
+        }
+        let mut c;
+        let mut prev_point;
+        let mut last_oncurve;
+        if points.front().unwrap().is_move() {
+            open = true;
+            // Open contour
+            let p = points.pop_front().unwrap();
+            prev_point = (p.x, p.y);
+            last_oncurve = prev_point;
+            c = vec![prev_point];
+        } else {
+            c = vec![];
+            // Closed contour: rotate the points so that the list ends with an on-curve
+            // point, which is where the first segment starts from.
+            let mut rotations = 0;
+            while points.back().unwrap().is_offcurve() && rotations < points.len() {
+                points.rotate_left(1);
+                rotations += 1;
+            }
+            let last_point = points.back().unwrap();
+            prev_point = (last_point.x, last_point.y);
+            last_oncurve = prev_point;
+        }
+        if points.front().map(|p| p.is_line()).unwrap_or(false) {
+            let p = points.back().unwrap();
+            prev_point = (p.x, p.y);
+        }
+        loop {
+            match points.pop_front() {
+                Some(Point {
+                    type_: PointKind::Move,
+                    ..
+                }) => {
+                    return Err(GlifError {
+                        location: None,
+                        message: "Contour has a move point after its start.".to_string(),
+                    });
+                }
+                Some(Point {
+                    type_: PointKind::Offcurve,
+                    x,
+                    y,
+                    ..
+                }) => {
+                    prev_point = (*x, *y);
+                    c.push(prev_point);
+                }
+                Some(Point {
+                    type_: PointKind::Curve,
+                    x,
+                    y,
+                    smooth,
+                    ..
+                }) => {
+                    prev_point = (*x, *y);
+                    c.push(prev_point);
+                    c.insert(0, last_oncurve);
+                    let smooth = smooth.as_ref().map(|s| s == "yes").unwrap_or(false);
+                    contour_acc.push((smooth, c));
+                    c = vec![];
+                    last_oncurve = prev_point;
+                }
+                Some(Point {
+                    type_: PointKind::Line,
+                    x,
+                    y,
+                    ..
+                }) => {
+                    if c.len() > 1 {
+                        return Err(GlifError {
+                            location: None,
+                            message: format!(
+                                "Contour has a line point after {} off-curve points.",
+                                c.len()
+                            ),
+                        });
+                    }
+                    if c.is_empty() {
+                        c.push(prev_point);
+                    }
+                    c.push((*x, *y));
+                    contour_acc.push((false, c));
+                    c = vec![];
+                    prev_point = (*x, *y);
+                    last_oncurve = prev_point;
+                }
+                Some(Point {
+                    type_: PointKind::Qcurve,
+                    x,
+                    y,
+                    smooth,
+                    ..
+                }) => {
+                    let smooth = smooth.as_ref().map(|s| s == "yes").unwrap_or(false);
+                    let end = (*x, *y);
+                    let offcurves = std::mem::take(&mut c);
+                    let mut start = last_oncurve;
+                    if offcurves.is_empty() {
+                        contour_acc.push((smooth, vec![start, end]));
+                    }
+                    // Consecutive off-curve points have an implied on-curve point
+                    // halfway between them.
+                    for (i, &off) in offcurves.iter().enumerate() {
+                        let next = if let Some(&n) = offcurves.get(i + 1) {
+                            (midpoint(off.0, n.0), midpoint(off.1, n.1))
+                        } else {
+                            end
+                        };
+                        contour_acc.push((smooth, vec![start, off, next]));
+                        start = next;
+                    }
+                    prev_point = end;
+                    last_oncurve = end;
+                }
+                None => {
+                    if !c.is_empty() {
+                        if !c.contains(&prev_point) {
+                            c.push(prev_point);
+                        }
+                        contour_acc.push((false, c));
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(Some((open, contour_acc)))
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 struct Component {
@@ -121,9 +256,16 @@ enum OutlineEntry {
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 struct Anchor {
+    #[serde(default)]
     name: String,
+    #[serde(default)]
     x: f64,
+    #[serde(default)]
     y: f64,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    identifier: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
@@ -172,36 +314,48 @@ pub struct Glif {
     anchors: Vec<Anchor>,
     #[serde(rename = "guideline", default)]
     guidelines: Vec<Guideline>,
+    /// Contents of the `<lib>` element, which is read separately as a property list.
+    #[serde(skip)]
+    lib: HashMap<String, DictValue>,
 }
 
+/// The glyphs of a [`Glif`], one for each of its code points. Yields an error if a contour's
+/// points don't form valid curves.
 pub struct GlifIterator {
     glif: Glif,
+    unicode: Vec<char>,
     kinds: Vec<(super::GlyphKind, Option<crate::unicode::names::Name>)>,
 }
 
 impl IntoIterator for Glif {
-    type Item = super::Glyph;
+    type Item = Result<super::Glyph, GlifError>;
     type IntoIter = GlifIterator;
 
     fn into_iter(mut self) -> Self::IntoIter {
-        let unicodes = std::mem::take(&mut self.unicode);
+        let unicode = std::mem::take(&mut self.unicode)
+            .into_iter()
+            .filter_map(|unicode| u32::from_str_radix(unicode.hex.as_str(), 16).ok())
+            .filter_map(|n| n.try_into().ok())
+            .collect::<Vec<char>>();
 
-        let kinds = if unicodes.is_empty() {
+        let kinds = if unicode.is_empty() {
             vec![(super::GlyphKind::Component, None)]
         } else {
-            unicodes
-                .into_iter()
-                .filter_map(|unicode| u32::from_str_radix(unicode.hex.as_str(), 16).ok())
-                .filter_map(|n| n.try_into().ok())
-                .map(|val| (super::GlyphKind::Char(val), val.char_name()))
+            unicode
+                .iter()
+                .map(|&val| (super::GlyphKind::Char(val), val.char_name()))
                 .collect::<Vec<_>>()
         };
-        GlifIterator { glif: self, kinds }
+        GlifIterator {
+            glif: self,
+            unicode,
+            kinds,
+        }
     }
 }
 
 impl Iterator for GlifIterator {
-    type Item = super::Glyph;
+    type Item = Result<super::Glyph, GlifError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         use super::{Bezier, Glyph};
@@ -210,14 +364,16 @@ impl Iterator for GlifIterator {
             name,
             outline,
             advance,
-            anchors: _,
+            anchors,
             guidelines,
+            lib,
             ..
         } = self.glif.clone();
         let mut ret = Glyph {
             name: name.into(),
             name2,
             kind,
+            unicode: self.unicode.clone(),
             width: advance.map(|a| a.width),
             contours: vec![],
             components: vec![],
@@ -234,7 +390,22 @@ impl Iterator for GlifIterator {
                         .build()
                 })
                 .collect::<Vec<_>>(),
+            anchors: anchors
+                .into_iter()
+                .map(|a| {
+                    super::Anchor::builder()
+                        .name(a.name)
+                        .identifier(a.identifier)
+                        .color(a.color)
+                        .x(a.x)
+                        .y(a.y)
+                        .build()
+                })
+                .collect::<Vec<_>>(),
+            lib,
             glif_source: String::new(),
+            filename: None,
+            modified: false,
         };
 
         if let Some(outline) = outline {
@@ -264,120 +435,382 @@ impl Iterator for GlifIterator {
                     }
                 };
 
-                let mut contour_acc = vec![];
-                let mut open = false;
-                let mut points = contour
-                    .point
-                    .iter()
-                    .collect::<std::collections::VecDeque<&_>>();
-                if points.is_empty() {
-                    continue;
-                }
-                let mut c;
-                let mut prev_point;
-                let mut last_oncurve;
-                if points.front().unwrap().is_move() {
-                    open = true;
-                    // Open contour
-                    let p = points.pop_front().unwrap();
-                    prev_point = (p.x, p.y);
-                    last_oncurve = prev_point;
-                    c = vec![prev_point];
-                } else {
-                    c = vec![];
-                    // Closed contour
-                    while points.front().unwrap().is_curve() {
-                        points.rotate_left(1);
-                    }
-                    let last_point = points.back().unwrap();
-                    prev_point = (last_point.x, last_point.y);
-                    let first_point = points.front().unwrap();
-                    last_oncurve = (first_point.x, first_point.y);
-                }
-                if points.front().unwrap().is_line() {
-                    let p = points.back().unwrap();
-                    prev_point = (p.x, p.y);
+                let (open, curves) = match contour.curves() {
+                    Ok(Some(c)) => c,
+                    Ok(None) => continue,
+                    Err(err) => return Some(Err(err)),
+                };
+                let super_ = super::Contour::new();
+                *super_.imp().open.borrow_mut() = open;
+                *super_.imp().curves.borrow_mut() = curves
+                    .into_iter()
+                    .map(|(smooth, points)| Bezier::new(smooth, points))
+                    .collect();
+                ret.contours.push(super_);
+            }
+        }
+
+        Some(Ok(ret))
+    }
+}
+
+/// GLIF syntax error, with the 1-based line and column in the document where it was found if
+/// known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlifError {
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl std::fmt::Display for GlifError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.location {
+            Some((line, column)) => {
+                write!(fmt, "{} (line {}, column {})", self.message, line, column)
+            }
+            None => write!(fmt, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for GlifError {}
+
+impl Glif {
+    pub fn from_str(s: &str) -> Result<Self, GlifError> {
+        /* Read the lib first: it goes through the whole document, so XML syntax errors are found
+         * with their position, which the deserializer doesn't report. */
+        let lib = parse_lib(s)?;
+        let mut g: Glif = quick_xml::de::from_str(s).map_err(|err| GlifError {
+            location: None,
+            message: err.to_string(),
+        })?;
+        g.lib = lib;
+        Ok(g)
+    }
+
+    /// Serialize to a format 2 GLIF XML document.
+    pub fn to_xml(&self) -> String {
+        use std::fmt::Write;
+
+        let mut ret = String::new();
+        let _ = writeln!(ret, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            ret,
+            r#"<glyph name="{}" format="{}">"#,
+            xml_escape(&self.name),
+            xml_escape(self.format.as_deref().unwrap_or("2"))
+        );
+        for Unicode { hex } in self.unicode.iter() {
+            let _ = writeln!(ret, "\t<unicode hex=\"{}\"/>", xml_escape(hex));
+        }
+        if let Some(Advance { width }) = self.advance.as_ref() {
+            let _ = writeln!(ret, "\t<advance width=\"{}\"/>", fmt_number(*width));
+        }
+        for g in self.guidelines.iter() {
+            let _ = write!(
+                ret,
+                "\t<guideline x=\"{}\" y=\"{}\" angle=\"{}\"",
+                fmt_number(g.x),
+                fmt_number(g.y),
+                fmt_number(g.angle)
+            );
+            for (attr, val) in [
+                ("name", &g.name),
+                ("color", &g.color),
+                ("identifier", &g.identifier),
+            ] {
+                if let Some(val) = val.as_ref() {
+                    let _ = write!(ret, " {}=\"{}\"", attr, xml_escape(val));
                 }
-                loop {
-                    match points.pop_front() {
-                        Some(Point {
-                            type_: PointKind::Move,
-                            ..
-                        }) => {
-                            panic!() // FIXME return Err
-                        }
-                        Some(Point {
-                            type_: PointKind::Offcurve,
-                            x,
-                            y,
-                            ..
-                        }) => {
-                            prev_point = (*x, *y);
-                            c.push(prev_point);
-                        }
-                        Some(Point {
-                            type_: PointKind::Curve,
-                            x,
-                            y,
-                            smooth,
-                            ..
-                        }) => {
-                            prev_point = (*x, *y);
-                            c.push(prev_point);
-                            c.insert(0, last_oncurve);
-                            let smooth = smooth.as_ref().map(|s| s == "yes").unwrap_or(false);
-                            contour_acc.push(Bezier::new(smooth, c));
-                            c = vec![];
-                            last_oncurve = prev_point;
-                        }
-                        Some(Point {
-                            type_: PointKind::Line,
-                            x,
-                            y,
-                            ..
-                        }) => {
-                            assert!(c.is_empty() || c.len() == 1);
-                            if c.is_empty() {
-                                c.push(prev_point);
+            }
+            let _ = writeln!(ret, "/>");
+        }
+        if let Some(Outline { countours }) = self.outline.as_ref() {
+            let _ = writeln!(ret, "\t<outline>");
+            for entry in countours {
+                match entry {
+                    OutlineEntry::Contour(Contour { point }) => {
+                        let _ = writeln!(ret, "\t\t<contour>");
+                        for p in point {
+                            let _ = write!(ret, "\t\t\t<point x=\"{}\" y=\"{}\"", p.x, p.y);
+                            if let Some(type_) = p.type_.as_str() {
+                                let _ = write!(ret, " type=\"{}\"", type_);
                             }
-                            c.push((*x, *y));
-                            contour_acc.push(Bezier::new(false, c));
-                            c = vec![];
-                            prev_point = (*x, *y);
-                            last_oncurve = prev_point;
-                        }
-                        Some(Point {
-                            type_: PointKind::Qcurve,
-                            ..
-                        }) => {
-                            todo!()
+                            if let Some(smooth) = p.smooth.as_ref() {
+                                let _ = write!(ret, " smooth=\"{}\"", xml_escape(smooth));
+                            }
+                            let _ = writeln!(ret, "/>");
                         }
-                        None => {
-                            if !c.is_empty() {
-                                if !c.contains(&prev_point) {
-                                    c.push(prev_point);
-                                }
-                                contour_acc.push(Bezier::new(false, c));
+                        let _ = writeln!(ret, "\t\t</contour>");
+                    }
+                    OutlineEntry::Component(c) => {
+                        let _ = write!(ret, "\t\t<component base=\"{}\"", xml_escape(&c.base));
+                        for (attr, val, default) in [
+                            ("xScale", c.x_scale, 1.0),
+                            ("xyScale", c.xy_scale, 0.0),
+                            ("yxScale", c.yx_scale, 0.0),
+                            ("yScale", c.y_scale, 1.0),
+                            ("xOffset", c.x_offset, 0.0),
+                            ("yOffset", c.y_offset, 0.0),
+                        ] {
+                            if val != default {
+                                let _ = write!(ret, " {}=\"{}\"", attr, fmt_number(val));
                             }
-                            break;
                         }
+                        let _ = writeln!(ret, "/>");
                     }
                 }
-                let super_ = super::Contour::new();
-                *super_.imp().open.borrow_mut() = open;
-                *super_.imp().curves.borrow_mut() = contour_acc;
-                ret.contours.push(super_);
             }
+            let _ = writeln!(ret, "\t</outline>");
+        }
+        for a in self.anchors.iter() {
+            let _ = write!(
+                ret,
+                "\t<anchor name=\"{}\" x=\"{}\" y=\"{}\"",
+                xml_escape(&a.name),
+                fmt_number(a.x),
+                fmt_number(a.y)
+            );
+            for (attr, val) in [("color", &a.color), ("identifier", &a.identifier)] {
+                if let Some(val) = val.as_ref() {
+                    let _ = write!(ret, " {}=\"{}\"", attr, xml_escape(val));
+                }
+            }
+            let _ = writeln!(ret, "/>");
+        }
+        if !self.lib.is_empty() {
+            let _ = writeln!(ret, "\t<lib>");
+            crate::utils::plist::write_dict(&self.lib, &mut ret, 2);
+            let _ = writeln!(ret, "\t</lib>");
+        }
+        let _ = writeln!(ret, "</glyph>");
+        ret
+    }
+}
+
+/// Read the dictionary in the `<lib>` element of the GLIF document `s`, if there is one.
+fn parse_lib(s: &str) -> Result<HashMap<String, DictValue>, GlifError> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(s);
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    let mut start = None;
+    let error = |offset: usize, message: String| GlifError {
+        location: Some(line_column(s, offset)),
+        message,
+    };
+    loop {
+        let position = reader.buffer_position();
+        match reader
+            .read_event(&mut buf)
+            .map_err(|err| error(reader.buffer_position(), err.to_string()))?
+        {
+            Event::Start(ref e) => {
+                depth += 1;
+                if depth == 2 && e.name() == b"lib" {
+                    start = Some(reader.buffer_position());
+                }
+            }
+            Event::End(ref e) => {
+                if depth == 2 && e.name() == b"lib" {
+                    if let Some(start) = start {
+                        return match crate::utils::plist::parse(&s[start..position]) {
+                            Ok(DictValue::Dict(dict)) => Ok(dict),
+                            Ok(_) => Err(error(start, "<lib> does not contain a <dict>.".into())),
+                            Err(err) => {
+                                /* The plist error is located relative to the start of the lib. */
+                                let (line, column) = line_column(s, start);
+                                Err(GlifError {
+                                    location: Some(if err.line == 1 {
+                                        (line, column + err.column - 1)
+                                    } else {
+                                        (line + err.line - 1, err.column)
+                                    }),
+                                    message: format!("Invalid <lib>: {}", err.message),
+                                })
+                            }
+                        };
+                    }
+                }
+                depth -= 1;
+            }
+            Event::Eof => return Ok(HashMap::default()),
+            _ => {}
         }
+        buf.clear();
+    }
+}
 
-        Some(ret)
+impl PointKind {
+    /// Value of the `type` attribute. Off-curve points omit it.
+    fn as_str(&self) -> Option<&'static str> {
+        match self {
+            PointKind::Move => Some("move"),
+            PointKind::Line => Some("line"),
+            PointKind::Offcurve => None,
+            PointKind::Curve => Some("curve"),
+            PointKind::Qcurve => Some("qcurve"),
+        }
     }
 }
 
-impl Glif {
-    pub fn from_str(s: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let g: Glif = quick_xml::de::from_str(s)?;
-        Ok(g)
+impl From<&super::Glyph> for Glif {
+    fn from(glyph: &super::Glyph) -> Self {
+        let unicode = match (glyph.unicode.is_empty(), &glyph.kind) {
+            (true, super::GlyphKind::Char(c)) => vec![*c],
+            _ => glyph.unicode.clone(),
+        };
+        let mut countours = glyph
+            .contours
+            .iter()
+            .map(contour_points)
+            .filter(|point| !point.is_empty())
+            .map(|point| OutlineEntry::Contour(Contour { point }))
+            .collect::<Vec<_>>();
+        countours.extend(glyph.components.iter().map(|c| {
+            OutlineEntry::Component(Component {
+                base: c.base_name.clone(),
+                x_offset: c.x_offset,
+                y_offset: c.y_offset,
+                x_scale: c.x_scale,
+                xy_scale: c.xy_scale,
+                yx_scale: c.yx_scale,
+                y_scale: c.y_scale,
+            })
+        }));
+        Glif {
+            name: glyph.name.to_string(),
+            format: Some("2".to_string()),
+            unicode: unicode
+                .into_iter()
+                .map(|c| Unicode {
+                    hex: format!("{:04X}", c as u32),
+                })
+                .collect(),
+            advance: glyph.width.map(|width| Advance { width }),
+            outline: if countours.is_empty() {
+                None
+            } else {
+                Some(Outline { countours })
+            },
+            anchors: glyph
+                .anchors
+                .iter()
+                .map(|a| Anchor {
+                    name: a.name(),
+                    x: a.x(),
+                    y: a.y(),
+                    color: a.color(),
+                    identifier: a.identifier(),
+                })
+                .collect(),
+            guidelines: glyph
+                .guidelines
+                .iter()
+                .map(|g| Guideline {
+                    name: g.name(),
+                    identifier: g.identifier(),
+                    color: g.imp().color.borrow().clone(),
+                    angle: g.angle(),
+                    x: g.x() as f64,
+                    y: g.y() as f64,
+                })
+                .collect(),
+            lib: glyph.lib.clone(),
+        }
+    }
+}
+
+/// The coordinate halfway between `a` and `b`, rounded to the nearest integer with halves away
+/// from zero, so that negative coordinates aren't biased the way integer division would.
+fn midpoint(a: i64, b: i64) -> i64 {
+    ((a + b) as f64 / 2.).round() as i64
+}
+
+/// Flatten a contour's curves back into a GLIF point list.
+///
+/// Each curve's first point is the previous curve's last point, so only the first curve
+/// contributes its starting point: as a `move` point for open contours, or as a `line` point if a
+/// closed contour doesn't already end where it started.
+fn contour_points(contour: &super::Contour) -> Vec<Point> {
+    let open = *contour.open().borrow();
+    let curves = contour.curves().borrow();
+    let mut ret = vec![];
+    let first_point = curves
+        .first()
+        .and_then(|b| b.points().borrow().first().copied());
+    let last_point = curves
+        .last()
+        .and_then(|b| b.points().borrow().last().copied());
+    if let Some((x, y)) = first_point {
+        if open {
+            ret.push(Point {
+                x,
+                y,
+                type_: PointKind::Move,
+                smooth: None,
+            });
+        } else if first_point != last_point {
+            ret.push(Point {
+                x,
+                y,
+                type_: PointKind::Line,
+                smooth: None,
+            });
+        }
+    }
+    for curve in curves.iter() {
+        let points = curve.points().borrow();
+        let smooth = if *curve.smooth().borrow() {
+            Some("yes".to_string())
+        } else {
+            None
+        };
+        let type_ = match points.len() {
+            0 | 1 => continue,
+            2 => PointKind::Line,
+            3 => PointKind::Qcurve,
+            _ => PointKind::Curve,
+        };
+        let n = points.len();
+        for &(x, y) in &points[1..n - 1] {
+            ret.push(Point {
+                x,
+                y,
+                type_: PointKind::Offcurve,
+                smooth: None,
+            });
+        }
+        let (x, y) = points[n - 1];
+        ret.push(Point {
+            x,
+            y,
+            type_,
+            smooth,
+        });
+    }
+    ret
+}
+
+fn fmt_number(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < i64::MAX as f64 {
+        (v as i64).to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+impl super::Glyph {
+    /// Returns the glyph as a format 2 `.glif` document.
+    ///
+    /// Glyphs that haven't been modified since they were loaded return their original source
+    /// unchanged, so that saving a project only touches the files of edited glyphs.
+    pub fn to_glif(&self) -> String {
+        if !self.modified && !self.glif_source.is_empty() {
+            return self.glif_source.clone();
+        }
+        Glif::from(self).to_xml()
     }
 }
 
@@ -392,10 +825,195 @@ impl Default for Glif {
 fn test_glif_parse() {
     let g: Glif = quick_xml::de::from_str(_UPPERCASE_A_GLIF).unwrap();
     println!("{:#?}", g);
-    let g: super::Glyph = g.into();
+    let g: super::Glyph = g.into_iter().next().unwrap().unwrap();
     println!("\n\n{:#?}", g);
 }
 
+#[test]
+fn test_glif_write() {
+    type Curves = Vec<(bool, Vec<(i64, i64)>)>;
+
+    fn curves(g: &super::Glyph) -> Vec<(bool, Curves)> {
+        g.contours
+            .iter()
+            .map(|c| {
+                (
+                    *c.open().borrow(),
+                    c.curves()
+                        .borrow()
+                        .iter()
+                        .map(|b| (*b.smooth().borrow(), b.points().borrow().clone()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    for src in [_LOWERCASE_B_GLIF, _UPPERCASE_A_GLIF] {
+        let mut g: super::Glyph = Glif::from_str(src)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        g.glif_source = src.to_string();
+        assert_eq!(g.to_glif(), src);
+
+        g.modified = true;
+        let written = g.to_glif();
+        let mut g2: super::Glyph = Glif::from_str(&written)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(g.name, g2.name);
+        assert_eq!(g.kind, g2.kind);
+        assert_eq!(g.unicode, g2.unicode);
+        assert_eq!(g.width, g2.width);
+        let anchors = |g: &super::Glyph| {
+            g.anchors
+                .iter()
+                .map(|a| (a.name(), a.x(), a.y()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(anchors(&g).len(), 3);
+        assert_eq!(anchors(&g), anchors(&g2));
+        assert_eq!(curves(&g), curves(&g2));
+        g2.modified = true;
+        assert_eq!(written, g2.to_glif());
+    }
+}
+
+#[test]
+fn test_glif_lib() {
+    let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="a" format="2">
+  <advance width="500"/>
+  <lib>
+    <dict>
+      <key>public.markColor</key>
+      <string>1,0,0,1</string>
+      <key>com.example.export</key>
+      <dict>
+        <key>skip</key>
+        <true/>
+        <key>names</key>
+        <array><string>uni0061</string></array>
+      </dict>
+    </dict>
+  </lib>
+</glyph>
+"#;
+    let mut g: super::Glyph = Glif::from_str(src)
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(g.width, Some(500.));
+    assert_eq!(
+        g.lib["public.markColor"],
+        DictValue::String("1,0,0,1".to_string())
+    );
+    assert!(
+        matches!(&g.lib["com.example.export"], DictValue::Dict(d) if d["skip"] == DictValue::Boolean(true))
+    );
+    g.modified = true;
+    let written = g.to_glif();
+    let g2: super::Glyph = Glif::from_str(&written)
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(g.lib, g2.lib);
+
+    assert!(Glif::from_str(
+        &src.replace("<dict>\n      <key>public", "<array>\n      <key>public")
+    )
+    .is_err());
+    g.lib.clear();
+    assert!(!g.to_glif().contains("<lib>"));
+}
+
+#[test]
+fn test_glif_errors() {
+    let err = Glif::from_str(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<glyph name=\"a\" format=\"2\">\n  <outline>\n  </glyph>\n",
+    )
+    .unwrap_err();
+    assert_eq!(err.location.map(|(line, _)| line), Some(4));
+
+    let err = Glif::from_str(
+        "<glyph name=\"a\" format=\"2\">\n  <lib>\n    <dict>\n      <key>k</key>\n      <integer>x</integer>\n    </dict>\n  </lib>\n</glyph>\n",
+    )
+    .unwrap_err();
+    assert!(err.message.starts_with("Invalid <lib>"), "{}", err);
+    assert_eq!(err.location.map(|(line, _)| line), Some(5));
+
+    let err = Glif::from_str("<glyph format=\"2\"/>").unwrap_err();
+    assert_eq!(err.location, None);
+}
+
+#[test]
+fn test_glif_implied_on_curve_points() {
+    let glyph: super::Glyph = Glif::from_str(
+        r#"<glyph name="q" format="2">
+  <outline>
+    <contour>
+      <point x="0" y="0" type="line"/>
+      <point x="-1" y="-3"/>
+      <point x="-2" y="-4"/>
+      <point x="3" y="5"/>
+      <point x="0" y="-10" type="qcurve"/>
+    </contour>
+  </outline>
+</glyph>
+"#,
+    )
+    .unwrap()
+    .into_iter()
+    .next()
+    .unwrap()
+    .unwrap();
+    let on_curve = glyph.contours[0]
+        .curves()
+        .borrow()
+        .iter()
+        .map(|b| *b.points().borrow().last().unwrap())
+        .collect::<Vec<_>>();
+    /* (-1 + -2) / 2 and (-3 + -4) / 2 round away from zero, (-2 + 3) / 2 and (-4 + 5) / 2 too. */
+    assert_eq!(on_curve, vec![(0, 0), (-2, -4), (1, 1), (0, -10)]);
+}
+
+#[test]
+fn test_glif_invalid_contours() {
+    let glyph = |points: &str| {
+        Glif::from_str(&format!(
+            r#"<glyph name="a" format="2"><outline><contour>{}</contour></outline></glyph>"#,
+            points
+        ))
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+    };
+    assert!(glyph(r#"<point x="0" y="0" type="move"/><point x="1" y="1" type="line"/>"#).is_ok());
+    let err = glyph(
+        r#"<point x="0" y="0" type="line"/><point x="1" y="1" type="move"/>
+        <point x="2" y="0" type="line"/>"#,
+    )
+    .unwrap_err();
+    assert!(err.message.contains("move point"), "{}", err);
+    let err = glyph(
+        r#"<point x="0" y="0" type="line"/><point x="1" y="1"/><point x="2" y="1"/>
+        <point x="3" y="0" type="line"/>"#,
+    )
+    .unwrap_err();
+    assert!(err.message.contains("2 off-curve points"), "{}", err);
+}
+
 const _LOWERCASE_B_GLIF: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
 <glyph name="b" format="2">
 	<unicode hex="0062"/>
@@ -20,9 +20,8 @@
  */
 
 use super::*;
+use glib::prelude::*;
 use glib::{ParamFlags, ParamSpec, ParamSpecDouble, ParamSpecInt64, ParamSpecString, Value};
-use gtk::glib;
-use gtk::prelude::*;
 
 use std::cell::RefCell;
 
@@ -129,6 +128,7 @@ mod imp {
     }
 
     impl Guideline {
+        #[cfg(feature = "cairo")]
         pub fn draw(
             &self,
             cr: &Context,
@@ -0,0 +1,713 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Read the `<path>` elements of SVG documents as glyph contours.
+//!
+//! The document's height, from its `viewBox` or else its `height`, is scaled to the font's units
+//! per em with the top of the document at the ascender, which is how
+//! [`glyph_svg`](crate::export::glyph_svg) lays glyphs out. Documents without either are read
+//! as one font unit per user unit.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::export::{apply, compose, Point, Segment, Transform, IDENTITY};
+use crate::glyphs::Contour;
+use crate::project::FontInfo;
+use crate::utils::curves::Bezier;
+
+/// A subpath of SVG path data.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubPath {
+    pub start: Point,
+    pub segments: Vec<Segment>,
+    /// Whether the subpath ends with a `Z` command.
+    pub closed: bool,
+}
+
+impl SubPath {
+    fn transformed(&self, t: Transform) -> Self {
+        let f = |p: Point| apply(t, p);
+        SubPath {
+            start: f(self.start),
+            segments: self
+                .segments
+                .iter()
+                .map(|s| match *s {
+                    Segment::Line(p) => Segment::Line(f(p)),
+                    Segment::Quad(c, p) => Segment::Quad(f(c), f(p)),
+                    Segment::Cubic(c1, c2, p) => Segment::Cubic(f(c1), f(c2), f(p)),
+                })
+                .collect(),
+            closed: self.closed,
+        }
+    }
+
+    /// A contour with the subpath's points rounded to integers. Closed subpaths that don't end at
+    /// their start get a line back to it.
+    pub fn to_contour(&self) -> Contour {
+        let round = |(x, y): Point| (x.round() as i64, y.round() as i64);
+        let start = round(self.start);
+        let mut curves = vec![];
+        let mut current = start;
+        for s in &self.segments {
+            let mut points = vec![current];
+            match *s {
+                Segment::Line(p) => points.push(round(p)),
+                Segment::Quad(c, p) => points.extend([round(c), round(p)]),
+                Segment::Cubic(c1, c2, p) => points.extend([round(c1), round(c2), round(p)]),
+            }
+            /* Skip lines that vanish when rounded. */
+            if points.len() == 2 && points[0] == points[1] {
+                continue;
+            }
+            current = *points.last().unwrap();
+            curves.push(Bezier::new(false, points));
+        }
+        if self.closed && current != start {
+            curves.push(Bezier::new(false, vec![current, start]));
+        }
+        let contour = Contour::new();
+        *contour.open().borrow_mut() = !self.closed;
+        *contour.curves().borrow_mut() = curves;
+        contour
+    }
+}
+
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Tokens<'_> {
+    fn skip_separators(&mut self) {
+        while self.pos < self.data.len()
+            && (self.data[self.pos].is_ascii_whitespace() || self.data[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, what: &str) -> String {
+        /* The data comes from a `&str`, so it is valid UTF-8 from any character boundary on. */
+        let mut start = self.pos.min(self.data.len());
+        while start > 0 && start < self.data.len() && self.data[start] & 0xC0 == 0x80 {
+            start -= 1;
+        }
+        match std::str::from_utf8(&self.data[start..])
+            .ok()
+            .and_then(|s| s.chars().next())
+        {
+            Some(c) => format!(
+                "Invalid path data: expected {} at position {}, found {:?}.",
+                what,
+                start + 1,
+                c
+            ),
+            None => format!("Invalid path data: expected {} at end of data.", what),
+        }
+    }
+
+    /// The next command letter, if there is one before the next number.
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        match self.data.get(self.pos) {
+            Some(c) if c.is_ascii_alphabetic() => {
+                self.pos += 1;
+                Some(*c)
+            }
+            _ => None,
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.data.len()
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |this: &mut Self| {
+            let from = this.pos;
+            while this.pos < this.data.len() && this.data[this.pos].is_ascii_digit() {
+                this.pos += 1;
+            }
+            this.pos > from
+        };
+        if matches!(self.data.get(self.pos), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut any = digits(self);
+        if self.data.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            any |= digits(self);
+        }
+        if !any {
+            self.pos = start;
+            return Err(self.error("a number"));
+        }
+        if matches!(self.data.get(self.pos), Some(b'e' | b'E')) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if matches!(self.data.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mantissa_end;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .ok_or_else(|| {
+                self.pos = start;
+                self.error("a number")
+            })
+    }
+
+    fn point(&mut self) -> Result<Point, String> {
+        Ok((self.number()?, self.number()?))
+    }
+
+    /// Arc flags are a single `0` or `1` and may be written without separators.
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        match self.data.get(self.pos) {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            _ => Err(self.error("an arc flag")),
+        }
+    }
+}
+
+/// Parse the `d` attribute of a `<path>`. Both absolute and relative forms of all commands are
+/// supported; elliptical arcs are converted to cubic curves.
+pub fn parse_path_data(d: &str) -> Result<Vec<SubPath>, String> {
+    let mut tokens = Tokens {
+        data: d.as_bytes(),
+        pos: 0,
+    };
+    let mut ret: Vec<SubPath> = vec![];
+    let mut path: Option<SubPath> = None;
+    let mut current: Point = (0., 0.);
+    let mut command: Option<u8> = None;
+    /* Control point to reflect for `S` and `T`, if the previous segment was of the same kind. */
+    let mut last_cubic_control: Option<Point> = None;
+    let mut last_quad_control: Option<Point> = None;
+    while !tokens.at_end() {
+        let explicit = tokens.command();
+        let cmd = match (explicit, command) {
+            (Some(c), _) => c,
+            (None, Some(b'Z' | b'z')) | (None, None) => return Err(tokens.error("a command")),
+            /* Coordinates following a moveto are implicit linetos. */
+            (None, Some(b'M')) => b'L',
+            (None, Some(b'm')) => b'l',
+            (None, Some(c)) => c,
+        };
+        if command.is_none() && !matches!(cmd, b'M' | b'm') {
+            tokens.pos -= 1;
+            return Err(tokens.error("a moveto command"));
+        }
+        command = Some(cmd);
+        let relative = cmd.is_ascii_lowercase();
+        let offset = |(x, y): Point, current: Point| {
+            if relative {
+                (x + current.0, y + current.1)
+            } else {
+                (x, y)
+            }
+        };
+        if !matches!(cmd, b'M' | b'm' | b'Z' | b'z') {
+            /* Drawing after a closepath continues from the start of the closed subpath. */
+            if path.as_ref().map(|p| p.closed).unwrap_or(true) {
+                if let Some(p) = path.take() {
+                    ret.push(p);
+                }
+                path = Some(SubPath {
+                    start: current,
+                    ..SubPath::default()
+                });
+            }
+        }
+        let mut cubic_control = None;
+        let mut quad_control = None;
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                let p = offset(tokens.point()?, current);
+                if let Some(p) = path.take() {
+                    ret.push(p);
+                }
+                path = Some(SubPath {
+                    start: p,
+                    ..SubPath::default()
+                });
+                current = p;
+            }
+            b'Z' => {
+                if let Some(p) = path.as_mut() {
+                    p.closed = true;
+                    current = p.start;
+                }
+            }
+            b'L' => {
+                current = offset(tokens.point()?, current);
+                path.as_mut().unwrap().segments.push(Segment::Line(current));
+            }
+            b'H' => {
+                let x = tokens.number()?;
+                current = (if relative { current.0 + x } else { x }, current.1);
+                path.as_mut().unwrap().segments.push(Segment::Line(current));
+            }
+            b'V' => {
+                let y = tokens.number()?;
+                current = (current.0, if relative { current.1 + y } else { y });
+                path.as_mut().unwrap().segments.push(Segment::Line(current));
+            }
+            b'C' | b'S' => {
+                let c1 = if cmd.eq_ignore_ascii_case(&b'C') {
+                    offset(tokens.point()?, current)
+                } else {
+                    last_cubic_control
+                        .map(|c| (2. * current.0 - c.0, 2. * current.1 - c.1))
+                        .unwrap_or(current)
+                };
+                let c2 = offset(tokens.point()?, current);
+                let p = offset(tokens.point()?, current);
+                path.as_mut()
+                    .unwrap()
+                    .segments
+                    .push(Segment::Cubic(c1, c2, p));
+                cubic_control = Some(c2);
+                current = p;
+            }
+            b'Q' | b'T' => {
+                let c = if cmd.eq_ignore_ascii_case(&b'Q') {
+                    offset(tokens.point()?, current)
+                } else {
+                    last_quad_control
+                        .map(|c| (2. * current.0 - c.0, 2. * current.1 - c.1))
+                        .unwrap_or(current)
+                };
+                let p = offset(tokens.point()?, current);
+                path.as_mut().unwrap().segments.push(Segment::Quad(c, p));
+                quad_control = Some(c);
+                current = p;
+            }
+            b'A' => {
+                let (rx, ry) = tokens.point()?;
+                let rotation = tokens.number()?;
+                let large_arc = tokens.flag()?;
+                let sweep = tokens.flag()?;
+                let p = offset(tokens.point()?, current);
+                path.as_mut().unwrap().segments.extend(arc_to_cubics(
+                    current,
+                    (rx, ry),
+                    rotation,
+                    large_arc,
+                    sweep,
+                    p,
+                ));
+                current = p;
+            }
+            _ => {
+                tokens.pos -= 1;
+                return Err(tokens.error("a command"));
+            }
+        }
+        last_cubic_control = cubic_control;
+        last_quad_control = quad_control;
+    }
+    ret.extend(path);
+    ret.retain(|p| !p.segments.is_empty());
+    Ok(ret)
+}
+
+/// Cubic curves approximating an SVG elliptical arc from `from` to `to`, at most a quarter turn
+/// each. See the SVG specification's implementation notes for the conversion from endpoint to
+/// center parameterization.
+fn arc_to_cubics(
+    from: Point,
+    (rx, ry): (f64, f64),
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+) -> Vec<Segment> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    if from == to {
+        return vec![];
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0. || ry == 0. {
+        return vec![Segment::Line(to)];
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from.0 - to.0) / 2., (from.1 - to.1) / 2.);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = (
+        cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.,
+        sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.,
+    );
+    let angle = |(ux, uy): Point, (vx, vy): Point| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let start_angle = angle((1., 0.), ((x1 - cx1) / rx, (y1 - cy1) / ry));
+    let mut sweep_angle = angle(
+        ((x1 - cx1) / rx, (y1 - cy1) / ry),
+        ((-x1 - cx1) / rx, (-y1 - cy1) / ry),
+    );
+    if !sweep && sweep_angle > 0. {
+        sweep_angle -= 2. * PI;
+    } else if sweep && sweep_angle < 0. {
+        sweep_angle += 2. * PI;
+    }
+
+    /* Points on the unit circle are mapped onto the ellipse. */
+    let map = |(ux, uy): Point| {
+        (
+            center.0 + rx * cos * ux - ry * sin * uy,
+            center.1 + rx * sin * ux + ry * cos * uy,
+        )
+    };
+    let count = (sweep_angle.abs() / FRAC_PI_2 - 1e-9).ceil().max(1.) as usize;
+    let delta = sweep_angle / count as f64;
+    let k = 4. / 3. * (delta / 4.).tan();
+    let mut ret = Vec::with_capacity(count);
+    for i in 0..count {
+        let t1 = start_angle + i as f64 * delta;
+        let t2 = t1 + delta;
+        let (s1, c1) = t1.sin_cos();
+        let (s2, c2) = t2.sin_cos();
+        let end = if i + 1 == count { to } else { map((c2, s2)) };
+        ret.push(Segment::Cubic(
+            map((c1 - k * s1, s1 + k * c1)),
+            map((c2 + k * s2, s2 - k * c2)),
+            end,
+        ));
+    }
+    ret
+}
+
+/// Parse an SVG `transform` attribute.
+pub fn parse_transform(value: &str) -> Result<Transform, String> {
+    let mut ret = IDENTITY;
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let invalid = || format!("Invalid transform {:?}.", value);
+        let open = rest.find('(').ok_or_else(invalid)?;
+        let close = rest[open..]
+            .find(')')
+            .map(|i| open + i)
+            .ok_or_else(invalid)?;
+        let name = rest[..open].trim();
+        let args = rest[open + 1..close]
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .map(str::parse::<f64>)
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| invalid())?;
+        let t = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => [a, b, c, d, e, f],
+            ("translate", &[x]) => [1., 0., 0., 1., x, 0.],
+            ("translate", &[x, y]) => [1., 0., 0., 1., x, y],
+            ("scale", &[s]) => [s, 0., 0., s, 0., 0.],
+            ("scale", &[x, y]) => [x, 0., 0., y, 0., 0.],
+            ("rotate", &[a]) | ("rotate", &[a, _, _]) => {
+                let (sin, cos) = a.to_radians().sin_cos();
+                let rotation = [cos, sin, -sin, cos, 0., 0.];
+                if let [_, x, y] = args[..] {
+                    compose(
+                        [1., 0., 0., 1., x, y],
+                        compose(rotation, [1., 0., 0., 1., -x, -y]),
+                    )
+                } else {
+                    rotation
+                }
+            }
+            ("skewX", &[a]) => [1., 0., a.to_radians().tan(), 1., 0., 0.],
+            ("skewY", &[a]) => [1., a.to_radians().tan(), 0., 1., 0., 0.],
+            _ => return Err(invalid()),
+        };
+        ret = compose(ret, t);
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Ok(ret)
+}
+
+/// Leading number of a length such as `100`, `12.5px` or `10mm`. Units are ignored.
+fn parse_length(value: &str) -> Option<f64> {
+    let end = value
+        .trim()
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(value.trim().len());
+    value.trim()[..end].parse().ok()
+}
+
+/// Elements whose contents aren't drawn where they appear.
+const NOT_RENDERED: &[&[u8]] = &[
+    b"defs",
+    b"clipPath",
+    b"mask",
+    b"marker",
+    b"pattern",
+    b"symbol",
+    b"metadata",
+];
+
+/// Read every `<path>` of the SVG document `svg` as contours in the font units of `info`,
+/// applying the `transform`s of the paths and their ancestors.
+pub fn svg_to_contours(svg: &str, info: &FontInfo) -> Result<Vec<Contour>, String> {
+    let mut reader = Reader::from_str(svg);
+    let mut buf = Vec::new();
+    /* Transformation to font units of each open element. */
+    let mut stack: Vec<Transform> = vec![];
+    let mut skip_depth = 0;
+    let mut ret = vec![];
+    let attribute = |e: &BytesStart, reader: &Reader<&[u8]>, name: &[u8]| {
+        e.attributes()
+            .flatten()
+            .find(|a| a.key == name)
+            .and_then(|a| a.unescape_and_decode_value(reader).ok())
+    };
+    loop {
+        let event = reader
+            .read_event(&mut buf)
+            .map_err(|err| format!("Invalid SVG document: {}", err))?;
+        let (e, empty) = match event {
+            Event::Start(ref e) => (e, false),
+            Event::Empty(ref e) => (e, true),
+            Event::End(_) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                }
+                stack.pop();
+                buf.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+        let name = e.local_name().to_vec();
+        let mut transform = match stack.last() {
+            Some(t) => *t,
+            None if name == b"svg" => {
+                let upm = info.units_per_em();
+                let view_box = attribute(e, &reader, b"viewBox").and_then(|v| {
+                    let v = v
+                        .split(|c: char| c.is_whitespace() || c == ',')
+                        .filter(|s| !s.is_empty())
+                        .map(str::parse::<f64>)
+                        .collect::<Result<Vec<f64>, _>>()
+                        .ok()?;
+                    (v.len() == 4 && v[3] > 0.).then(|| (v[0], v[1], v[3]))
+                });
+                let (x, y, height) = view_box
+                    .or_else(|| {
+                        attribute(e, &reader, b"height")
+                            .as_deref()
+                            .and_then(parse_length)
+                            .filter(|h| *h > 0.)
+                            .map(|h| (0., 0., h))
+                    })
+                    .unwrap_or((0., 0., upm));
+                let scale = upm / height;
+                [
+                    scale,
+                    0.,
+                    0.,
+                    -scale,
+                    -x * scale,
+                    info.ascender() + y * scale,
+                ]
+            }
+            None => return Err("Not an SVG document.".to_string()),
+        };
+        if let Some(t) = attribute(e, &reader, b"transform") {
+            transform = compose(transform, parse_transform(&t)?);
+        }
+        if skip_depth > 0 || NOT_RENDERED.contains(&name.as_slice()) {
+            if !empty {
+                skip_depth += 1;
+            }
+        } else if name == b"path" {
+            if let Some(d) = attribute(e, &reader, b"d") {
+                ret.extend(
+                    parse_path_data(&d)?
+                        .iter()
+                        .map(|p| p.transformed(transform).to_contour()),
+                );
+            }
+        }
+        if !empty {
+            stack.push(transform);
+        }
+        buf.clear();
+    }
+    if ret.is_empty() {
+        return Err("The SVG document has no paths.".to_string());
+    }
+    Ok(ret)
+}
+
+#[test]
+fn test_svg_import() {
+    use glib::subclass::prelude::*;
+
+    let close = |a: Point, b: Point| (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6;
+
+    let paths = parse_path_data("M10,20 l10 0 h5 V40 H10 z m5 5 c1 1 2 2 3 3 s1 1 2 2").unwrap();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(
+        paths[0],
+        SubPath {
+            start: (10., 20.),
+            segments: vec![
+                Segment::Line((20., 20.)),
+                Segment::Line((25., 20.)),
+                Segment::Line((25., 40.)),
+                Segment::Line((10., 40.)),
+            ],
+            closed: true,
+        }
+    );
+    /* A moveto after a closepath is relative to the start of the closed subpath. */
+    assert_eq!(paths[1].start, (15., 25.));
+    assert_eq!(
+        paths[1].segments,
+        vec![
+            Segment::Cubic((16., 26.), (17., 27.), (18., 28.)),
+            Segment::Cubic((19., 29.), (19., 29.), (20., 30.)),
+        ]
+    );
+    let paths = parse_path_data("M0 0Q10 10 20 0T40 0t20 0M1e1-1.5.5.5").unwrap();
+    assert_eq!(
+        paths[0].segments,
+        vec![
+            Segment::Quad((10., 10.), (20., 0.)),
+            Segment::Quad((30., -10.), (40., 0.)),
+            Segment::Quad((50., 10.), (60., 0.)),
+        ]
+    );
+    assert_eq!(paths.len(), 2);
+    assert_eq!(paths[1].segments, vec![Segment::Line((0.5, 0.5))]);
+    assert_eq!(paths[1].start, (10., -1.5));
+    assert!(parse_path_data("L 10 10").is_err());
+    assert!(parse_path_data("M 10 10 Z 5 5").is_err());
+    assert!(parse_path_data("M 10 10 L 5").is_err());
+
+    /* A half circle of radius 10, with compact arc flags. */
+    let paths = parse_path_data("M0 0a10 10 0 0110 10").unwrap();
+    assert_eq!(paths[0].segments.len(), 1);
+    let paths = parse_path_data("M0 0A10 10 0 1 1 0 20").unwrap();
+    assert_eq!(paths[0].segments.len(), 2);
+    let mut current = paths[0].start;
+    for s in &paths[0].segments {
+        if let Segment::Cubic(c1, c2, p) = *s {
+            for t in [0.25, 0.5, 0.75] {
+                let mt = 1. - t;
+                let x = mt * mt * mt * current.0
+                    + 3. * mt * mt * t * c1.0
+                    + 3. * mt * t * t * c2.0
+                    + t * t * t * p.0;
+                let y = mt * mt * mt * current.1
+                    + 3. * mt * mt * t * c1.1
+                    + 3. * mt * t * t * c2.1
+                    + t * t * t * p.1;
+                let r = (x * x + (y - 10.) * (y - 10.)).sqrt();
+                assert!((r - 10.).abs() < 0.01, "{}", r);
+            }
+            current = p;
+        } else {
+            panic!("{:?}", s);
+        }
+    }
+    assert!(close(current, (0., 20.)));
+    /* Sweeping clockwise in SVG's y-down coordinates goes through positive x. */
+    assert!(matches!(paths[0].segments[0], Segment::Cubic(_, _, p) if close(p, (10., 10.))));
+
+    let t = parse_transform("translate(10) scale(2, 3) rotate(90 1 1)").unwrap();
+    let p = apply(t, (2., 1.));
+    assert!(close(p, (12., 6.)), "{:?}", p);
+    assert!(parse_transform("a)b(1)").is_err());
+    assert!(parse_transform("scale(1").is_err());
+    assert_eq!(
+        parse_path_data("M 0 0 L 1 é").unwrap_err(),
+        "Invalid path data: expected a number at position 11, found 'é'."
+    );
+
+    let info = FontInfo::new();
+    *info.imp().units_per_em.borrow_mut() = Some(1000.);
+    *info.imp().ascender.borrow_mut() = Some(800.);
+    let contours = svg_to_contours(
+        r#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50mm" height="50mm" viewBox="0 0 100 100">
+  <defs><path d="M 0 0 L 100 100"/></defs>
+  <g transform="translate(10,0)">
+    <path d="M 0 0 H 10 V 10 Z" transform="scale(2)"/>
+    <path d="M 0 80 L 10 80"/>
+  </g>
+</svg>"#,
+        &info,
+    )
+    .unwrap();
+    assert_eq!(contours.len(), 2);
+    let points = |c: &Contour| {
+        c.curves()
+            .borrow()
+            .iter()
+            .map(|b| b.points().borrow().clone())
+            .collect::<Vec<_>>()
+    };
+    assert!(!*contours[0].open().borrow());
+    assert_eq!(
+        points(&contours[0]),
+        vec![
+            vec![(100, 800), (300, 800)],
+            vec![(300, 800), (300, 600)],
+            vec![(300, 600), (100, 800)],
+        ]
+    );
+    assert!(*contours[1].open().borrow());
+    assert_eq!(points(&contours[1]), vec![vec![(100, 0), (200, 0)]]);
+    assert!(svg_to_contours("<svg/>", &info).is_err());
+    assert!(svg_to_contours("<html><path d='M 0 0 L 1 1'/></html>", &info).is_err());
+}
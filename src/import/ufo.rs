@@ -0,0 +1,243 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Copy glyphs between projects.
+
+use glib::subclass::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::glyphs::Glyph;
+use crate::project::Project;
+
+/// What to do with an imported glyph whose name is already used in the target project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameConflict {
+    /// Import it under the first free name of the form `name.001`, `name.002` and so on, without
+    /// code points.
+    Rename,
+    /// Replace the existing glyph.
+    Overwrite,
+    /// Keep the existing glyph. Imported components that use it as their base keep doing so.
+    Skip,
+}
+
+/// Copies of the glyphs `names` of `source` and of the glyphs their components use, with their
+/// anchors and guidelines, named for `target` according to `conflict`. If `scale` is set, they are
+/// scaled from the units per em of `source` to those of `target`.
+///
+/// The glyphs are not added to `target`. Those whose name is already used there are meant to
+/// replace the existing glyph in place with [`Project::replace_glyph`].
+pub fn import_glyphs(
+    source: &Project,
+    target: &Project,
+    names: &[String],
+    conflict: NameConflict,
+    scale: bool,
+) -> Vec<Glyph> {
+    let source_glyphs = source.imp().glyphs.borrow();
+    let target_glyphs = target.imp().glyphs.borrow();
+
+    /* The requested glyphs, followed by the bases of their components. */
+    let mut queue = names
+        .iter()
+        .filter(|n| source_glyphs.contains_key(*n))
+        .cloned()
+        .collect::<Vec<String>>();
+    let mut seen = queue.iter().cloned().collect::<HashSet<String>>();
+    let mut i = 0;
+    while i < queue.len() {
+        for c in source_glyphs[&queue[i]].borrow().components.iter() {
+            let base = c.base_name();
+            if source_glyphs.contains_key(base) && seen.insert(base.to_string()) {
+                queue.push(base.to_string());
+            }
+        }
+        i += 1;
+    }
+
+    let (free, taken): (Vec<String>, Vec<String>) = queue
+        .into_iter()
+        .partition(|n| !target_glyphs.contains_key(n));
+    let mut used = target_glyphs
+        .keys()
+        .chain(free.iter())
+        .cloned()
+        .collect::<HashSet<String>>();
+    let mut renamed = HashMap::default();
+    let mut imported = free;
+    match conflict {
+        NameConflict::Overwrite => imported.extend(taken),
+        NameConflict::Skip => {}
+        NameConflict::Rename => {
+            for name in taken {
+                let new_name = (1..)
+                    .map(|i| format!("{}.{:03}", name, i))
+                    .find(|n| !used.contains(n))
+                    .unwrap();
+                used.insert(new_name.clone());
+                renamed.insert(name.clone(), new_name);
+                imported.push(name);
+            }
+        }
+    }
+
+    let factor = target.fontinfo().units_per_em() / source.fontinfo().units_per_em();
+    imported
+        .into_iter()
+        .map(|name| {
+            let source_glyph = source_glyphs[&name].borrow();
+            let mut glyph = match renamed.get(&name) {
+                Some(new_name) => source_glyph.duplicate(new_name.clone()),
+                None => Glyph {
+                    kind: source_glyph.kind.clone(),
+                    name2: source_glyph.name2.clone(),
+                    unicode: source_glyph.unicode.clone(),
+                    ..source_glyph.duplicate(name.clone())
+                },
+            };
+            glyph.rename_component_bases(&renamed);
+            if scale && factor != 1. {
+                glyph.scale(factor);
+            }
+            glyph
+        })
+        .collect()
+}
+
+#[test]
+fn test_import_glyphs() {
+    use crate::glyphs::{Anchor, Contour};
+    use crate::utils::curves::Bezier;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let glyph = |name: &'static str, c: char, points: Vec<(i64, i64)>| {
+        let mut g = Glyph::new_empty(name, c);
+        g.contours.clear();
+        if !points.is_empty() {
+            let contour = Contour::new();
+            contour
+                .curves()
+                .borrow_mut()
+                .push(Bezier::new(false, points));
+            g.contours.push(contour);
+        }
+        g.width = Some(500.);
+        Rc::new(RefCell::new(g))
+    };
+    let source = Project::new();
+    *source.fontinfo().imp().units_per_em.borrow_mut() = Some(2000.);
+    source
+        .add_glyph(glyph("a", 'a', vec![(0, 0), (100, 200)]))
+        .unwrap();
+    source
+        .add_glyph(glyph("acute", '\u{b4}', vec![(10, 10), (20, 20)]))
+        .unwrap();
+    source.add_glyph(glyph("b", 'b', vec![])).unwrap();
+    source.imp().glyphs.borrow()["acute"]
+        .borrow_mut()
+        .anchors
+        .push(
+            Anchor::builder()
+                .name("_top".into())
+                .x(100.)
+                .y(400.)
+                .build(),
+        );
+    let glif = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="aacute" format="2">
+  <advance width="500"/>
+  <unicode hex="00E1"/>
+  <outline>
+    <component base="a"/>
+    <component base="acute" xOffset="200" yOffset="10"/>
+  </outline>
+</glyph>"#;
+    let aacute = crate::glyphs::glif::Glif::from_str(glif)
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    source.add_glyph(Rc::new(RefCell::new(aacute))).unwrap();
+
+    let target = Project::new();
+    target
+        .add_glyph(glyph("a", 'a', vec![(1, 1), (2, 2)]))
+        .unwrap();
+
+    let names = |glyphs: &[Glyph]| {
+        glyphs
+            .iter()
+            .map(|g| g.name.to_string())
+            .collect::<Vec<String>>()
+    };
+    let imported = import_glyphs(
+        &source,
+        &target,
+        &["aacute".to_string()],
+        NameConflict::Skip,
+        false,
+    );
+    assert_eq!(names(&imported), vec!["aacute", "acute"]);
+    assert_eq!(imported[0].unicode, vec!['\u{e1}']);
+    assert_eq!(imported[1].anchors[0].name(), "_top");
+
+    let imported = import_glyphs(
+        &source,
+        &target,
+        &["aacute".to_string()],
+        NameConflict::Rename,
+        true,
+    );
+    assert_eq!(names(&imported), vec!["aacute", "acute", "a.001"]);
+    assert!(imported[2].unicode.is_empty());
+    assert_eq!(
+        imported[0]
+            .components
+            .iter()
+            .map(|c| c.base_name())
+            .collect::<Vec<_>>(),
+        vec!["a.001", "acute"]
+    );
+    /* The target uses the default 1000 units per em. */
+    assert_eq!(imported[0].components[1].transform()[4..], [100., 5.]);
+    assert_eq!(imported[1].anchors[0].x(), 50.);
+    assert_eq!(imported[2].width, Some(250.));
+    assert_eq!(
+        *imported[2].contours[0].curves().borrow()[0]
+            .points()
+            .borrow(),
+        vec![(0, 0), (50, 100)]
+    );
+    /* The source isn't changed. */
+    assert_eq!(source.imp().glyphs.borrow()["a"].borrow().width, Some(500.));
+
+    let imported = import_glyphs(
+        &source,
+        &target,
+        &["a".to_string(), "b".to_string(), "missing".to_string()],
+        NameConflict::Overwrite,
+        false,
+    );
+    assert_eq!(names(&imported), vec!["b", "a"]);
+    assert_eq!(imported[1].unicode, vec!['a']);
+}
@@ -0,0 +1,35 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The font model of `gerb`: glyphs and their `.glif` representation, font info, kerning and
+//! layers of UFO projects, Bézier curve geometry and Unicode data, compilation to binary font
+//! files and import of SVG outlines.
+//!
+//! The model uses GLib objects but doesn't depend on GTK, so it can be used without a display.
+//! Drawing glyphs with cairo is available with the `cairo` feature, and rendering them to SVG and
+//! PDF surfaces with the `svg` and `pdf` features.
+
+pub mod export;
+pub mod glyphs;
+pub mod import;
+pub mod project;
+pub mod unicode;
+pub mod utils;
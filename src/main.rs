@@ -23,11 +23,9 @@ use gtk::prelude::*;
 
 mod app;
 pub use app::*;
-pub mod glyphs;
-pub mod project;
+mod cli;
+pub use gerb::{export, glyphs, import, project, unicode, utils};
 pub mod resources;
-pub mod unicode;
-pub mod utils;
 pub mod views;
 mod window;
 use app::GerbApp;
@@ -41,6 +39,11 @@ fn main() {
     //    std::process::exit(-1);
     //}
 
+    let env_args = std::env::args().skip(1).collect::<Vec<String>>();
+    if let Some(status) = cli::run(&env_args) {
+        std::process::exit(status);
+    }
+
     gtk::init().expect("Failed to initialize gtk");
 
     //let app = GerbApp::new(env_args);
@@ -19,9 +19,8 @@
  * along with gerb. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use glib::subclass::prelude::*;
 use glib::{ParamSpec, Value};
-use gtk::glib;
-use gtk::subclass::prelude::*;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -30,7 +29,86 @@ use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-use crate::glyphs::{Glyph, Guideline};
+use crate::glyphs::Glyph;
+use crate::utils::plist::{DictValue, Plist};
+
+mod fontinfo;
+pub use fontinfo::*;
+mod kerning;
+pub use kerning::*;
+mod layers;
+pub use layers::*;
+mod load;
+pub use load::*;
+
+/// `lib.plist` key of the glyph order.
+pub const GLYPH_ORDER_KEY: &str = "public.glyphOrder";
+
+/// Glyph names in the order of `public.glyphOrder`, indexed so that looking up a glyph's position
+/// doesn't scan the whole list.
+#[derive(Debug, Clone, Default)]
+pub struct GlyphOrder {
+    names: Vec<String>,
+    positions: HashMap<String, usize>,
+}
+
+impl GlyphOrder {
+    pub fn new(names: Vec<String>) -> Self {
+        let mut ret = Self {
+            names,
+            positions: HashMap::default(),
+        };
+        ret.reindex();
+        ret
+    }
+
+    /// A name listed more than once keeps its first position.
+    fn reindex(&mut self) {
+        self.positions.clear();
+        for (i, name) in self.names.iter().enumerate() {
+            self.positions.entry(name.clone()).or_insert(i);
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.positions.get(name).copied()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.positions.contains_key(name)
+    }
+
+    pub fn push(&mut self, name: String) {
+        self.positions
+            .entry(name.clone())
+            .or_insert(self.names.len());
+        self.names.push(name);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        if self.contains(name) {
+            self.names.retain(|n| n != name);
+            self.reindex();
+        }
+    }
+
+    pub fn rename(&mut self, old_name: &str, new_name: &str) {
+        if self.contains(old_name) {
+            for n in self.names.iter_mut().filter(|n| *n == old_name) {
+                *n = new_name.to_string();
+            }
+            self.reindex();
+        }
+    }
+}
 
 mod imp {
     use super::*;
@@ -40,32 +118,22 @@ mod imp {
         pub name: RefCell<String>,
         pub modified: RefCell<bool>,
         pub last_saved: RefCell<Option<u64>>,
+        /// Glyphs of the default layer.
         pub glyphs: RefCell<HashMap<String, Rc<RefCell<Glyph>>>>,
+        /// Glyph order from `public.glyphOrder` in `lib.plist`. Glyphs that aren't in it come
+        /// after the ones that are.
+        pub glyph_order: RefCell<GlyphOrder>,
+        /// Contents of `lib.plist`, except for the glyph order.
+        pub lib: RefCell<HashMap<String, DictValue>>,
+        pub default_layer_name: RefCell<String>,
+        /// Every other layer, in the order they are listed in `layercontents.plist`.
+        pub layers: RefCell<Vec<Layer>>,
         pub path: RefCell<Option<PathBuf>>,
-        pub family_name: RefCell<String>,
-        pub style_name: RefCell<String>,
-        pub version_major: RefCell<i64>,
-        pub version_minor: RefCell<u64>,
-        /// Copyright statement.
-        pub copyright: RefCell<String>,
-        /// Trademark statement.
-        pub trademark: RefCell<String>,
-        /// Units per em.
-        pub units_per_em: RefCell<f64>,
-        /// Descender value. Note: The specification is agnostic about the relationship to the more specific vertical metric values.
-        pub descender: RefCell<f64>,
-        /// x-height value.
-        pub x_height: RefCell<f64>,
-        /// Cap height value.
-        pub cap_height: RefCell<f64>,
-        /// Ascender value. Note: The specification is agnostic about the relationship to the more specific vertical metric values.
-        pub ascender: RefCell<f64>,
-        /// Italic angle. This must be an angle in counter-clockwise degrees from the vertical.
-        pub italic_angle: RefCell<f64>,
-        /// Arbitrary note about the font.
-        pub note: RefCell<String>,
-        /// A list of guideline definitions that apply to all glyphs in all layers in the font. This attribute is optional.
-        pub guidelines: RefCell<Vec<Guideline>>,
+        pub fontinfo: RefCell<FontInfo>,
+        pub kerning: RefCell<Kerning>,
+        pub groups: RefCell<Groups>,
+        /// Problems found while loading the project that didn't prevent it from loading.
+        pub load_report: RefCell<LoadReport>,
     }
 
     impl Default for Project {
@@ -75,21 +143,15 @@ mod imp {
                 modified: RefCell::new(false),
                 last_saved: RefCell::new(None),
                 glyphs: RefCell::new(HashMap::default()),
+                glyph_order: RefCell::new(GlyphOrder::default()),
+                lib: RefCell::new(HashMap::default()),
+                default_layer_name: RefCell::new(DEFAULT_LAYER_NAME.to_string()),
+                layers: RefCell::new(vec![]),
                 path: RefCell::new(None),
-                family_name: RefCell::new("New project".to_string()),
-                style_name: RefCell::new(String::new()),
-                version_major: RefCell::new(0),
-                version_minor: RefCell::new(0),
-                copyright: RefCell::new(String::new()),
-                trademark: RefCell::new(String::new()),
-                units_per_em: RefCell::new(1000.0),
-                descender: RefCell::new(-200.),
-                x_height: RefCell::new(450.),
-                cap_height: RefCell::new(650.),
-                ascender: RefCell::new(700.),
-                italic_angle: RefCell::new(0.),
-                note: RefCell::new(String::new()),
-                guidelines: RefCell::new(vec![]),
+                fontinfo: RefCell::new(FontInfo::new()),
+                kerning: RefCell::new(Kerning::default()),
+                groups: RefCell::new(Groups::default()),
+                load_report: RefCell::new(LoadReport::default()),
             }
         }
     }
@@ -135,122 +197,688 @@ impl Project {
         ret
     }
 
-    pub fn from_path(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let glyphs = Glyph::from_ufo(path);
-        let mut path: PathBuf = Path::new(path).into();
-        if !path.exists() {
-            return Err(format!("Directory <i>{}</i> does not exist.", path.display()).into());
+    pub fn fontinfo(&self) -> FontInfo {
+        self.imp().fontinfo.borrow().clone()
+    }
+
+    /// Load the UFO directory at `path`. Problems that don't prevent loading, such as glyphs whose
+    /// `.glif` file can't be parsed, are kept in the project's `load_report`.
+    pub fn from_path(path: &str) -> Result<Self, LoadError> {
+        let mut load_report = LoadReport::default();
+        let ufo_path: PathBuf = Path::new(path).into();
+        if !ufo_path.is_dir() {
+            return Err(LoadError::new(&ufo_path, LoadErrorKind::NotADirectory));
         }
-        if !path.is_dir() {
-            return Err(format!("Path {} is not a directory.", path.display()).into());
+        let fontinfo_path = ufo_path.join("fontinfo.plist");
+        let mut file = File::open(&fontinfo_path)
+            .map_err(|err| LoadError::new(&fontinfo_path, LoadErrorKind::Io(err)))?;
+        let mut s = String::new();
+        file.read_to_string(&mut s)
+            .map_err(|err| LoadError::new(&fontinfo_path, LoadErrorKind::Io(err)))?;
+        let plist = Plist::from_str(&s)
+            .map_err(|err| LoadError::new(&fontinfo_path, LoadErrorKind::Plist(err)))?;
+        let fontinfo = FontInfo::from_plist(plist.dict);
+        let groups_path = ufo_path.join("groups.plist");
+        let groups = match read_optional_plist(&groups_path)? {
+            Some(dict) => {
+                Groups::from_plist(dict).map_err(|err| LoadError::invalid(&groups_path, err))?
+            }
+            None => Groups::default(),
+        };
+        let kerning_path = ufo_path.join("kerning.plist");
+        let kerning = match read_optional_plist(&kerning_path)? {
+            Some(dict) => {
+                Kerning::from_plist(dict).map_err(|err| LoadError::invalid(&kerning_path, err))?
+            }
+            None => Kerning::default(),
+        };
+        let glyphs = Glyph::from_ufo(path, &mut load_report)?;
+        for warning in kerning.validate(&groups, &glyphs) {
+            load_report.warn(LoadError::invalid(&kerning_path, warning));
         }
-        path.push("fontinfo.plist");
-        let mut file = match File::open(&path) {
-            Err(err) => return Err(format!("couldn't open {}: {}", path.display(), err).into()),
-            Ok(file) => file,
+        let lib_path = ufo_path.join("lib.plist");
+        let mut lib = read_optional_plist(&lib_path)?.unwrap_or_default();
+        let glyph_order = match lib.remove(GLYPH_ORDER_KEY) {
+            Some(DictValue::Array(names)) => names
+                .into_iter()
+                .filter_map(|n| match n {
+                    DictValue::String(n) => Some(n),
+                    _ => None,
+                })
+                .collect(),
+            Some(other) => {
+                load_report.warn(LoadError::invalid(
+                    &lib_path,
+                    format!("{} is not an array: {:?}", GLYPH_ORDER_KEY, other),
+                ));
+                vec![]
+            }
+            None => vec![],
         };
+        for name in glyph_order.iter().filter(|n| !glyphs.contains_key(*n)) {
+            load_report.warn_glyph(
+                name,
+                false,
+                LoadError::invalid(
+                    &lib_path,
+                    format!(
+                        "{} contains glyph {:?} which does not exist.",
+                        GLYPH_ORDER_KEY, name
+                    ),
+                ),
+            );
+        }
+        let mut default_layer_name = DEFAULT_LAYER_NAME.to_string();
+        let mut layers = vec![];
+        let layercontents_path = ufo_path.join("layercontents.plist");
+        if layercontents_path.exists() {
+            let s = std::fs::read_to_string(&layercontents_path)
+                .map_err(|err| LoadError::new(&layercontents_path, LoadErrorKind::Io(err)))?;
+            for (name, dir_name) in parse_layer_contents(&s)
+                .map_err(|kind| LoadError::new(&layercontents_path, kind))?
+            {
+                if dir_name == DEFAULT_LAYER_DIR {
+                    default_layer_name = name;
+                    continue;
+                }
+                let glyphs = Glyph::from_glyphs_dir(&ufo_path.join(&dir_name), &mut load_report)?;
+                layers.push(Layer {
+                    name,
+                    dir_name,
+                    glyphs,
+                });
+            }
+        }
+        let ret: Self = Self::new();
+        *ret.imp().name.borrow_mut() = fontinfo.family_name();
+        *ret.imp().modified.borrow_mut() = false;
+        *ret.imp().last_saved.borrow_mut() = None;
+        *ret.imp().glyphs.borrow_mut() = glyphs;
+        *ret.imp().glyph_order.borrow_mut() = GlyphOrder::new(glyph_order);
+        *ret.imp().lib.borrow_mut() = lib;
+        *ret.imp().default_layer_name.borrow_mut() = default_layer_name;
+        *ret.imp().layers.borrow_mut() = layers;
+        *ret.imp().path.borrow_mut() = Some(ufo_path);
+        *ret.imp().fontinfo.borrow_mut() = fontinfo;
+        *ret.imp().kerning.borrow_mut() = kerning;
+        *ret.imp().groups.borrow_mut() = groups;
+        *ret.imp().load_report.borrow_mut() = load_report;
+        Ok(ret)
+    }
 
-        let mut s = String::new();
-        if let Err(err) = file.read_to_string(&mut s) {
-            return Err(format!("couldn't read {}: {}", path.display(), err).into());
-        }
-        let mut plist = fontinfo::Plist::from_str(&s)
-            .map_err(|err| format!("couldn't read fontinfo.plist {}: {}", path.display(), err))?;
-        let family_name =
-            if let Some(fontinfo::DictValue::String(s)) = plist.dict.remove("familyName") {
-                s
-            } else {
-                String::new()
-            };
-        let style_name =
-            if let Some(fontinfo::DictValue::String(s)) = plist.dict.remove("styleName") {
-                s
-            } else {
-                String::new()
-            };
-        let copyright = if let Some(fontinfo::DictValue::String(s)) = plist.dict.remove("copyright")
+    /// Add `glyph` to the project. Fails if a glyph with the same name already exists.
+    pub fn add_glyph(&self, glyph: Rc<RefCell<Glyph>>) -> Result<(), Box<dyn std::error::Error>> {
+        let name = glyph.borrow().name.to_string();
+        let mut glyphs = self.imp().glyphs.borrow_mut();
+        if glyphs.contains_key(&name) {
+            return Err(format!("Glyph {:?} already exists.", name).into());
+        }
         {
-            s
-        } else {
-            String::new()
-        };
-        let trademark = if let Some(fontinfo::DictValue::String(s)) = plist.dict.remove("trademark")
+            let mut glyph_order = self.imp().glyph_order.borrow_mut();
+            if !glyph_order.is_empty() && !glyph_order.contains(&name) {
+                glyph_order.push(name.clone());
+            }
+        }
+        if glyph.borrow().filename.is_none() {
+            glyph.borrow_mut().filename =
+                Some(glif_file_name(&name, &used_glif_file_names(&glyphs)));
+        }
+        glyphs.insert(name, glyph);
+        Glyph::link_components(&glyphs);
+        *self.imp().modified.borrow_mut() = true;
+        Ok(())
+    }
+
+    /// Remove glyph `name` from the project. Components that use it as their base are left
+    /// dangling until a glyph with that name is added again.
+    pub fn remove_glyph(&self, name: &str) -> Option<Rc<RefCell<Glyph>>> {
+        let mut glyphs = self.imp().glyphs.borrow_mut();
+        let ret = glyphs.remove(name)?;
+        self.imp().glyph_order.borrow_mut().remove(name);
+        Glyph::link_components(&glyphs);
+        *self.imp().modified.borrow_mut() = true;
+        Some(ret)
+    }
+
+    /// Add `glyph` to the project, replacing the glyph with the same name if there is one, which
+    /// is returned. A replaced glyph's position in the glyph order and its file name are kept.
+    pub fn replace_glyph(&self, glyph: Rc<RefCell<Glyph>>) -> Option<Rc<RefCell<Glyph>>> {
+        let name = glyph.borrow().name.to_string();
+        let mut glyphs = self.imp().glyphs.borrow_mut();
+        let ret = glyphs.remove(&name);
+        match ret {
+            Some(ref old) => {
+                glyph.borrow_mut().filename = old.borrow().filename.clone();
+            }
+            None => {
+                let mut glyph_order = self.imp().glyph_order.borrow_mut();
+                if !glyph_order.is_empty() && !glyph_order.contains(&name) {
+                    glyph_order.push(name.clone());
+                }
+            }
+        }
+        if glyph.borrow().filename.is_none() {
+            glyph.borrow_mut().filename =
+                Some(glif_file_name(&name, &used_glif_file_names(&glyphs)));
+        }
+        glyphs.insert(name, glyph);
+        Glyph::link_components(&glyphs);
+        *self.imp().modified.borrow_mut() = true;
+        ret
+    }
+
+    /// Rename glyph `old_name` to `new_name`, along with every component, group and kerning pair
+    /// that refers to it.
+    pub fn rename_glyph(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut glyphs = self.imp().glyphs.borrow_mut();
+        if glyphs.contains_key(new_name) {
+            return Err(format!("Glyph {:?} already exists.", new_name).into());
+        }
+        let glyph = glyphs
+            .remove(old_name)
+            .ok_or_else(|| format!("Glyph {:?} does not exist.", old_name))?;
         {
-            s
-        } else {
-            String::new()
+            let filename = glif_file_name(new_name, &used_glif_file_names(&glyphs));
+            let mut g = glyph.borrow_mut();
+            g.name = new_name.to_string().into();
+            g.filename = Some(filename);
+            g.modified = true;
+        }
+        glyphs.insert(new_name.to_string(), glyph);
+        for g in glyphs.values() {
+            let mut g = g.borrow_mut();
+            if g.rename_component_base(old_name, new_name) {
+                g.modified = true;
+            }
+        }
+        Glyph::link_components(&glyphs);
+        for layer in self.imp().layers.borrow_mut().iter_mut() {
+            if let Some(glyph) = layer.glyphs.remove(old_name) {
+                {
+                    let filename = glif_file_name(new_name, &used_glif_file_names(&layer.glyphs));
+                    let mut g = glyph.borrow_mut();
+                    g.name = new_name.to_string().into();
+                    g.filename = Some(filename);
+                    g.modified = true;
+                }
+                layer.glyphs.insert(new_name.to_string(), glyph);
+            }
+            for g in layer.glyphs.values() {
+                let mut g = g.borrow_mut();
+                if g.rename_component_base(old_name, new_name) {
+                    g.modified = true;
+                }
+            }
+            Glyph::link_components(&layer.glyphs);
+        }
+        self.imp()
+            .glyph_order
+            .borrow_mut()
+            .rename(old_name, new_name);
+        self.imp()
+            .groups
+            .borrow_mut()
+            .rename_glyph(old_name, new_name);
+        self.imp()
+            .kerning
+            .borrow_mut()
+            .rename_glyph(old_name, new_name);
+        *self.imp().modified.borrow_mut() = true;
+        Ok(())
+    }
+
+    /// Compare glyphs for display: by their position in `public.glyphOrder` if they are in it,
+    /// otherwise by code point and name after all glyphs that are.
+    pub fn cmp_glyphs(&self, a: &Glyph, b: &Glyph) -> std::cmp::Ordering {
+        let glyph_order = self.imp().glyph_order.borrow();
+        let position = |g: &Glyph| glyph_order.position(&g.name);
+        match (position(a), position(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.cmp(b),
+        }
+    }
+
+    /// Names of all layers, the default layer first.
+    pub fn layer_names(&self) -> Vec<String> {
+        std::iter::once(self.imp().default_layer_name.borrow().clone())
+            .chain(self.imp().layers.borrow().iter().map(|l| l.name.clone()))
+            .collect()
+    }
+
+    /// Glyph `name` of layer `layer`, if the layer has it.
+    pub fn layer_glyph(&self, layer: &str, name: &str) -> Option<Rc<RefCell<Glyph>>> {
+        if *self.imp().default_layer_name.borrow() == layer {
+            return self.imp().glyphs.borrow().get(name).cloned();
+        }
+        self.imp()
+            .layers
+            .borrow()
+            .iter()
+            .find(|l| l.name == layer)
+            .and_then(|l| l.glyphs.get(name).cloned())
+    }
+
+    /// Glyph `name` of layer `layer`. If a non-default layer doesn't have it yet, an empty glyph
+    /// with the same advance width and code points as the default layer's is added to it.
+    /// Returns `None` if there is no such layer or glyph.
+    pub fn layer_glyph_or_insert(&self, layer: &str, name: &str) -> Option<Rc<RefCell<Glyph>>> {
+        if let Some(ret) = self.layer_glyph(layer, name) {
+            return Some(ret);
+        }
+        let default = self.imp().glyphs.borrow().get(name).cloned()?;
+        let mut layers = self.imp().layers.borrow_mut();
+        let layer = layers.iter_mut().find(|l| l.name == layer)?;
+        let glyph = {
+            let default = default.borrow();
+            let mut glyph = Glyph::new_empty(name.to_string(), '\0');
+            glyph.contours.clear();
+            glyph.name2 = default.name2.clone();
+            glyph.kind = default.kind.clone();
+            glyph.unicode = default.unicode.clone();
+            glyph.width = default.width;
+            glyph
         };
-        let units_per_em =
-            if let Some(fontinfo::DictValue::Integer(u)) = plist.dict.remove("unitsPerEm") {
-                u as f64
-            } else {
-                1000.0
-            };
-        let x_height = if let Some(fontinfo::DictValue::Integer(x)) = plist.dict.remove("xHeight") {
-            x as f64
-        } else {
-            500.0
+        let glyph = Rc::new(RefCell::new(glyph));
+        layer.glyphs.insert(name.to_string(), glyph.clone());
+        Some(glyph)
+    }
+
+    /// The kerning value between glyphs `left` and `right`, taking kerning groups into account.
+    pub fn kerning_value(&self, left: &str, right: &str) -> f64 {
+        self.imp()
+            .kerning
+            .borrow()
+            .lookup(&self.imp().groups.borrow(), left, right)
+            .map(|(_, v)| v)
+            .unwrap_or(0.)
+    }
+
+    /// Save project to the UFO directory it was loaded from.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self
+            .imp()
+            .path
+            .borrow()
+            .clone()
+            .ok_or("Project has not been saved to a directory yet.")?;
+        self.save_as(&path)
+    }
+
+    /// Save project as a UFO directory at `path`.
+    ///
+    /// Everything is first written to a temporary sibling directory, which then replaces `path`.
+    /// Files gerb doesn't know about (`features.fea`, `data/`, other layers etc.) are copied over
+    /// from the project's current directory. If anything fails, the existing directory is left
+    /// untouched.
+    pub fn save_as<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| format!("Invalid project path {}.", path.display()))?
+            .to_string_lossy()
+            .to_string();
+        let parent = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
         };
-        let ascender = if let Some(fontinfo::DictValue::Integer(a)) = plist.dict.remove("ascender")
-        {
-            a as f64
-        } else {
-            700.0
+        let tmp_path = parent.join(format!(".{}.gerb-tmp", file_name));
+        let old_path = parent.join(format!(".{}.gerb-old", file_name));
+        if tmp_path.exists() {
+            std::fs::remove_dir_all(&tmp_path)?;
+        }
+        let glif_sources = match self.write_ufo(&tmp_path) {
+            Ok(v) => v,
+            Err(err) => {
+                let _ = std::fs::remove_dir_all(&tmp_path);
+                return Err(err);
+            }
         };
-        let descender =
-            if let Some(fontinfo::DictValue::Integer(d)) = plist.dict.remove("descender") {
-                d as f64
-            } else {
-                -200.0
-            };
-        let cap_height =
-            if let Some(fontinfo::DictValue::Integer(c)) = plist.dict.remove("capHeight") {
-                c as f64
-            } else {
-                600.0
-            };
-        let italic_angle =
-            if let Some(fontinfo::DictValue::Integer(i)) = plist.dict.remove("italicAngle") {
-                i as f64
-            } else {
-                0.0
+        if path.exists() {
+            /* Leftovers of an earlier save are only in the way if they can't be removed, which
+             * the rename below reports. */
+            if old_path.exists() {
+                let _ = std::fs::remove_dir_all(&old_path);
+            }
+            if let Err(err) = std::fs::rename(path, &old_path) {
+                let _ = std::fs::remove_dir_all(&tmp_path);
+                return Err(err.into());
+            }
+            if let Err(err) = std::fs::rename(&tmp_path, path) {
+                let _ = std::fs::rename(&old_path, path);
+                let _ = std::fs::remove_dir_all(&tmp_path);
+                return Err(format!("Could not replace {}: {}", path.display(), err).into());
+            }
+            /* The project is saved at this point; a copy of the previous version left behind
+             * doesn't make the save fail. */
+            let _ = std::fs::remove_dir_all(&old_path);
+        } else {
+            std::fs::rename(&tmp_path, path)?;
+        }
+
+        {
+            let update = |glyphs: &HashMap<String, Rc<RefCell<Glyph>>>,
+                          sources: HashMap<String, (String, String)>| {
+                for (name, (filename, source)) in sources {
+                    if let Some(g) = glyphs.get(&name) {
+                        let mut g = g.borrow_mut();
+                        g.filename = Some(filename);
+                        g.glif_source = source;
+                        g.modified = false;
+                    }
+                }
             };
-        let version_major =
-            if let Some(fontinfo::DictValue::Integer(i)) = plist.dict.remove("versionMajor") {
-                i
-            } else {
-                0
+            let mut glif_sources = glif_sources;
+            if let Some(sources) = glif_sources.remove(DEFAULT_LAYER_DIR) {
+                update(&self.imp().glyphs.borrow(), sources);
+            }
+            for layer in self.imp().layers.borrow().iter() {
+                if let Some(sources) = glif_sources.remove(&layer.dir_name) {
+                    update(&layer.glyphs, sources);
+                }
+            }
+        }
+        *self.imp().path.borrow_mut() = Some(path.to_path_buf());
+        *self.imp().modified.borrow_mut() = false;
+        self.fontinfo().imp().modified.set(false);
+        *self.imp().last_saved.borrow_mut() = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        Ok(())
+    }
+
+    /// Write the UFO to the (new) directory `dest`. Returns the file name and contents of every
+    /// glyph's `.glif` file, keyed by layer directory and glyph name.
+    #[allow(clippy::type_complexity)]
+    fn write_ufo(
+        &self,
+        dest: &Path,
+    ) -> Result<HashMap<String, HashMap<String, (String, String)>>, Box<dyn std::error::Error>>
+    {
+        std::fs::create_dir_all(dest.join(DEFAULT_LAYER_DIR))?;
+        if let Some(src) = self.imp().path.borrow().as_ref() {
+            if src.is_dir() {
+                copy_unknown_files(src, dest, true)?;
+            }
+        }
+
+        let metainfo = Plist {
+            dict: [
+                (
+                    "creator".to_string(),
+                    DictValue::String("com.epilys.gerb".to_string()),
+                ),
+                ("formatVersion".to_string(), DictValue::Integer(3)),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        write(dest.join("metainfo.plist"), &metainfo.to_xml())?;
+        let fontinfo = Plist {
+            dict: self.fontinfo().to_plist(),
+        };
+        write(dest.join("fontinfo.plist"), &fontinfo.to_xml())?;
+        let groups = self.imp().groups.borrow();
+        if !groups.is_empty() {
+            let groups = Plist {
+                dict: groups.to_plist(),
             };
-        let version_minor =
-            if let Some(fontinfo::DictValue::Integer(i)) = plist.dict.remove("versionMinor") {
-                i as u64
-            } else {
-                0
+            write(dest.join("groups.plist"), &groups.to_xml())?;
+        }
+        let kerning = self.imp().kerning.borrow();
+        if !kerning.is_empty() {
+            let kerning = Plist {
+                dict: kerning.to_plist(),
             };
-        let ret: Self = Self::new();
-        *ret.imp().name.borrow_mut() = family_name.clone();
-        *ret.imp().modified.borrow_mut() = false;
-        *ret.imp().last_saved.borrow_mut() = None;
-        *ret.imp().glyphs.borrow_mut() = glyphs?;
-        *ret.imp().path.borrow_mut() = Some(path);
-        *ret.imp().family_name.borrow_mut() = family_name;
-        *ret.imp().style_name.borrow_mut() = style_name;
-        *ret.imp().version_major.borrow_mut() = version_major;
-        *ret.imp().version_minor.borrow_mut() = version_minor;
-        *ret.imp().copyright.borrow_mut() = copyright;
-        *ret.imp().trademark.borrow_mut() = trademark;
-        *ret.imp().units_per_em.borrow_mut() = units_per_em;
-        *ret.imp().descender.borrow_mut() = descender;
-        *ret.imp().x_height.borrow_mut() = x_height;
-        *ret.imp().cap_height.borrow_mut() = cap_height;
-        *ret.imp().ascender.borrow_mut() = ascender;
-        *ret.imp().italic_angle.borrow_mut() = italic_angle;
-        *ret.imp().note.borrow_mut() = String::new();
-        *ret.imp().guidelines.borrow_mut() = vec![];
+            write(dest.join("kerning.plist"), &kerning.to_xml())?;
+        }
+
+        let glyph_order = self.imp().glyph_order.borrow();
+        let mut lib = self.imp().lib.borrow().clone();
+        if !lib.is_empty() || !glyph_order.is_empty() {
+            if !glyph_order.is_empty() {
+                lib.insert(
+                    GLYPH_ORDER_KEY.to_string(),
+                    DictValue::Array(
+                        glyph_order
+                            .names()
+                            .iter()
+                            .map(|n| DictValue::String(n.clone()))
+                            .collect(),
+                    ),
+                );
+            }
+            write(dest.join("lib.plist"), &Plist { dict: lib }.to_xml())?;
+        }
+
+        let mut ret = HashMap::default();
+        ret.insert(
+            DEFAULT_LAYER_DIR.to_string(),
+            write_glyph_set(&dest.join(DEFAULT_LAYER_DIR), &self.imp().glyphs.borrow())?,
+        );
+        let layers = self.imp().layers.borrow();
+        for layer in layers.iter() {
+            /* Skip empty glyphs that were only created by looking at a layer in the editor. */
+            let glyphs = layer
+                .glyphs
+                .iter()
+                .filter(|(_, g)| {
+                    let g = g.borrow();
+                    g.filename.is_some() || !g.is_empty()
+                })
+                .map(|(n, g)| (n.clone(), g.clone()))
+                .collect();
+            let dir = dest.join(&layer.dir_name);
+            std::fs::create_dir_all(&dir)?;
+            ret.insert(layer.dir_name.clone(), write_glyph_set(&dir, &glyphs)?);
+        }
+        let default_layer_name = self.imp().default_layer_name.borrow();
+        write(
+            dest.join("layercontents.plist"),
+            &layer_contents_to_xml(
+                std::iter::once((default_layer_name.as_str(), DEFAULT_LAYER_DIR)).chain(
+                    layers
+                        .iter()
+                        .map(|l| (l.name.as_str(), l.dir_name.as_str())),
+                ),
+            ),
+        )?;
         Ok(ret)
     }
 }
 
+fn write(path: PathBuf, contents: &str) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(&path, contents)
+        .map_err(|err| format!("Couldn't write {}: {}", path.display(), err).into())
+}
+
+/// Write the `.glif` files and `contents.plist` of a layer to the existing directory `dir`.
+/// Returns the file name and contents of every glyph's `.glif` file, keyed by glyph name.
+fn write_glyph_set(
+    dir: &Path,
+    glyphs: &HashMap<String, Rc<RefCell<Glyph>>>,
+) -> Result<HashMap<String, (String, String)>, Box<dyn std::error::Error>> {
+    let mut names = glyphs.keys().cloned().collect::<Vec<String>>();
+    names.sort();
+    let mut used_filenames = used_glif_file_names(glyphs);
+    let mut contents = HashMap::default();
+    let mut ret = HashMap::default();
+    for name in names {
+        let glyph = glyphs[&name].borrow();
+        let filename = if let Some(f) = glyph.filename.clone() {
+            f
+        } else {
+            let f = glif_file_name(&name, &used_filenames);
+            used_filenames.insert(f.to_lowercase());
+            f
+        };
+        let source = glyph.to_glif();
+        write(dir.join(&filename), &source)?;
+        contents.insert(name.clone(), DictValue::String(filename.clone()));
+        ret.insert(name, (filename, source));
+    }
+    write(
+        dir.join("contents.plist"),
+        &Plist { dict: contents }.to_xml(),
+    )?;
+    Ok(ret)
+}
+
+/// Read and parse the property list at `path`, if it exists.
+fn read_optional_plist(path: &Path) -> Result<Option<HashMap<String, DictValue>>, LoadError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let s = std::fs::read_to_string(path)
+        .map_err(|err| LoadError::new(path, LoadErrorKind::Io(err)))?;
+    let plist =
+        Plist::from_str(&s).map_err(|err| LoadError::new(path, LoadErrorKind::Plist(err)))?;
+    Ok(Some(plist.dict))
+}
+
+/// Recursively copy everything in `src` to `dest`, except for the files that `Project::write_ufo`
+/// generates itself.
+fn copy_unknown_files(src: &Path, dest: &Path, top_level: bool) -> std::io::Result<()> {
+    for entry in src.read_dir()? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let is_generated = if top_level {
+            matches!(
+                file_name.as_ref(),
+                "metainfo.plist"
+                    | "fontinfo.plist"
+                    | "groups.plist"
+                    | "kerning.plist"
+                    | "layercontents.plist"
+                    | "lib.plist"
+            )
+        } else {
+            src.file_name()
+                .map(|n| Layer::is_glyph_set_dir(&n.to_string_lossy()))
+                .unwrap_or(false)
+                && (file_name == "contents.plist" || file_name.ends_with(".glif"))
+        };
+        if is_generated {
+            continue;
+        }
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            copy_unknown_files(&entry.path(), &target, false)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// File name for the `.glif` file of a glyph that doesn't have one yet, following the UFO user
+/// name to file name convention.
+fn glif_file_name(name: &str, used_filenames: &std::collections::HashSet<String>) -> String {
+    crate::utils::filenames::user_name_to_file_name(name, used_filenames, "", ".glif")
+}
+
+/// Lowercase file names of the glyphs in `glyphs` that have one.
+fn used_glif_file_names(
+    glyphs: &HashMap<String, Rc<RefCell<Glyph>>>,
+) -> std::collections::HashSet<String> {
+    glyphs
+        .values()
+        .filter_map(|g| g.borrow().filename.as_ref().map(|f| f.to_lowercase()))
+        .collect()
+}
+
+/// Settings for a new project, see [`Project::from_template`].
+#[derive(Debug, Clone)]
+pub struct ProjectTemplate {
+    pub family_name: String,
+    pub style_name: String,
+    pub units_per_em: f64,
+    pub ascender: f64,
+    pub descender: f64,
+    pub x_height: f64,
+    pub cap_height: f64,
+    /// Characters to create empty glyphs for.
+    pub charset: Vec<char>,
+}
+
+impl Default for ProjectTemplate {
+    fn default() -> Self {
+        ProjectTemplate {
+            family_name: "Untitled".to_string(),
+            style_name: "Regular".to_string(),
+            units_per_em: 1000.,
+            ascender: 750.,
+            descender: -250.,
+            x_height: 500.,
+            cap_height: 700.,
+            charset: (' '..='~').collect(),
+        }
+    }
+}
+
+impl ProjectTemplate {
+    /// Characters of the Unicode block at `index` of
+    /// [`UNICODE_BLOCKS`](crate::unicode::blocks::UNICODE_BLOCKS) that can have a glyph, i.e.
+    /// assigned characters that aren't control characters.
+    pub fn block_charset(index: usize) -> Vec<char> {
+        use crate::unicode::names::CharName;
+
+        let ((start, end), _, _, _, _) = crate::unicode::blocks::UNICODE_BLOCKS[index];
+        (start..=end)
+            .filter(|c| !c.is_control() && c.char_name().is_some())
+            .collect()
+    }
+}
+
+impl Project {
+    /// Create a new, unsaved project with an empty glyph for every character in the template's
+    /// charset.
+    pub fn from_template(template: &ProjectTemplate) -> Self {
+        let ret = Self::default();
+        let name = if template.style_name.is_empty() {
+            template.family_name.clone()
+        } else {
+            format!("{} {}", template.family_name, template.style_name)
+        };
+        let mut glyphs = HashMap::default();
+        for &c in &template.charset {
+            let mut glyph = Glyph::new_empty(Glyph::default_name(c), c);
+            glyph.contours.clear();
+            glyph.modified = true;
+            glyphs.insert(glyph.name.to_string(), Rc::new(RefCell::new(glyph)));
+        }
+        let imp = ret.imp();
+        *imp.name.borrow_mut() = name;
+        *imp.modified.borrow_mut() = true;
+        *imp.glyphs.borrow_mut() = glyphs;
+        let fontinfo = FontInfo::new();
+        {
+            let info = fontinfo.imp();
+            *info.family_name.borrow_mut() = Some(template.family_name.clone());
+            *info.style_name.borrow_mut() =
+                Some(template.style_name.clone()).filter(|s| !s.is_empty());
+            *info.version_major.borrow_mut() = Some(1);
+            *info.version_minor.borrow_mut() = Some(0);
+            *info.units_per_em.borrow_mut() = Some(template.units_per_em);
+            *info.ascender.borrow_mut() = Some(template.ascender);
+            *info.descender.borrow_mut() = Some(template.descender);
+            *info.x_height.borrow_mut() = Some(template.x_height);
+            *info.cap_height.borrow_mut() = Some(template.cap_height);
+            *info.italic_angle.borrow_mut() = Some(0.0);
+        }
+        *imp.fontinfo.borrow_mut() = fontinfo;
+        ret
+    }
+}
+
 impl Default for Project {
     fn default() -> Self {
         let ret: Self = Self::new();
@@ -258,375 +886,219 @@ impl Default for Project {
         *ret.imp().modified.borrow_mut() = false;
         *ret.imp().last_saved.borrow_mut() = None;
         *ret.imp().glyphs.borrow_mut() = HashMap::default();
+        *ret.imp().glyph_order.borrow_mut() = GlyphOrder::default();
+        *ret.imp().default_layer_name.borrow_mut() = DEFAULT_LAYER_NAME.to_string();
+        *ret.imp().layers.borrow_mut() = vec![];
         *ret.imp().path.borrow_mut() = None;
-        *ret.imp().family_name.borrow_mut() = "New project".to_string();
-        *ret.imp().style_name.borrow_mut() = String::new();
-        *ret.imp().version_major.borrow_mut() = 0;
-        *ret.imp().version_minor.borrow_mut() = 0;
-        *ret.imp().copyright.borrow_mut() = String::new();
-        *ret.imp().trademark.borrow_mut() = String::new();
-        *ret.imp().units_per_em.borrow_mut() = 1000.0;
-        *ret.imp().descender.borrow_mut() = -200.;
-        *ret.imp().x_height.borrow_mut() = 450.;
-        *ret.imp().cap_height.borrow_mut() = 650.;
-        *ret.imp().ascender.borrow_mut() = 700.;
-        *ret.imp().italic_angle.borrow_mut() = 0.;
-        *ret.imp().note.borrow_mut() = String::new();
-        *ret.imp().guidelines.borrow_mut() = vec![];
+        *ret.imp().fontinfo.borrow_mut() = FontInfo::new();
+        *ret.imp().kerning.borrow_mut() = Kerning::default();
+        *ret.imp().groups.borrow_mut() = Groups::default();
+        *ret.imp().load_report.borrow_mut() = LoadReport::default();
         ret
     }
 }
 
-mod fontinfo {
-    use std::collections::HashMap;
+#[test]
+fn test_project_from_template() {
+    let template = ProjectTemplate {
+        charset: ProjectTemplate::block_charset(0),
+        ..ProjectTemplate::default()
+    };
+    assert_eq!(template.charset.len(), 95);
+    let project = Project::from_template(&template);
+    assert_eq!(project.imp().glyphs.borrow().len(), 95);
+    assert!(project.imp().glyphs.borrow().contains_key("ampersand"));
 
-    extern crate quick_xml;
-    extern crate serde;
-
-    use serde::Deserialize;
+    let path = std::env::temp_dir().join(format!("gerb-test-{}.ufo", std::process::id()));
+    project.save_as(&path).unwrap();
+    assert!(!*project.imp().modified.borrow());
+    let loaded = Project::from_path(path.to_str().unwrap()).unwrap();
+    std::fs::remove_dir_all(&path).unwrap();
+    assert_eq!(loaded.fontinfo().family_name(), template.family_name);
+    assert_eq!(loaded.fontinfo().units_per_em(), template.units_per_em);
+    assert_eq!(loaded.fontinfo().x_height(), template.x_height);
+    let glyphs = loaded.imp().glyphs.borrow();
+    assert_eq!(glyphs.len(), 95);
+    assert_eq!(glyphs["A"].borrow().unicode, vec!['A']);
+    assert!(glyphs["A"].borrow().is_empty());
+}
 
-    #[derive(Debug, Deserialize, PartialEq)]
-    #[serde(rename_all = "lowercase")]
-    pub struct Plist {
-        pub dict: HashMap<String, DictValue>,
-    }
+#[test]
+fn test_glyph_components_rename_remove() {
+    use crate::glyphs::glif::Glif;
 
-    #[derive(Debug, Deserialize, PartialEq)]
-    #[serde(rename_all = "lowercase")]
-    pub enum DictValue {
-        Integer(i64),
-        String(String),
-        Array(Vec<DictValue>),
-        Real(f64),
-    }
+    const AACUTE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="Aacute" format="2">
+	<unicode hex="00C1"/>
+	<advance width="668"/>
+	<outline>
+		<component base="A"/>
+		<component base="acute" xOffset="300" yOffset="200"/>
+	</outline>
+</glyph>
+"#;
+    let project = Project::new();
+    project
+        .add_glyph(Rc::new(RefCell::new(Glyph::new_empty("A", 'A'))))
+        .unwrap();
+    let g: Glyph = Glif::from_str(AACUTE)
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    project.add_glyph(Rc::new(RefCell::new(g))).unwrap();
+    let aacute = project.imp().glyphs.borrow()["Aacute"].clone();
+    assert!(aacute.borrow().components[0].base().is_some());
+    assert!(aacute.borrow().components[1].base().is_none());
+    assert!(project
+        .add_glyph(Rc::new(RefCell::new(Glyph::new_empty("A", 'A'))))
+        .is_err());
 
-    impl Plist {
-        pub fn from_str(xml: &str) -> Result<Self, Box<dyn std::error::Error>> {
-            use quick_xml::events::Event;
-            use quick_xml::Reader;
+    project.rename_glyph("A", "A.alt").unwrap();
+    assert_eq!(aacute.borrow().components[0].base_name(), "A.alt");
+    assert!(aacute.borrow().components[0].base().is_some());
+    assert!(aacute.borrow().modified);
 
-            let mut ret = Self {
-                dict: HashMap::default(),
-            };
+    let a = project.remove_glyph("A.alt").unwrap();
+    assert!(aacute.borrow().components[0].base().is_none());
+    project.add_glyph(a.clone()).unwrap();
+    assert!(aacute.borrow().components[0].base().is_some());
 
-            let mut reader = Reader::from_str(xml);
-            reader.trim_text(true);
+    *project.imp().glyph_order.borrow_mut() =
+        GlyphOrder::new(vec!["A.alt".to_string(), "Aacute".to_string()]);
+    let new_a = Rc::new(RefCell::new(Glyph::new_empty("A.alt", 'A')));
+    assert!(Rc::ptr_eq(
+        &project.replace_glyph(new_a.clone()).unwrap(),
+        &a
+    ));
+    assert_eq!(new_a.borrow().filename, a.borrow().filename);
+    assert!(Rc::ptr_eq(
+        &aacute.borrow().components[0].base().unwrap(),
+        &new_a
+    ));
+    assert_eq!(
+        project.imp().glyph_order.borrow().names(),
+        ["A.alt".to_string(), "Aacute".to_string()]
+    );
+}
 
-            let mut buf = Vec::new();
+#[test]
+fn test_project_layers() {
+    let project = Project::from_template(&ProjectTemplate {
+        charset: vec!['A', 'B'],
+        ..ProjectTemplate::default()
+    });
+    project.imp().layers.borrow_mut().push(Layer {
+        name: "background".to_string(),
+        dir_name: "glyphs.background".to_string(),
+        glyphs: HashMap::default(),
+    });
+    assert_eq!(
+        project.layer_names(),
+        vec![DEFAULT_LAYER_NAME.to_string(), "background".to_string()]
+    );
+    let a = project.layer_glyph_or_insert("background", "A").unwrap();
+    let contour = crate::glyphs::Contour::new();
+    *contour.curves().borrow_mut() = vec![crate::utils::curves::Bezier::new(
+        false,
+        vec![(0, 0), (100, 100)],
+    )];
+    a.borrow_mut().contours.push(contour);
+    /* Empty layer glyphs aren't saved. */
+    project.layer_glyph_or_insert("background", "B").unwrap();
+    assert!(project.layer_glyph_or_insert("nope", "A").is_none());
 
-            #[derive(Debug)]
-            enum ArrayType {
-                Integer,
-                Real,
-                String,
-            }
+    let path = std::env::temp_dir().join(format!("gerb-test-layers-{}.ufo", std::process::id()));
+    project.save_as(&path).unwrap();
+    let loaded = Project::from_path(path.to_str().unwrap()).unwrap();
+    std::fs::remove_dir_all(&path).unwrap();
+    assert_eq!(loaded.layer_names(), project.layer_names());
+    let layer_a = loaded.layer_glyph("background", "A").unwrap();
+    assert_eq!(layer_a.borrow().unicode, vec!['A']);
+    assert!(!layer_a.borrow().is_empty());
+    assert!(loaded.layer_glyph("background", "B").is_none());
+    assert!(loaded.layer_glyph(DEFAULT_LAYER_NAME, "B").is_some());
+}
 
-            #[derive(Debug)]
-            enum State {
-                Start,
-                InDict,
-                InKey,
-                Key(String),
-                String(String),
-                Integer(String),
-                Real(String),
-                Array(String, Vec<DictValue>, ArrayType),
-            }
-            let mut state = State::Start;
-            loop {
-                match (&mut state, reader.read_event(&mut buf)) {
-                    (State::Start, Ok(Event::Start(ref e))) => match e.name() {
-                        b"dict" => {
-                            state = State::InDict;
-                        }
-                        _ => (),
-                    },
-                    (State::InDict, Ok(Event::Start(ref e))) => match e.name() {
-                        b"key" => {
-                            state = State::InKey;
-                        }
-                        _ => (),
-                    },
-                    (State::Key(_), Ok(Event::End(ref e))) => match e.name() {
-                        b"key" => {}
-                        _ => (),
-                    },
-                    (State::Key(keyval), Ok(Event::Start(ref e))) => match e.name() {
-                        b"integer" => {
-                            let keyval = std::mem::replace(keyval, String::new());
-                            state = State::Integer(keyval);
-                        }
-                        b"array" => {
-                            let keyval = std::mem::replace(keyval, String::new());
-                            state = State::Array(keyval, vec![], ArrayType::Integer);
-                        }
-                        b"real" => {
-                            let keyval = std::mem::replace(keyval, String::new());
-                            state = State::Real(keyval);
-                        }
-                        b"string" => {
-                            let keyval = std::mem::replace(keyval, String::new());
-                            state = State::String(keyval);
-                        }
-                        _ => (),
-                    },
-                    (State::InDict, Ok(Event::End(ref e))) => match e.name() {
-                        b"dict" => {
-                            break;
-                        }
-                        b"string" | b"real" | b"integer" | b"array" => {}
-                        _ => (),
-                    },
-                    (State::Array(keyval, values, _array_type), Ok(Event::End(ref e))) => {
-                        match e.name() {
-                            b"array" => {
-                                let keyval = std::mem::replace(keyval, String::new());
-                                let values = std::mem::replace(values, vec![]);
-                                ret.dict.insert(keyval, DictValue::Array(values));
-                                state = State::InDict;
-                            }
-                            b"string" | b"real" | b"integer" => {}
-                            _ => (),
-                        }
-                    }
-                    (State::Array(_, _, array_type), Ok(Event::Start(ref e))) => match e.name() {
-                        b"integer" => {
-                            *array_type = ArrayType::Integer;
-                        }
-                        b"string" => {
-                            *array_type = ArrayType::String;
-                        }
-                        b"real" => {
-                            *array_type = ArrayType::Real;
-                        }
-                        _ => (),
-                    },
-                    (State::Array(_, values, array_type), Ok(Event::Text(e))) => match array_type {
-                        ArrayType::Integer => {
-                            values.push(DictValue::Integer(
-                                std::str::from_utf8(&e.unescaped()?)?.parse()?,
-                            ));
-                        }
-                        ArrayType::String => {
-                            values.push(DictValue::String(e.unescape_and_decode(&mut reader)?));
-                        }
-                        ArrayType::Real => {
-                            values.push(DictValue::Real(
-                                std::str::from_utf8(&e.unescaped()?)?.parse()?,
-                            ));
-                        }
-                    },
-                    (State::InKey, Ok(Event::Text(e))) => {
-                        state = State::Key(e.unescape_and_decode(&mut reader)?);
-                    }
-                    (State::String(keyval), Ok(Event::Text(e))) => {
-                        let keyval = std::mem::replace(keyval, String::new());
-                        state = State::InDict;
-                        ret.dict.insert(
-                            keyval,
-                            DictValue::String(e.unescape_and_decode(&mut reader)?),
-                        );
-                    }
-                    (State::Integer(keyval), Ok(Event::Text(e))) => {
-                        let keyval = std::mem::replace(keyval, String::new());
-                        state = State::InDict;
-                        ret.dict.insert(
-                            keyval,
-                            DictValue::Integer(std::str::from_utf8(&e.unescaped()?)?.parse()?),
-                        );
-                    }
-                    (State::Real(keyval), Ok(Event::Text(e))) => {
-                        let keyval = std::mem::replace(keyval, String::new());
-                        state = State::InDict;
-                        ret.dict.insert(
-                            keyval,
-                            DictValue::Real(std::str::from_utf8(&e.unescaped()?)?.parse()?),
-                        );
-                    }
-                    (State::InDict, Ok(Event::Eof)) => break, // exits the loop when reaching end of file
-                    (_, Err(e)) => {
-                        panic!("Error at position {}: {:?}", reader.buffer_position(), e)
-                    }
-                    _ => (), // There are several other `Event`s we do not consider here
-                }
+#[test]
+fn test_project_glyph_contents() {
+    let project = Project::from_template(&ProjectTemplate {
+        charset: vec!['A', 'B', 'C'],
+        ..ProjectTemplate::default()
+    });
+    *project.imp().glyph_order.borrow_mut() =
+        GlyphOrder::new(vec!["C".to_string(), "A".to_string()]);
+    project.imp().lib.borrow_mut().insert(
+        "com.example.flag".to_string(),
+        DictValue::Array(vec![DictValue::Integer(1), DictValue::Boolean(false)]),
+    );
+    let path = std::env::temp_dir().join(format!("gerb-test-contents-{}.ufo", std::process::id()));
+    project.save_as(&path).unwrap();
+    std::fs::write(path.join("glyphs").join(".DS_Store"), "junk").unwrap();
+    std::fs::write(path.join("glyphs").join("stray.glif"), "junk").unwrap();
+    let b_file_name = project.imp().glyphs.borrow()["B"]
+        .borrow()
+        .filename
+        .clone()
+        .unwrap();
+    std::fs::remove_file(path.join("glyphs").join(b_file_name)).unwrap();
+    let loaded = Project::from_path(path.to_str().unwrap()).unwrap();
+    std::fs::remove_dir_all(&path).unwrap();
+    let report = loaded.imp().load_report.borrow();
+    assert_eq!(report.warnings.len(), 2, "{:?}", report);
+    assert!(report.warnings[0].error.path.ends_with("stray.glif"));
+    assert_eq!(report.warnings[0].glyph, None);
+    assert_eq!(report.warnings[1].glyph.as_deref(), Some("B"));
+    assert_eq!(report.skipped_glyphs(), vec!["B"]);
+    assert_eq!(
+        loaded.imp().glyph_order.borrow().names(),
+        ["C".to_string(), "A".to_string()]
+    );
+    assert_eq!(*loaded.imp().lib.borrow(), *project.imp().lib.borrow());
+    let glyphs = loaded.imp().glyphs.borrow();
+    let mut names = glyphs.values().collect::<Vec<_>>();
+    names.sort_by(|a, b| loaded.cmp_glyphs(&a.borrow(), &b.borrow()));
+    assert_eq!(
+        names
+            .into_iter()
+            .map(|g| g.borrow().name.to_string())
+            .collect::<Vec<_>>(),
+        vec!["C".to_string(), "A".to_string()]
+    );
+}
 
-                //buf.clear();
-            }
-            Ok(ret)
-        }
-    }
-
-    #[test]
-    fn test_plist_parse() {
-        //let p: Plist = quick_xml::de::from_str(_PLIST).unwrap();
-        //println!("{:#?}", p);
-        let p: Plist = Plist::from_str(_PLIST).unwrap();
-        println!("{:#?}", p);
-    }
-
-    const _PLIST: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-	<dict>
-		<key>ascender</key>
-		<integer>712</integer>
-		<key>capHeight</key>
-		<integer>656</integer>
-		<key>copyright</key>
-		<string>Copyright 2010–2021 Adobe Systems Incorporated (http://www.adobe.com/), with Reserved Font Name 'Source'.</string>
-		<key>descender</key>
-		<integer>-205</integer>
-		<key>familyName</key>
-		<string>Source Sans 3</string>
-		<key>guidelines</key>
-		<array>
-		</array>
-		<key>italicAngle</key>
-		<integer>0</integer>
-		<key>openTypeHheaAscender</key>
-		<integer>1024</integer>
-		<key>openTypeHheaDescender</key>
-		<integer>-400</integer>
-		<key>openTypeHheaLineGap</key>
-		<integer>0</integer>
-		<key>openTypeNameDesigner</key>
-		<string>Paul D. Hunt</string>
-		<key>openTypeNameLicense</key>
-		<string>This Font Software is licensed under the SIL Open Font License, Version 1.1. This license is available with a FAQ at: http://scripts.sil.org/OFL. This Font Software is distributed on an 'AS IS' BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the SIL Open Font License for the specific language, permissions and limitations governing your use of this Font Software.</string>
-		<key>openTypeNameLicenseURL</key>
-		<string>http://scripts.sil.org/OFL</string>
-		<key>openTypeNameManufacturer</key>
-		<string>Adobe Systems Incorporated</string>
-		<key>openTypeNameManufacturerURL</key>
-		<string>http://www.adobe.com/type</string>
-		<key>openTypeOS2CodePageRanges</key>
-		<array>
-			<integer>0</integer>
-			<integer>1</integer>
-			<integer>2</integer>
-			<integer>3</integer>
-			<integer>4</integer>
-			<integer>7</integer>
-			<integer>8</integer>
-			<integer>29</integer>
-		</array>
-		<key>openTypeOS2Panose</key>
-		<array>
-			<integer>2</integer>
-			<integer>11</integer>
-			<integer>5</integer>
-			<integer>3</integer>
-			<integer>3</integer>
-			<integer>4</integer>
-			<integer>3</integer>
-			<integer>2</integer>
-			<integer>2</integer>
-			<integer>4</integer>
-		</array>
-		<key>openTypeOS2TypoAscender</key>
-		<integer>750</integer>
-		<key>openTypeOS2TypoDescender</key>
-		<integer>-250</integer>
-		<key>openTypeOS2TypoLineGap</key>
-		<integer>0</integer>
-		<key>openTypeOS2UnicodeRanges</key>
-		<array>
-			<integer>0</integer>
-			<integer>1</integer>
-			<integer>2</integer>
-			<integer>4</integer>
-			<integer>5</integer>
-			<integer>6</integer>
-			<integer>7</integer>
-			<integer>9</integer>
-			<integer>29</integer>
-			<integer>30</integer>
-			<integer>32</integer>
-			<integer>57</integer>
-		</array>
-		<key>openTypeOS2VendorID</key>
-		<string>ADBO</string>
-		<key>openTypeOS2WinAscent</key>
-		<integer>984</integer>
-		<key>openTypeOS2WinDescent</key>
-		<integer>273</integer>
-		<key>postscriptBlueFuzz</key>
-		<integer>0</integer>
-		<key>postscriptBlueScale</key>
-		<real>0.0625</real>
-		<key>postscriptBlueValues</key>
-		<array>
-			<integer>-12</integer>
-			<integer>0</integer>
-			<integer>486</integer>
-			<integer>498</integer>
-			<integer>518</integer>
-			<integer>530</integer>
-			<integer>574</integer>
-			<integer>586</integer>
-			<integer>638</integer>
-			<integer>650</integer>
-			<integer>656</integer>
-			<integer>668</integer>
-			<integer>712</integer>
-			<integer>724</integer>
-		</array>
-		<key>postscriptFamilyBlues</key>
-		<array>
-			<integer>-12</integer>
-			<integer>0</integer>
-			<integer>486</integer>
-			<integer>498</integer>
-			<integer>518</integer>
-			<integer>530</integer>
-			<integer>574</integer>
-			<integer>586</integer>
-			<integer>638</integer>
-			<integer>650</integer>
-			<integer>656</integer>
-			<integer>668</integer>
-			<integer>712</integer>
-			<integer>724</integer>
-		</array>
-		<key>postscriptFamilyOtherBlues</key>
-		<array>
-			<integer>-217</integer>
-			<integer>-205</integer>
-		</array>
-		<key>postscriptFontName</key>
-		<string>SourceSans3-Regular</string>
-		<key>postscriptOtherBlues</key>
-		<array>
-			<integer>-217</integer>
-			<integer>-205</integer>
-		</array>
-		<key>postscriptStemSnapH</key>
-		<array>
-			<integer>67</integer>
-			<integer>78</integer>
-		</array>
-		<key>postscriptStemSnapV</key>
-		<array>
-			<integer>84</integer>
-			<integer>95</integer>
-		</array>
-		<key>postscriptUnderlinePosition</key>
-		<integer>-75</integer>
-		<key>postscriptUnderlineThickness</key>
-		<integer>50</integer>
-		<key>styleName</key>
-		<string>Regular</string>
-		<key>trademark</key>
-		<string>Source is a trademark of Adobe Systems Incorporated in the United States and/or other countries.</string>
-		<key>unitsPerEm</key>
-		<integer>1000</integer>
-		<key>versionMajor</key>
-		<integer>3</integer>
-		<key>versionMinor</key>
-		<integer>38</integer>
-		<key>xHeight</key>
-		<integer>486</integer>
-	</dict>
-</plist>
-"##;
+#[test]
+fn test_project_invalid_glif() {
+    let project = Project::from_template(&ProjectTemplate {
+        charset: vec!['A', 'B'],
+        ..ProjectTemplate::default()
+    });
+    let path = std::env::temp_dir().join(format!("gerb-test-invalid-{}.ufo", std::process::id()));
+    project.save_as(&path).unwrap();
+    let b_file_name = project.imp().glyphs.borrow()["B"]
+        .borrow()
+        .filename
+        .clone()
+        .unwrap();
+    /* A line point after two off-curve points. */
+    std::fs::write(
+        path.join("glyphs").join(b_file_name),
+        r#"<glyph name="B" format="2"><outline><contour>
+<point x="0" y="0" type="line"/><point x="1" y="1"/><point x="2" y="1"/>
+<point x="3" y="0" type="line"/>
+</contour></outline></glyph>"#,
+    )
+    .unwrap();
+    let loaded = Project::from_path(path.to_str().unwrap()).unwrap();
+    std::fs::remove_dir_all(&path).unwrap();
+    let report = loaded.imp().load_report.borrow();
+    assert_eq!(report.warnings.len(), 1, "{:?}", report);
+    assert!(matches!(
+        report.warnings[0].error.kind,
+        LoadErrorKind::Glif(_)
+    ));
+    assert_eq!(report.skipped_glyphs(), vec!["B"]);
+    assert!(loaded.imp().glyphs.borrow().contains_key("A"));
+    assert!(!loaded.imp().glyphs.borrow().contains_key("B"));
 }
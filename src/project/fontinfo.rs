@@ -0,0 +1,849 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Typed contents of `fontinfo.plist`, see <https://unifiedfontobject.org/versions/ufo3/fontinfo.plist/>.
+
+use glib::prelude::*;
+use glib::subclass::prelude::*;
+use glib::{ParamFlags, ParamSpec, ParamSpecBoolean, ParamSpecDouble, ParamSpecInt64, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use crate::glyphs::Guideline;
+use crate::utils::plist::DictValue;
+
+/// Conversion between property list values and Rust types.
+pub trait PlistValue: Sized {
+    /// Returns `None` if `value` doesn't have the expected type.
+    fn from_plist(value: &DictValue) -> Option<Self>;
+    fn to_plist(&self) -> DictValue;
+}
+
+impl PlistValue for String {
+    fn from_plist(value: &DictValue) -> Option<Self> {
+        match value {
+            DictValue::String(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    fn to_plist(&self) -> DictValue {
+        DictValue::String(self.clone())
+    }
+}
+
+impl PlistValue for i64 {
+    fn from_plist(value: &DictValue) -> Option<Self> {
+        match value {
+            DictValue::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn to_plist(&self) -> DictValue {
+        DictValue::Integer(*self)
+    }
+}
+
+/// Numbers can be either `<integer>` or `<real>`. Integral values are written as `<integer>`.
+impl PlistValue for f64 {
+    fn from_plist(value: &DictValue) -> Option<Self> {
+        match value {
+            DictValue::Integer(v) => Some(*v as f64),
+            DictValue::Real(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn to_plist(&self) -> DictValue {
+        if self.fract() == 0.0 && self.abs() < i64::MAX as f64 {
+            DictValue::Integer(*self as i64)
+        } else {
+            DictValue::Real(*self)
+        }
+    }
+}
+
+impl PlistValue for bool {
+    fn from_plist(value: &DictValue) -> Option<Self> {
+        match value {
+            DictValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn to_plist(&self) -> DictValue {
+        DictValue::Boolean(*self)
+    }
+}
+
+impl<T: PlistValue> PlistValue for Vec<T> {
+    fn from_plist(value: &DictValue) -> Option<Self> {
+        match value {
+            DictValue::Array(v) => v.iter().map(T::from_plist).collect(),
+            _ => None,
+        }
+    }
+
+    fn to_plist(&self) -> DictValue {
+        DictValue::Array(self.iter().map(T::to_plist).collect())
+    }
+}
+
+impl PlistValue for Guideline {
+    fn from_plist(value: &DictValue) -> Option<Self> {
+        let dict = match value {
+            DictValue::Dict(d) => d,
+            _ => return None,
+        };
+        if dict
+            .keys()
+            .any(|k| !["x", "y", "angle", "name", "color", "identifier"].contains(&k.as_str()))
+        {
+            return None;
+        }
+        let coord = |key: &str| -> Option<i64> {
+            match dict.get(key) {
+                None => Some(0),
+                Some(DictValue::Integer(v)) => Some(*v),
+                Some(DictValue::Real(v)) if v.fract() == 0.0 => Some(*v as i64),
+                Some(_) => None,
+            }
+        };
+        let string = |key: &str| -> Option<Option<String>> {
+            match dict.get(key) {
+                None => Some(None),
+                Some(v) => Some(Some(String::from_plist(v)?)),
+            }
+        };
+        Some(
+            Guideline::builder()
+                .x(coord("x")?)
+                .y(coord("y")?)
+                .angle(match dict.get("angle") {
+                    None => 0.0,
+                    Some(v) => f64::from_plist(v)?,
+                })
+                .name(string("name")?)
+                .color(string("color")?)
+                .identifier(string("identifier")?)
+                .build(),
+        )
+    }
+
+    fn to_plist(&self) -> DictValue {
+        let mut dict = HashMap::default();
+        dict.insert("x".to_string(), DictValue::Integer(self.x()));
+        dict.insert("y".to_string(), DictValue::Integer(self.y()));
+        dict.insert("angle".to_string(), self.angle().to_plist());
+        for (key, val) in [
+            ("name", self.name()),
+            ("color", self.imp().color.borrow().clone()),
+            ("identifier", self.identifier()),
+        ] {
+            if let Some(val) = val {
+                dict.insert(key.to_string(), DictValue::String(val));
+            }
+        }
+        DictValue::Dict(dict)
+    }
+}
+
+/// Declare a struct for a dictionary value of `fontinfo.plist`. Dictionaries with keys that
+/// aren't in the specification fail to convert, so that they are preserved as-is instead.
+macro_rules! plist_dict {
+    ($(#[$meta:meta])* pub struct $name:ident { $($field:ident: $ty:ty => $key:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, PartialEq)]
+        pub struct $name {
+            $(pub $field: Option<$ty>,)*
+        }
+
+        impl PlistValue for $name {
+            fn from_plist(value: &DictValue) -> Option<Self> {
+                let dict = match value {
+                    DictValue::Dict(d) => d,
+                    _ => return None,
+                };
+                if dict.keys().any(|k| ![$($key),*].contains(&k.as_str())) {
+                    return None;
+                }
+                Some(Self {
+                    $($field: match dict.get($key) {
+                        Some(v) => Some(<$ty as PlistValue>::from_plist(v)?),
+                        None => None,
+                    },)*
+                })
+            }
+
+            fn to_plist(&self) -> DictValue {
+                #[allow(unused_mut)]
+                let mut dict = HashMap::default();
+                $(if let Some(v) = self.$field.as_ref() {
+                    dict.insert($key.to_string(), v.to_plist());
+                })*
+                DictValue::Dict(dict)
+            }
+        }
+    };
+}
+
+plist_dict! {
+    /// A record of the OpenType `gasp` table.
+    pub struct GaspRangeRecord {
+        range_max_ppem: i64 => "rangeMaxPPEM",
+        range_gasp_behavior: Vec<i64> => "rangeGaspBehavior",
+    }
+}
+
+plist_dict! {
+    /// A record of the OpenType `name` table.
+    pub struct NameRecord {
+        name_id: i64 => "nameID",
+        platform_id: i64 => "platformID",
+        encoding_id: i64 => "encodingID",
+        language_id: i64 => "languageID",
+        string: String => "string",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataUniqueId {
+        id: String => "id",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataVendor {
+        name: String => "name",
+        url: String => "url",
+        dir: String => "dir",
+        class: String => "class",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataCredit {
+        name: String => "name",
+        url: String => "url",
+        role: String => "role",
+        dir: String => "dir",
+        class: String => "class",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataCredits {
+        credits: Vec<WoffMetadataCredit> => "credits",
+    }
+}
+
+plist_dict! {
+    /// Localized text, used by several WOFF metadata records.
+    pub struct WoffMetadataText {
+        text: String => "text",
+        language: String => "language",
+        dir: String => "dir",
+        class: String => "class",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataDescription {
+        url: String => "url",
+        text: Vec<WoffMetadataText> => "text",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataLicense {
+        url: String => "url",
+        id: String => "id",
+        text: Vec<WoffMetadataText> => "text",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataCopyright {
+        text: Vec<WoffMetadataText> => "text",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataTrademark {
+        text: Vec<WoffMetadataText> => "text",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataLicensee {
+        name: String => "name",
+        dir: String => "dir",
+        class: String => "class",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataExtensionItem {
+        id: String => "id",
+        names: Vec<WoffMetadataText> => "names",
+        values: Vec<WoffMetadataText> => "values",
+    }
+}
+
+plist_dict! {
+    pub struct WoffMetadataExtension {
+        id: String => "id",
+        names: Vec<WoffMetadataText> => "names",
+        items: Vec<WoffMetadataExtensionItem> => "items",
+    }
+}
+
+/// Values that are exposed as `FontInfo` properties.
+///
+/// Unset values are shown as their type's default; setting a property that is unset to that
+/// default leaves it unset, so that just looking at an object's properties doesn't add keys to
+/// `fontinfo.plist`.
+trait PropertyValue: PlistValue + PartialEq {
+    fn param_spec(name: &str) -> ParamSpec;
+    /// The value an unset property is shown as.
+    fn unset_default() -> Option<Self>;
+    fn to_property(value: &Option<Self>) -> Value;
+    /// Returns `None` if `value` can't be converted.
+    fn from_property(value: &Value) -> Option<Option<Self>>;
+}
+
+impl PropertyValue for String {
+    fn param_spec(name: &str) -> ParamSpec {
+        glib::ParamSpecString::new(name, name, name, None, ParamFlags::READWRITE)
+    }
+
+    fn unset_default() -> Option<Self> {
+        None
+    }
+
+    fn to_property(value: &Option<Self>) -> Value {
+        value.to_value()
+    }
+
+    fn from_property(value: &Value) -> Option<Option<Self>> {
+        Some(
+            value
+                .get::<Option<String>>()
+                .ok()?
+                .filter(|s| !s.is_empty()),
+        )
+    }
+}
+
+impl PropertyValue for i64 {
+    fn param_spec(name: &str) -> ParamSpec {
+        ParamSpecInt64::new(
+            name,
+            name,
+            name,
+            i64::MIN,
+            i64::MAX,
+            0,
+            ParamFlags::READWRITE,
+        )
+    }
+
+    fn unset_default() -> Option<Self> {
+        Some(0)
+    }
+
+    fn to_property(value: &Option<Self>) -> Value {
+        value.unwrap_or_default().to_value()
+    }
+
+    fn from_property(value: &Value) -> Option<Option<Self>> {
+        Some(Some(value.get::<i64>().ok()?))
+    }
+}
+
+impl PropertyValue for f64 {
+    fn param_spec(name: &str) -> ParamSpec {
+        ParamSpecDouble::new(
+            name,
+            name,
+            name,
+            f64::MIN,
+            f64::MAX,
+            0.0,
+            ParamFlags::READWRITE,
+        )
+    }
+
+    fn unset_default() -> Option<Self> {
+        Some(0.0)
+    }
+
+    fn to_property(value: &Option<Self>) -> Value {
+        value.unwrap_or_default().to_value()
+    }
+
+    fn from_property(value: &Value) -> Option<Option<Self>> {
+        Some(Some(value.get::<f64>().ok()?))
+    }
+}
+
+impl PropertyValue for bool {
+    fn param_spec(name: &str) -> ParamSpec {
+        ParamSpecBoolean::new(name, name, name, false, ParamFlags::READWRITE)
+    }
+
+    fn unset_default() -> Option<Self> {
+        Some(false)
+    }
+
+    fn to_property(value: &Option<Self>) -> Value {
+        value.unwrap_or_default().to_value()
+    }
+
+    fn from_property(value: &Value) -> Option<Option<Self>> {
+        Some(Some(value.get::<bool>().ok()?))
+    }
+}
+
+/// Number lists are edited as space separated text.
+impl<T: PropertyValue + std::str::FromStr + ToString> PropertyValue for Vec<T> {
+    fn param_spec(name: &str) -> ParamSpec {
+        glib::ParamSpecString::new(name, name, name, None, ParamFlags::READWRITE)
+    }
+
+    fn unset_default() -> Option<Self> {
+        None
+    }
+
+    fn to_property(value: &Option<Self>) -> Value {
+        value
+            .as_ref()
+            .map(|v| {
+                v.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .to_value()
+    }
+
+    fn from_property(value: &Value) -> Option<Option<Self>> {
+        let text = value.get::<Option<String>>().ok()?.unwrap_or_default();
+        if text.trim().is_empty() {
+            return Some(None);
+        }
+        text.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<T>().ok())
+            .collect::<Option<Vec<T>>>()
+            .map(Some)
+    }
+}
+
+/// Declare the `FontInfo` object. Values in `properties` are editable as GObject properties named
+/// after their field, with `_` replaced by `-`.
+macro_rules! fontinfo {
+    (
+        properties { $($(#[doc = $doc:literal])* $field:ident: $ty:ty => $key:literal,)* }
+        other { $($(#[doc = $odoc:literal])* $ofield:ident: $oty:ty => $okey:literal,)* }
+    ) => {
+        mod imp {
+            use super::*;
+
+            #[derive(Debug, Default)]
+            pub struct FontInfo {
+                $($(#[doc = $doc])* pub $field: RefCell<Option<$ty>>,)*
+                $($(#[doc = $odoc])* pub $ofield: RefCell<Option<$oty>>,)*
+                /// Keys that aren't in the specification, or whose values don't have the expected
+                /// type. They are written back unchanged.
+                pub extra: RefCell<HashMap<String, DictValue>>,
+                /// Whether any property was changed since the last save.
+                pub modified: Cell<bool>,
+            }
+
+            #[glib::object_subclass]
+            impl ObjectSubclass for FontInfo {
+                const NAME: &'static str = "FontInfo";
+                type Type = super::FontInfo;
+                type ParentType = glib::Object;
+                type Interfaces = ();
+            }
+
+            impl ObjectImpl for FontInfo {
+                fn properties() -> &'static [ParamSpec] {
+                    static PROPERTIES: once_cell::sync::Lazy<Vec<ParamSpec>> =
+                        once_cell::sync::Lazy::new(|| {
+                            vec![$(<$ty as PropertyValue>::param_spec(
+                                &stringify!($field).replace('_', "-"),
+                            ),)*]
+                        });
+                    PROPERTIES.as_ref()
+                }
+
+                fn property(&self, _obj: &Self::Type, _id: usize, pspec: &ParamSpec) -> Value {
+                    let name = pspec.name().replace('-', "_");
+                    $(if name == stringify!($field) {
+                        return <$ty as PropertyValue>::to_property(&self.$field.borrow());
+                    })*
+                    unimplemented!("{}", pspec.name())
+                }
+
+                fn set_property(
+                    &self,
+                    _obj: &Self::Type,
+                    _id: usize,
+                    value: &Value,
+                    pspec: &ParamSpec,
+                ) {
+                    let name = pspec.name().replace('-', "_");
+                    $(if name == stringify!($field) {
+                        if let Some(new) = <$ty as PropertyValue>::from_property(value) {
+                            let mut field = self.$field.borrow_mut();
+                            let is_unset_default = field.is_none()
+                                && new == <$ty as PropertyValue>::unset_default();
+                            if *field != new && !is_unset_default {
+                                *field = new;
+                                self.modified.set(true);
+                            }
+                        }
+                        return;
+                    })*
+                    unimplemented!("{}", pspec.name())
+                }
+            }
+        }
+
+        impl FontInfo {
+            /// Read values from a parsed `fontinfo.plist` dictionary.
+            pub fn from_plist(mut dict: HashMap<String, DictValue>) -> Self {
+                let ret = Self::new();
+                $(if let Some(v) = dict.remove($key) {
+                    match <$ty as PlistValue>::from_plist(&v) {
+                        Some(v) => *ret.imp().$field.borrow_mut() = Some(v),
+                        None => {
+                            dict.insert($key.to_string(), v);
+                        }
+                    }
+                })*
+                $(if let Some(v) = dict.remove($okey) {
+                    match <$oty as PlistValue>::from_plist(&v) {
+                        Some(v) => *ret.imp().$ofield.borrow_mut() = Some(v),
+                        None => {
+                            dict.insert($okey.to_string(), v);
+                        }
+                    }
+                })*
+                *ret.imp().extra.borrow_mut() = dict;
+                ret
+            }
+
+            /// The `fontinfo.plist` dictionary. Unset values are omitted.
+            pub fn to_plist(&self) -> HashMap<String, DictValue> {
+                let imp = self.imp();
+                let mut ret = imp.extra.borrow().clone();
+                $(if let Some(v) = imp.$field.borrow().as_ref() {
+                    ret.insert($key.to_string(), v.to_plist());
+                })*
+                $(if let Some(v) = imp.$ofield.borrow().as_ref() {
+                    ret.insert($okey.to_string(), v.to_plist());
+                })*
+                ret
+            }
+        }
+    };
+}
+
+fontinfo! {
+    properties {
+        family_name: String => "familyName",
+        style_name: String => "styleName",
+        style_map_family_name: String => "styleMapFamilyName",
+        /// One of `regular`, `italic`, `bold` or `bold italic`.
+        style_map_style_name: String => "styleMapStyleName",
+        version_major: i64 => "versionMajor",
+        version_minor: i64 => "versionMinor",
+        year: i64 => "year",
+        /// Copyright statement.
+        copyright: String => "copyright",
+        /// Trademark statement.
+        trademark: String => "trademark",
+        /// Units per em.
+        units_per_em: f64 => "unitsPerEm",
+        /// Descender value. Note: The specification is agnostic about the relationship to the more specific vertical metric values.
+        descender: f64 => "descender",
+        /// x-height value.
+        x_height: f64 => "xHeight",
+        /// Cap height value.
+        cap_height: f64 => "capHeight",
+        /// Ascender value. Note: The specification is agnostic about the relationship to the more specific vertical metric values.
+        ascender: f64 => "ascender",
+        /// Italic angle. This must be an angle in counter-clockwise degrees from the vertical.
+        italic_angle: f64 => "italicAngle",
+        /// Arbitrary note about the font.
+        note: String => "note",
+        /// Format is `YYYY/MM/DD HH:MM:SS`.
+        open_type_head_created: String => "openTypeHeadCreated",
+        open_type_head_lowest_rec_ppem: i64 => "openTypeHeadLowestRecPPEM",
+        /// Bit numbers of the `flags` field.
+        open_type_head_flags: Vec<i64> => "openTypeHeadFlags",
+        open_type_hhea_ascender: i64 => "openTypeHheaAscender",
+        open_type_hhea_descender: i64 => "openTypeHheaDescender",
+        open_type_hhea_line_gap: i64 => "openTypeHheaLineGap",
+        open_type_hhea_caret_slope_rise: i64 => "openTypeHheaCaretSlopeRise",
+        open_type_hhea_caret_slope_run: i64 => "openTypeHheaCaretSlopeRun",
+        open_type_hhea_caret_offset: i64 => "openTypeHheaCaretOffset",
+        open_type_name_designer: String => "openTypeNameDesigner",
+        open_type_name_designer_url: String => "openTypeNameDesignerURL",
+        open_type_name_manufacturer: String => "openTypeNameManufacturer",
+        open_type_name_manufacturer_url: String => "openTypeNameManufacturerURL",
+        open_type_name_license: String => "openTypeNameLicense",
+        open_type_name_license_url: String => "openTypeNameLicenseURL",
+        open_type_name_version: String => "openTypeNameVersion",
+        open_type_name_unique_id: String => "openTypeNameUniqueID",
+        open_type_name_description: String => "openTypeNameDescription",
+        open_type_name_preferred_family_name: String => "openTypeNamePreferredFamilyName",
+        open_type_name_preferred_subfamily_name: String => "openTypeNamePreferredSubfamilyName",
+        open_type_name_compatible_full_name: String => "openTypeNameCompatibleFullName",
+        open_type_name_sample_text: String => "openTypeNameSampleText",
+        open_type_name_wws_family_name: String => "openTypeNameWWSFamilyName",
+        open_type_name_wws_subfamily_name: String => "openTypeNameWWSSubfamilyName",
+        open_type_os2_width_class: i64 => "openTypeOS2WidthClass",
+        open_type_os2_weight_class: i64 => "openTypeOS2WeightClass",
+        /// Bit numbers of the `fsSelection` field.
+        open_type_os2_selection: Vec<i64> => "openTypeOS2Selection",
+        open_type_os2_vendor_id: String => "openTypeOS2VendorID",
+        /// The ten PANOSE classification digits.
+        open_type_os2_panose: Vec<i64> => "openTypeOS2Panose",
+        /// Class and sub-class IDs.
+        open_type_os2_family_class: Vec<i64> => "openTypeOS2FamilyClass",
+        /// Bit numbers of the `ulUnicodeRange` fields.
+        open_type_os2_unicode_ranges: Vec<i64> => "openTypeOS2UnicodeRanges",
+        /// Bit numbers of the `ulCodePageRange` fields.
+        open_type_os2_code_page_ranges: Vec<i64> => "openTypeOS2CodePageRanges",
+        open_type_os2_typo_ascender: i64 => "openTypeOS2TypoAscender",
+        open_type_os2_typo_descender: i64 => "openTypeOS2TypoDescender",
+        open_type_os2_typo_line_gap: i64 => "openTypeOS2TypoLineGap",
+        open_type_os2_win_ascent: i64 => "openTypeOS2WinAscent",
+        open_type_os2_win_descent: i64 => "openTypeOS2WinDescent",
+        /// Bit numbers of the `fsType` field.
+        open_type_os2_type: Vec<i64> => "openTypeOS2Type",
+        open_type_os2_subscript_x_size: i64 => "openTypeOS2SubscriptXSize",
+        open_type_os2_subscript_y_size: i64 => "openTypeOS2SubscriptYSize",
+        open_type_os2_subscript_x_offset: i64 => "openTypeOS2SubscriptXOffset",
+        open_type_os2_subscript_y_offset: i64 => "openTypeOS2SubscriptYOffset",
+        open_type_os2_superscript_x_size: i64 => "openTypeOS2SuperscriptXSize",
+        open_type_os2_superscript_y_size: i64 => "openTypeOS2SuperscriptYSize",
+        open_type_os2_superscript_x_offset: i64 => "openTypeOS2SuperscriptXOffset",
+        open_type_os2_superscript_y_offset: i64 => "openTypeOS2SuperscriptYOffset",
+        open_type_os2_strikeout_size: i64 => "openTypeOS2StrikeoutSize",
+        open_type_os2_strikeout_position: i64 => "openTypeOS2StrikeoutPosition",
+        open_type_vhea_vert_typo_ascender: i64 => "openTypeVheaVertTypoAscender",
+        open_type_vhea_vert_typo_descender: i64 => "openTypeVheaVertTypoDescender",
+        open_type_vhea_vert_typo_line_gap: i64 => "openTypeVheaVertTypoLineGap",
+        open_type_vhea_caret_slope_rise: i64 => "openTypeVheaCaretSlopeRise",
+        open_type_vhea_caret_slope_run: i64 => "openTypeVheaCaretSlopeRun",
+        open_type_vhea_caret_offset: i64 => "openTypeVheaCaretOffset",
+        postscript_font_name: String => "postscriptFontName",
+        postscript_full_name: String => "postscriptFullName",
+        postscript_slant_angle: f64 => "postscriptSlantAngle",
+        postscript_unique_id: i64 => "postscriptUniqueID",
+        postscript_underline_thickness: f64 => "postscriptUnderlineThickness",
+        postscript_underline_position: f64 => "postscriptUnderlinePosition",
+        postscript_is_fixed_pitch: bool => "postscriptIsFixedPitch",
+        postscript_blue_values: Vec<f64> => "postscriptBlueValues",
+        postscript_other_blues: Vec<f64> => "postscriptOtherBlues",
+        postscript_family_blues: Vec<f64> => "postscriptFamilyBlues",
+        postscript_family_other_blues: Vec<f64> => "postscriptFamilyOtherBlues",
+        postscript_stem_snap_h: Vec<f64> => "postscriptStemSnapH",
+        postscript_stem_snap_v: Vec<f64> => "postscriptStemSnapV",
+        postscript_blue_fuzz: f64 => "postscriptBlueFuzz",
+        postscript_blue_shift: f64 => "postscriptBlueShift",
+        postscript_blue_scale: f64 => "postscriptBlueScale",
+        postscript_force_bold: bool => "postscriptForceBold",
+        postscript_default_width_x: f64 => "postscriptDefaultWidthX",
+        postscript_nominal_width_x: f64 => "postscriptNominalWidthX",
+        postscript_weight_name: String => "postscriptWeightName",
+        postscript_default_character: String => "postscriptDefaultCharacter",
+        postscript_windows_character_set: i64 => "postscriptWindowsCharacterSet",
+        macintosh_fond_family_id: i64 => "macintoshFONDFamilyID",
+        macintosh_fond_name: String => "macintoshFONDName",
+        woff_major_version: i64 => "woffMajorVersion",
+        woff_minor_version: i64 => "woffMinorVersion",
+    }
+    other {
+        open_type_gasp_range_records: Vec<GaspRangeRecord> => "openTypeGaspRangeRecords",
+        open_type_name_records: Vec<NameRecord> => "openTypeNameRecords",
+        woff_metadata_unique_id: WoffMetadataUniqueId => "woffMetadataUniqueID",
+        woff_metadata_vendor: WoffMetadataVendor => "woffMetadataVendor",
+        woff_metadata_credits: WoffMetadataCredits => "woffMetadataCredits",
+        woff_metadata_description: WoffMetadataDescription => "woffMetadataDescription",
+        woff_metadata_license: WoffMetadataLicense => "woffMetadataLicense",
+        woff_metadata_copyright: WoffMetadataCopyright => "woffMetadataCopyright",
+        woff_metadata_trademark: WoffMetadataTrademark => "woffMetadataTrademark",
+        woff_metadata_licensee: WoffMetadataLicensee => "woffMetadataLicensee",
+        woff_metadata_extensions: Vec<WoffMetadataExtension> => "woffMetadataExtensions",
+        /// A list of guideline definitions that apply to all glyphs in all layers in the font.
+        guidelines: Vec<Guideline> => "guidelines",
+    }
+}
+
+glib::wrapper! {
+    pub struct FontInfo(ObjectSubclass<imp::FontInfo>);
+}
+
+impl Default for FontInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontInfo {
+    pub fn new() -> Self {
+        let ret: Self = glib::Object::new::<Self>(&[]).unwrap();
+        ret
+    }
+
+    pub fn family_name(&self) -> String {
+        self.imp().family_name.borrow().clone().unwrap_or_default()
+    }
+
+    pub fn style_name(&self) -> String {
+        self.imp().style_name.borrow().clone().unwrap_or_default()
+    }
+
+    pub fn units_per_em(&self) -> f64 {
+        self.imp().units_per_em.borrow().unwrap_or(1000.0)
+    }
+
+    pub fn descender(&self) -> f64 {
+        self.imp().descender.borrow().unwrap_or(-200.0)
+    }
+
+    pub fn x_height(&self) -> f64 {
+        self.imp().x_height.borrow().unwrap_or(500.0)
+    }
+
+    pub fn cap_height(&self) -> f64 {
+        self.imp().cap_height.borrow().unwrap_or(600.0)
+    }
+
+    pub fn ascender(&self) -> f64 {
+        self.imp().ascender.borrow().unwrap_or(700.0)
+    }
+
+    pub fn italic_angle(&self) -> f64 {
+        self.imp().italic_angle.borrow().unwrap_or(0.0)
+    }
+}
+
+#[test]
+fn test_fontinfo_roundtrip() {
+    use crate::utils::plist::Plist;
+
+    let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>familyName</key>
+	<string>Test</string>
+	<key>unitsPerEm</key>
+	<integer>2048</integer>
+	<key>italicAngle</key>
+	<real>-12.5</real>
+	<key>postscriptIsFixedPitch</key>
+	<true/>
+	<key>postscriptBlueValues</key>
+	<array>
+		<integer>-10</integer>
+		<integer>0</integer>
+	</array>
+	<key>openTypeNameRecords</key>
+	<array>
+		<dict>
+			<key>nameID</key>
+			<integer>19</integer>
+			<key>platformID</key>
+			<integer>3</integer>
+			<key>encodingID</key>
+			<integer>1</integer>
+			<key>languageID</key>
+			<integer>1033</integer>
+			<key>string</key>
+			<string>Sample</string>
+		</dict>
+	</array>
+	<key>woffMetadataVendor</key>
+	<dict>
+		<key>name</key>
+		<string>Vendor</string>
+		<key>unknown</key>
+		<string>kept as is</string>
+	</dict>
+	<key>openTypeOS2WeightClass</key>
+	<string>not an integer</string>
+	<key>com.example.custom</key>
+	<integer>1</integer>
+	<key>guidelines</key>
+	<array>
+		<dict>
+			<key>x</key>
+			<integer>0</integer>
+			<key>y</key>
+			<integer>500</integer>
+			<key>angle</key>
+			<integer>0</integer>
+			<key>name</key>
+			<string>overshoot</string>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#;
+    let plist = Plist::from_str(src).unwrap();
+    let info = FontInfo::from_plist(plist.dict.clone());
+    assert_eq!(info.family_name(), "Test");
+    assert_eq!(info.units_per_em(), 2048.0);
+    assert_eq!(info.italic_angle(), -12.5);
+    assert_eq!(*info.imp().postscript_is_fixed_pitch.borrow(), Some(true));
+    assert_eq!(
+        info.imp().open_type_name_records.borrow().as_ref().unwrap()[0].name_id,
+        Some(19)
+    );
+    assert!(info.imp().woff_metadata_vendor.borrow().is_none());
+    assert!(info.imp().open_type_os2_weight_class.borrow().is_none());
+    assert_eq!(info.imp().extra.borrow().len(), 3);
+    assert_eq!(info.to_plist(), plist.dict);
+
+    assert_eq!(info.property::<f64>("units-per-em"), 2048.0);
+    assert_eq!(
+        info.property::<Option<String>>("postscript-blue-values"),
+        Some("-10 0".to_string())
+    );
+    info.set_property("postscript-force-bold", false);
+    assert!(info.imp().postscript_force_bold.borrow().is_none());
+    assert!(!info.imp().modified.get());
+    info.set_property("postscript-blue-values", "-12, 0 480 492");
+    assert_eq!(
+        *info.imp().postscript_blue_values.borrow(),
+        Some(vec![-12.0, 0.0, 480.0, 492.0])
+    );
+    assert!(info.imp().modified.get());
+}
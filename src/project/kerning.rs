@@ -0,0 +1,354 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Kerning pairs and glyph groups, stored in `kerning.plist` and `groups.plist`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::utils::plist::DictValue;
+
+/// Prefix of groups that can be used as the first member of a kerning pair.
+pub const KERN1_PREFIX: &str = "public.kern1.";
+/// Prefix of groups that can be used as the second member of a kerning pair.
+pub const KERN2_PREFIX: &str = "public.kern2.";
+
+/// Glyph groups from `groups.plist`, keyed by group name. Kerning groups are the ones whose name
+/// starts with [`KERN1_PREFIX`] or [`KERN2_PREFIX`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Groups {
+    pub groups: BTreeMap<String, Vec<String>>,
+}
+
+/// Kerning pairs from `kerning.plist`, keyed by first and then second member. Members are either
+/// glyph names or kerning group names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Kerning {
+    pub pairs: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// Which side of a kerning pair a glyph or group is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KerningSide {
+    First,
+    Second,
+}
+
+impl KerningSide {
+    fn prefix(self) -> &'static str {
+        match self {
+            KerningSide::First => KERN1_PREFIX,
+            KerningSide::Second => KERN2_PREFIX,
+        }
+    }
+}
+
+impl Groups {
+    pub fn from_plist(dict: HashMap<String, DictValue>) -> Result<Self, String> {
+        let mut groups = BTreeMap::new();
+        for (name, value) in dict {
+            let members = match value {
+                DictValue::Array(members) => members
+                    .into_iter()
+                    .map(|m| match m {
+                        DictValue::String(s) => Ok(s),
+                        other => Err(format!(
+                            "Group {:?} has a member that is not a string: {:?}",
+                            name, other
+                        )),
+                    })
+                    .collect::<Result<Vec<String>, String>>()?,
+                other => return Err(format!("Group {:?} is not an array: {:?}", name, other)),
+            };
+            groups.insert(name, members);
+        }
+        Ok(Self { groups })
+    }
+
+    pub fn to_plist(&self) -> HashMap<String, DictValue> {
+        self.groups
+            .iter()
+            .map(|(name, members)| {
+                (
+                    name.clone(),
+                    DictValue::Array(
+                        members
+                            .iter()
+                            .map(|m| DictValue::String(m.clone()))
+                            .collect(),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// The kerning group `glyph` belongs to on `side`, if any.
+    pub fn kerning_group(&self, glyph: &str, side: KerningSide) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(name, members)| {
+                name.starts_with(side.prefix()) && members.iter().any(|m| m == glyph)
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Replace glyph `old_name` with `new_name` in every group. Returns whether anything changed.
+    pub fn rename_glyph(&mut self, old_name: &str, new_name: &str) -> bool {
+        let mut ret = false;
+        for member in self.groups.values_mut().flatten() {
+            if member == old_name {
+                *member = new_name.to_string();
+                ret = true;
+            }
+        }
+        ret
+    }
+}
+
+impl Kerning {
+    pub fn from_plist(dict: HashMap<String, DictValue>) -> Result<Self, String> {
+        let mut pairs = BTreeMap::new();
+        for (first, value) in dict {
+            let seconds = match value {
+                DictValue::Dict(seconds) => seconds,
+                other => {
+                    return Err(format!(
+                        "Kerning for {:?} is not a dictionary: {:?}",
+                        first, other
+                    ))
+                }
+            };
+            let mut row = BTreeMap::new();
+            for (second, value) in seconds {
+                let value = match value {
+                    DictValue::Integer(v) => v as f64,
+                    DictValue::Real(v) => v,
+                    other => {
+                        return Err(format!(
+                            "Kerning value for pair {:?} {:?} is not a number: {:?}",
+                            first, second, other
+                        ))
+                    }
+                };
+                row.insert(second, value);
+            }
+            pairs.insert(first, row);
+        }
+        Ok(Self { pairs })
+    }
+
+    pub fn to_plist(&self) -> HashMap<String, DictValue> {
+        use crate::project::PlistValue;
+
+        self.pairs
+            .iter()
+            .filter(|(_, row)| !row.is_empty())
+            .map(|(first, row)| {
+                (
+                    first.clone(),
+                    DictValue::Dict(
+                        row.iter()
+                            .map(|(second, value)| (second.clone(), value.to_plist()))
+                            .collect(),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.values().all(BTreeMap::is_empty)
+    }
+
+    /// The value of the pair `first` `second` exactly as stored, without looking up groups.
+    pub fn get(&self, first: &str, second: &str) -> Option<f64> {
+        self.pairs.get(first)?.get(second).copied()
+    }
+
+    /// Set or, if `value` is `None`, remove the pair `first` `second`.
+    pub fn set(&mut self, first: &str, second: &str, value: Option<f64>) {
+        if let Some(value) = value {
+            self.pairs
+                .entry(first.to_string())
+                .or_default()
+                .insert(second.to_string(), value);
+        } else if let Some(row) = self.pairs.get_mut(first) {
+            row.remove(second);
+            if row.is_empty() {
+                self.pairs.remove(first);
+            }
+        }
+    }
+
+    /// Find the pair that applies between glyphs `left` and `right`, following the UFO lookup
+    /// order: glyph+glyph, glyph+group, group+glyph and finally group+group. Returns the members
+    /// of the matching pair and its value.
+    pub fn lookup(
+        &self,
+        groups: &Groups,
+        left: &str,
+        right: &str,
+    ) -> Option<((String, String), f64)> {
+        let left_group = groups.kerning_group(left, KerningSide::First);
+        let right_group = groups.kerning_group(right, KerningSide::Second);
+        [
+            Some((left, right)),
+            right_group.map(|r| (left, r)),
+            left_group.map(|l| (l, right)),
+            left_group.zip(right_group),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|(first, second)| {
+            self.get(first, second)
+                .map(|v| ((first.to_string(), second.to_string()), v))
+        })
+    }
+
+    /// Replace glyph `old_name` with `new_name` in every pair. Returns whether anything changed.
+    pub fn rename_glyph(&mut self, old_name: &str, new_name: &str) -> bool {
+        let mut ret = false;
+        if let Some(row) = self.pairs.remove(old_name) {
+            self.pairs.insert(new_name.to_string(), row);
+            ret = true;
+        }
+        for row in self.pairs.values_mut() {
+            if let Some(value) = row.remove(old_name) {
+                row.insert(new_name.to_string(), value);
+                ret = true;
+            }
+        }
+        ret
+    }
+
+    /// Check that every pair member and group member refers to something that exists, and that
+    /// no glyph is in more than one kerning group on the same side. Returns a description of each
+    /// problem found.
+    pub fn validate<T>(&self, groups: &Groups, glyphs: &HashMap<String, T>) -> Vec<String> {
+        let mut ret = vec![];
+        for (name, members) in &groups.groups {
+            for member in members {
+                if !glyphs.contains_key(member) {
+                    ret.push(format!(
+                        "Group {:?} contains glyph {:?} which does not exist.",
+                        name, member
+                    ));
+                }
+            }
+        }
+        for side in [KerningSide::First, KerningSide::Second] {
+            let mut seen: HashMap<&str, &str> = HashMap::default();
+            for (name, members) in groups
+                .groups
+                .iter()
+                .filter(|(name, _)| name.starts_with(side.prefix()))
+            {
+                for member in members {
+                    if let Some(other) = seen.insert(member, name) {
+                        ret.push(format!(
+                            "Glyph {:?} is in both kerning groups {:?} and {:?}.",
+                            member, other, name
+                        ));
+                    }
+                }
+            }
+        }
+        let check = |member: &str, side: KerningSide| -> Option<String> {
+            if member.starts_with(side.prefix()) {
+                if !groups.groups.contains_key(member) {
+                    return Some(format!(
+                        "Kerning refers to group {:?} which does not exist.",
+                        member
+                    ));
+                }
+            } else if !glyphs.contains_key(member) {
+                return Some(format!(
+                    "Kerning refers to glyph {:?} which does not exist.",
+                    member
+                ));
+            }
+            None
+        };
+        for (first, row) in &self.pairs {
+            ret.extend(check(first, KerningSide::First));
+            for second in row.keys() {
+                ret.extend(check(second, KerningSide::Second));
+            }
+        }
+        ret.dedup();
+        ret
+    }
+}
+
+#[test]
+fn test_kerning_lookup() {
+    use crate::utils::plist::Plist;
+
+    let groups = Plist::from_str(
+        r#"<plist version="1.0"><dict>
+<key>public.kern1.O</key><array><string>O</string><string>D</string></array>
+<key>public.kern2.O</key><array><string>O</string><string>C</string></array>
+<key>public.kern2.missing</key><array><string>Q</string></array>
+<key>public.kern2.twice</key><array><string>C</string></array>
+</dict></plist>"#,
+    )
+    .unwrap();
+    let groups = Groups::from_plist(groups.dict).unwrap();
+    let kerning = Plist::from_str(
+        r#"<plist version="1.0"><dict>
+<key>public.kern1.O</key><dict><key>public.kern2.O</key><integer>-10</integer><key>V</key><real>-50.5</real></dict>
+<key>D</key><dict><key>C</key><integer>5</integer><key>public.kern2.nope</key><integer>1</integer></dict>
+</dict></plist>"#,
+    )
+    .unwrap();
+    let mut kerning = Kerning::from_plist(kerning.dict).unwrap();
+    assert_eq!(
+        kerning.lookup(&groups, "O", "C"),
+        Some((
+            ("public.kern1.O".to_string(), "public.kern2.O".to_string()),
+            -10.
+        ))
+    );
+    assert_eq!(kerning.lookup(&groups, "D", "C").unwrap().1, 5.);
+    assert_eq!(kerning.lookup(&groups, "D", "V").unwrap().1, -50.5);
+    assert_eq!(kerning.lookup(&groups, "V", "D"), None);
+
+    let glyphs: HashMap<String, ()> = ["O", "D", "C", "V"]
+        .into_iter()
+        .map(|n| (n.to_string(), ()))
+        .collect();
+    let warnings = kerning.validate(&groups, &glyphs);
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert!(warnings[0].contains("\"Q\""));
+    assert!(warnings[1].contains("\"C\""));
+    assert!(warnings[2].contains("public.kern2.nope"));
+
+    kerning.set("D", "C", None);
+    kerning.set("D", "public.kern2.nope", None);
+    assert!(!kerning.pairs.contains_key("D"));
+    assert!(kerning.rename_glyph("V", "W"));
+    assert_eq!(kerning.get("public.kern1.O", "W"), Some(-50.5));
+    assert_eq!(Kerning::from_plist(kerning.to_plist()).unwrap(), kerning);
+    assert_eq!(Groups::from_plist(groups.to_plist()).unwrap(), groups);
+}
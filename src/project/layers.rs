@@ -0,0 +1,133 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Glyph layers, listed in `layercontents.plist`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::LoadErrorKind;
+use crate::glyphs::Glyph;
+use crate::utils::plist::{self, DictValue};
+
+/// Name of the default layer when a UFO doesn't have a `layercontents.plist`.
+pub const DEFAULT_LAYER_NAME: &str = "public.default";
+/// Directory of the default layer.
+pub const DEFAULT_LAYER_DIR: &str = "glyphs";
+
+/// A layer other than the default one. The default layer's glyphs are `Project::glyphs`.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    /// Directory of the layer's glyph set inside the UFO, e.g. `glyphs.background`.
+    pub dir_name: String,
+    pub glyphs: HashMap<String, Rc<RefCell<Glyph>>>,
+}
+
+impl Layer {
+    /// Whether `dir_name` is a glyph set directory, i.e. one whose `.glif` files and
+    /// `contents.plist` are written by the project itself.
+    pub fn is_glyph_set_dir(dir_name: &str) -> bool {
+        dir_name == DEFAULT_LAYER_DIR || dir_name.starts_with("glyphs.")
+    }
+}
+
+/// Parse `layercontents.plist` into `(layer name, directory name)` pairs, in drawing order.
+pub fn parse_layer_contents(xml: &str) -> Result<Vec<(String, String)>, LoadErrorKind> {
+    let entries = match plist::parse(xml).map_err(LoadErrorKind::Plist)? {
+        DictValue::Array(entries) => entries,
+        other => {
+            return Err(LoadErrorKind::Invalid(format!(
+                "Root value is not an array: {:?}",
+                other
+            )))
+        }
+    };
+    let mut ret = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match entry {
+            DictValue::Array(pair) => match pair.as_slice() {
+                [DictValue::String(name), DictValue::String(dir_name)] => {
+                    if ret.iter().any(|(n, _)| n == name) {
+                        return Err(LoadErrorKind::Invalid(format!(
+                            "Layer {:?} is listed twice.",
+                            name
+                        )));
+                    }
+                    ret.push((name.clone(), dir_name.clone()));
+                }
+                _ => {
+                    return Err(LoadErrorKind::Invalid(format!(
+                        "Entry is not a pair of strings: {:?}",
+                        pair
+                    )))
+                }
+            },
+            other => {
+                return Err(LoadErrorKind::Invalid(format!(
+                    "Entry is not an array: {:?}",
+                    other
+                )))
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// Serialize `(layer name, directory name)` pairs as `layercontents.plist`.
+pub fn layer_contents_to_xml<'a>(layers: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    DictValue::Array(
+        layers
+            .map(|(name, dir_name)| {
+                DictValue::Array(vec![
+                    DictValue::String(name.to_string()),
+                    DictValue::String(dir_name.to_string()),
+                ])
+            })
+            .collect(),
+    )
+    .to_xml()
+}
+
+#[test]
+fn test_layer_contents() {
+    let xml = layer_contents_to_xml(
+        [
+            ("foreground", DEFAULT_LAYER_DIR),
+            ("background", "glyphs.background"),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(
+        parse_layer_contents(&xml).unwrap(),
+        vec![
+            ("foreground".to_string(), "glyphs".to_string()),
+            ("background".to_string(), "glyphs.background".to_string()),
+        ]
+    );
+    assert!(parse_layer_contents(
+        r#"<plist version="1.0"><array><array><string>a</string></array></array></plist>"#
+    )
+    .is_err());
+    assert!(Layer::is_glyph_set_dir("glyphs.public.background"));
+    assert!(!Layer::is_glyph_set_dir("images"));
+}
@@ -0,0 +1,162 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Errors and problems found while reading a UFO directory.
+
+use std::path::{Path, PathBuf};
+
+use crate::glyphs::glif::GlifError;
+use crate::utils::plist::PlistError;
+
+/// Why a file of a UFO directory couldn't be used.
+#[derive(Debug)]
+pub enum LoadErrorKind {
+    /// The path does not exist or is not a directory.
+    NotADirectory,
+    /// Reading the file or directory failed.
+    Io(std::io::Error),
+    /// The file is not a valid property list.
+    Plist(PlistError),
+    /// The file is not a valid GLIF document.
+    Glif(GlifError),
+    /// The file is well formed but its contents are not what the UFO specification expects.
+    Invalid(String),
+}
+
+/// A problem with the file or directory at `path`.
+#[derive(Debug)]
+pub struct LoadError {
+    pub path: PathBuf,
+    pub kind: LoadErrorKind,
+}
+
+impl LoadError {
+    pub fn new(path: &Path, kind: LoadErrorKind) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            kind,
+        }
+    }
+
+    pub fn invalid(path: &Path, message: String) -> Self {
+        Self::new(path, LoadErrorKind::Invalid(message))
+    }
+
+    /// The 1-based line and column in the file where the problem was found, if known.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self.kind {
+            LoadErrorKind::Plist(ref err) => Some((err.line, err.column)),
+            LoadErrorKind::Glif(ref err) => err.location,
+            _ => None,
+        }
+    }
+
+    /// Description of the problem without the path.
+    pub fn message(&self) -> String {
+        match self.kind {
+            LoadErrorKind::NotADirectory => "Not a directory.".to_string(),
+            LoadErrorKind::Io(ref err) => err.to_string(),
+            LoadErrorKind::Plist(ref err) => err.message.clone(),
+            LoadErrorKind::Glif(ref err) => err.message.clone(),
+            LoadErrorKind::Invalid(ref message) => message.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "{}", self.path.display())?;
+        if let Some((line, column)) = self.location() {
+            write!(fmt, ":{}:{}", line, column)?;
+        }
+        write!(fmt, ": {}", self.message())
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind {
+            LoadErrorKind::Io(ref err) => Some(err),
+            LoadErrorKind::Plist(ref err) => Some(err),
+            LoadErrorKind::Glif(ref err) => Some(err),
+            LoadErrorKind::NotADirectory | LoadErrorKind::Invalid(_) => None,
+        }
+    }
+}
+
+/// A problem that didn't prevent the project from loading.
+#[derive(Debug)]
+pub struct LoadWarning {
+    /// The glyph the problem is about, if any.
+    pub glyph: Option<String>,
+    /// Whether the glyph was left out of the project because of the problem.
+    pub skipped: bool,
+    pub error: LoadError,
+}
+
+impl std::fmt::Display for LoadWarning {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.error.fmt(fmt)?;
+        if self.skipped {
+            write!(fmt, " The glyph was not loaded.")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every problem found while loading a project, in the order they were found.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub warnings: Vec<LoadWarning>,
+}
+
+impl LoadReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Record a problem that isn't about a particular glyph.
+    pub fn warn(&mut self, error: LoadError) {
+        self.warnings.push(LoadWarning {
+            glyph: None,
+            skipped: false,
+            error,
+        });
+    }
+
+    /// Record a problem with glyph `name`, which was loaded anyway unless `skipped` is set.
+    pub fn warn_glyph(&mut self, name: &str, skipped: bool, error: LoadError) {
+        self.warnings.push(LoadWarning {
+            glyph: Some(name.to_string()),
+            skipped,
+            error,
+        });
+    }
+
+    /// Names of the glyphs that could not be loaded.
+    pub fn skipped_glyphs(&self) -> Vec<&str> {
+        self.warnings
+            .iter()
+            .filter(|w| w.skipped)
+            .filter_map(|w| w.glyph.as_deref())
+            .collect()
+    }
+}
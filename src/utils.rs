@@ -19,16 +19,22 @@
  * along with gerb. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use gtk::cairo::Context;
+#[cfg(feature = "cairo")]
+use cairo::Context;
+use std::borrow::Cow;
+#[cfg(feature = "cairo")]
 use std::f64::consts::PI;
 
 pub mod curves;
+pub mod filenames;
+pub mod plist;
 pub mod range_query;
 
 pub const CODEPOINTS: &str = r##"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!"#$%&'()*+,-./:;<=>?@[\]^_`{|}~"##;
 
 pub type Point = (f64, f64);
 
+#[cfg(feature = "cairo")]
 pub fn draw_round_rectangle(
     cr: &Context,
     (x, y): (f64, f64),
@@ -126,3 +132,38 @@ pub fn distance_between_two_points<K: Into<(i64, i64)>, L: Into<(i64, i64)>>(
     let ylk = y_l - y_k;
     f64::sqrt((xlk * xlk + ylk * ylk) as f64)
 }
+
+/// Escape the XML special characters of `s` for use in attribute values and text nodes.
+pub fn xml_escape(s: &str) -> Cow<'_, str> {
+    if !s.contains(&['&', '<', '>', '"', '\''][..]) {
+        return s.into();
+    }
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            c => ret.push(c),
+        }
+    }
+    ret.into()
+}
+
+/// The 1-based line and column of the byte `offset` in `text`, counting columns in characters.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text.as_bytes()[..offset.min(text.len())];
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|p| p + 1)
+        .unwrap_or(0);
+    let column = String::from_utf8_lossy(&before[line_start..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
@@ -19,12 +19,13 @@
  * along with gerb. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use gtk::glib;
-use gtk::subclass::prelude::*;
+use glib::subclass::prelude::*;
 use std::cell::Ref;
 use std::cell::RefCell;
 
 pub type Point = (i64, i64);
+/// A point with floating point coordinates, for computations on curves.
+pub type FloatPoint = (f64, f64);
 
 glib::wrapper! {
     pub struct Bezier(ObjectSubclass<imp::Bezier>);
@@ -225,3 +226,592 @@ fn draw_curve_point(points: &[Point], t: f64) -> Option<Point> {
     assert_eq!(new_points.len(), points.len() - 1);
     draw_curve_point(&new_points, t)
 }
+
+impl Bezier {
+    /// The control points as floating point coordinates.
+    pub fn float_points(&self) -> Vec<FloatPoint> {
+        self.points()
+            .borrow()
+            .iter()
+            .map(|&(x, y)| (x as f64, y as f64))
+            .collect()
+    }
+
+    /// Split the curve at `t` into two curves of the same degree. Control points are rounded to
+    /// integer coordinates.
+    pub fn split(&self, t: f64) -> (Bezier, Bezier) {
+        let (a, b) = split_at(&self.float_points(), t);
+        let smooth = *self.smooth().borrow();
+        (
+            Bezier::new(smooth, round_points(&a)),
+            Bezier::new(smooth, round_points(&b)),
+        )
+    }
+
+    /// Exact bounding box `(x_min, y_min, x_max, y_max)` of the curve.
+    pub fn bbox(&self) -> (f64, f64, f64, f64) {
+        bounding_box(&self.float_points())
+    }
+
+    /// The values of `t` in `(0, 1)` where the curve has a horizontal or vertical extremum, in
+    /// increasing order.
+    pub fn extrema(&self) -> Vec<f64> {
+        let (mut ts, y_ts) = extrema(&self.float_points());
+        ts.extend(y_ts);
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+        ts
+    }
+
+    /// Split the curve at its extrema. Splits that would round to one of the curve's on-curve
+    /// points are skipped, so a curve without extrema is returned as a single copy.
+    pub fn split_at_extrema(&self) -> Vec<Bezier> {
+        let mut ret = vec![];
+        let mut rest = Bezier::new(*self.smooth().borrow(), self.points().borrow().clone());
+        let mut prev_t = 0.;
+        for t in self.extrema() {
+            let (left, right) = rest.split((t - prev_t) / (1. - prev_t));
+            let point = right.points().borrow()[0];
+            if point == rest.points().borrow()[0] || Some(&point) == rest.points().borrow().last() {
+                continue;
+            }
+            ret.push(left);
+            rest = right;
+            prev_t = t;
+        }
+        ret.push(rest);
+        ret
+    }
+
+    /// The value of `t` where the curve passes closest to `point`.
+    pub fn nearest(&self, point: Point) -> f64 {
+        nearest(&self.float_points(), (point.0 as f64, point.1 as f64))
+    }
+
+    /// A single curve following this curve and then `next`, which starts where this one ends.
+    /// Two lines become one line; anything else is refitted as a cubic that keeps the outer
+    /// tangents.
+    pub fn merge(&self, next: &Bezier) -> Bezier {
+        const SAMPLES: usize = 16;
+        let (a, b) = (self.float_points(), next.float_points());
+        let smooth = *self.smooth().borrow();
+        if a.len() <= 2 && b.len() <= 2 {
+            return Bezier::new(smooth, round_points(&[a[0], b[b.len() - 1]]));
+        }
+        let samples = (0..=SAMPLES)
+            .map(|i| evaluate(&a, i as f64 / SAMPLES as f64))
+            .chain((1..=SAMPLES).map(|i| evaluate(&b, i as f64 / SAMPLES as f64)))
+            .collect::<Vec<FloatPoint>>();
+        let end_tangent = tangent(&b, 1.);
+        let fitted = fit_cubic(&samples, tangent(&a, 0.), (-end_tangent.0, -end_tangent.1));
+        Bezier::new(smooth, round_points(&fitted))
+    }
+
+    /// Parameter pairs `(t, u)` where this curve at `t` meets `other` at `u`.
+    pub fn intersections(&self, other: &Bezier) -> Vec<(f64, f64)> {
+        intersections(&self.float_points(), &other.float_points())
+    }
+}
+
+/// Round floating point control points to integer coordinates.
+pub fn round_points(points: &[FloatPoint]) -> Vec<Point> {
+    points
+        .iter()
+        .map(|&(x, y)| (x.round() as i64, y.round() as i64))
+        .collect()
+}
+
+/// Evaluate the curve with control points `points` at `t` with de Casteljau's algorithm.
+pub fn evaluate(points: &[FloatPoint], t: f64) -> FloatPoint {
+    let mut p = points.to_vec();
+    for n in (1..p.len()).rev() {
+        for i in 0..n {
+            p[i] = lerp(p[i], p[i + 1], t);
+        }
+    }
+    p[0]
+}
+
+/// The control points of the derivative of the curve with control points `points`.
+pub fn derivative(points: &[FloatPoint]) -> Vec<FloatPoint> {
+    let n = (points.len() - 1) as f64;
+    points
+        .windows(2)
+        .map(|w| (n * (w[1].0 - w[0].0), n * (w[1].1 - w[0].1)))
+        .collect()
+}
+
+/// The tangent direction at `t`, falling back to the chord for degenerate curves.
+pub fn tangent(points: &[FloatPoint], t: f64) -> FloatPoint {
+    let d = if points.len() > 1 {
+        evaluate(&derivative(points), t)
+    } else {
+        (0., 0.)
+    };
+    if d.0.hypot(d.1) > 1e-9 {
+        return d;
+    }
+    let (first, last) = (points[0], points[points.len() - 1]);
+    (last.0 - first.0, last.1 - first.1)
+}
+
+/// Split the curve with control points `points` at `t` into two curves of the same degree.
+pub fn split_at(points: &[FloatPoint], t: f64) -> (Vec<FloatPoint>, Vec<FloatPoint>) {
+    let mut left = Vec::with_capacity(points.len());
+    let mut right = Vec::with_capacity(points.len());
+    let mut p = points.to_vec();
+    for n in (0..p.len()).rev() {
+        left.push(p[0]);
+        right.push(p[n]);
+        for i in 0..n {
+            p[i] = lerp(p[i], p[i + 1], t);
+        }
+    }
+    right.reverse();
+    (left, right)
+}
+
+fn lerp(a: FloatPoint, b: FloatPoint, t: f64) -> FloatPoint {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+/// Roots in `(0, 1)` of the polynomial of degree at most 2 with Bernstein coefficients `coeffs`.
+fn bernstein_roots(coeffs: &[f64]) -> Vec<f64> {
+    let (a, b, c) = match *coeffs {
+        [c0, c1] => (0., c1 - c0, c0),
+        [c0, c1, c2] => (c0 - 2. * c1 + c2, 2. * (c1 - c0), c0),
+        _ => return vec![],
+    };
+    let roots = if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            vec![]
+        } else {
+            vec![-c / b]
+        }
+    } else {
+        let discriminant = b * b - 4. * a * c;
+        if discriminant < 0. {
+            vec![]
+        } else {
+            let sqrt = discriminant.sqrt();
+            vec![(-b + sqrt) / (2. * a), (-b - sqrt) / (2. * a)]
+        }
+    };
+    let mut roots = roots
+        .into_iter()
+        .filter(|t| *t > 0. && *t < 1.)
+        .collect::<Vec<f64>>();
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots.dedup();
+    roots
+}
+
+/// The values of `t` in `(0, 1)` where the curve has a horizontal and a vertical extremum
+/// respectively, in increasing order.
+pub fn extrema(points: &[FloatPoint]) -> (Vec<f64>, Vec<f64>) {
+    if points.len() < 3 {
+        return (vec![], vec![]);
+    }
+    let d = derivative(points);
+    (
+        bernstein_roots(&d.iter().map(|p| p.0).collect::<Vec<f64>>()),
+        bernstein_roots(&d.iter().map(|p| p.1).collect::<Vec<f64>>()),
+    )
+}
+
+/// Exact bounding box `(x_min, y_min, x_max, y_max)` of the curve with control points `points`,
+/// from its end points and extrema. Curves of degree higher than three have no analytic extrema
+/// here and get the bounding box of their control points instead, which contains the curve.
+pub fn bounding_box(points: &[FloatPoint]) -> (f64, f64, f64, f64) {
+    if points.len() < 2 || points.len() > 4 {
+        return hull_bbox(points);
+    }
+    let (x_ts, y_ts) = extrema(points);
+    [points[0], points[points.len() - 1]]
+        .into_iter()
+        .chain(x_ts.into_iter().chain(y_ts).map(|t| evaluate(points, t)))
+        .fold(
+            (
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x0, y0, x1, y1), (x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        )
+}
+
+/// The smallest box containing both `a` and `b`, either of which may be missing.
+pub fn union_bbox(
+    a: Option<(f64, f64, f64, f64)>,
+    b: Option<(f64, f64, f64, f64)>,
+) -> Option<(f64, f64, f64, f64)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some((a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// The value of `t` where the curve with control points `points` passes closest to `point`.
+pub fn nearest(points: &[FloatPoint], point: FloatPoint) -> f64 {
+    const STEPS: usize = 64;
+    let distance = |t: f64| {
+        let p = evaluate(points, t);
+        (p.0 - point.0).hypot(p.1 - point.1)
+    };
+    let mut t = (0..=STEPS)
+        .map(|i| i as f64 / STEPS as f64)
+        .min_by(|a, b| distance(*a).partial_cmp(&distance(*b)).unwrap())
+        .unwrap();
+    /* Refine around the closest sample by moving towards whichever neighbour is closer, halving
+     * the step every time. */
+    let mut step = 1. / STEPS as f64;
+    while step > 1e-9 {
+        let (before, after) = ((t - step).max(0.), (t + step).min(1.));
+        if distance(before) < distance(t) {
+            t = before;
+        } else if distance(after) < distance(t) {
+            t = after;
+        } else {
+            step /= 2.;
+        }
+    }
+    t
+}
+
+/// A cubic curve through the first and last of `samples` that follows `start_tangent` and
+/// `end_tangent`, the latter pointing back from the end into the curve, and passes as close to
+/// the other samples as possible. This is the least squares fit of Schneider's "An Algorithm
+/// for Automatically Fitting Digitized Curves", with a few rounds of reparameterization.
+pub fn fit_cubic(
+    samples: &[FloatPoint],
+    start_tangent: FloatPoint,
+    end_tangent: FloatPoint,
+) -> [FloatPoint; 4] {
+    let normalize = |v: FloatPoint| {
+        let length = v.0.hypot(v.1);
+        if length < 1e-12 {
+            (0., 0.)
+        } else {
+            (v.0 / length, v.1 / length)
+        }
+    };
+    let (t1, t2) = (normalize(start_tangent), normalize(end_tangent));
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+    let chord = (last.0 - first.0).hypot(last.1 - first.1);
+    let curve = |a1: f64, a2: f64| {
+        [
+            first,
+            (first.0 + t1.0 * a1, first.1 + t1.1 * a1),
+            (last.0 + t2.0 * a2, last.1 + t2.1 * a2),
+            last,
+        ]
+    };
+    /* Chord length parameterization of the samples. */
+    let mut us = vec![0.];
+    for w in samples.windows(2) {
+        us.push(us[us.len() - 1] + (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1));
+    }
+    let total = us[us.len() - 1];
+    if total < 1e-12 {
+        return curve(0., 0.);
+    }
+    us.iter_mut().for_each(|u| *u /= total);
+
+    let dot = |a: FloatPoint, b: FloatPoint| a.0 * b.0 + a.1 * b.1;
+    let mut ret = curve(chord / 3., chord / 3.);
+    for _ in 0..4 {
+        let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0., 0., 0., 0., 0.);
+        for (&u, &p) in us.iter().zip(samples) {
+            let mu = 1. - u;
+            let (b0, b1, b2, b3) = (mu * mu * mu, 3. * mu * mu * u, 3. * mu * u * u, u * u * u);
+            let (a1, a2) = ((t1.0 * b1, t1.1 * b1), (t2.0 * b2, t2.1 * b2));
+            let base = (
+                first.0 * (b0 + b1) + last.0 * (b2 + b3),
+                first.1 * (b0 + b1) + last.1 * (b2 + b3),
+            );
+            let rest = (p.0 - base.0, p.1 - base.1);
+            c00 += dot(a1, a1);
+            c01 += dot(a1, a2);
+            c11 += dot(a2, a2);
+            x0 += dot(a1, rest);
+            x1 += dot(a2, rest);
+        }
+        let determinant = c00 * c11 - c01 * c01;
+        let (a1, a2) = if determinant.abs() < 1e-12 {
+            (chord / 3., chord / 3.)
+        } else {
+            (
+                (x0 * c11 - x1 * c01) / determinant,
+                (c00 * x1 - c01 * x0) / determinant,
+            )
+        };
+        /* Handles pointing backwards or collapsing onto the end points make loops and cusps. */
+        ret = if a1 < chord * 1e-3 || a2 < chord * 1e-3 {
+            curve(chord / 3., chord / 3.)
+        } else {
+            curve(a1, a2)
+        };
+        /* Move each sample's parameter to the closest point of the fitted curve, with a step of
+         * Newton's method. */
+        let d = derivative(&ret);
+        let dd = derivative(&d);
+        for (u, &p) in us.iter_mut().zip(samples) {
+            let q = evaluate(&ret, *u);
+            let (q1, q2) = (evaluate(&d, *u), evaluate(&dd, *u));
+            let diff = (q.0 - p.0, q.1 - p.1);
+            let denominator = dot(q1, q1) + dot(diff, q2);
+            if denominator.abs() > 1e-12 {
+                *u = (*u - dot(diff, q1) / denominator).clamp(0., 1.);
+            }
+        }
+    }
+    ret
+}
+
+/// Contribution of the curve with control points `points` to the winding number of `point`: the
+/// number of times it crosses the horizontal ray from `point` towards positive x upwards, minus the
+/// number of times it crosses it downwards.
+pub fn winding(points: &[FloatPoint], point: FloatPoint) -> i32 {
+    let mut ts = vec![0.];
+    ts.extend(extrema(points).1);
+    ts.push(1.);
+    let mut ret = 0;
+    for w in ts.windows(2) {
+        let (t0, t1) = (w[0], w[1]);
+        let (y0, y1) = (evaluate(points, t0).1, evaluate(points, t1).1);
+        /* Half-open intervals, so that a crossing at a point shared by two pieces counts once. */
+        let upwards = y0 <= point.1 && point.1 < y1;
+        let downwards = y1 <= point.1 && point.1 < y0;
+        if !upwards && !downwards {
+            continue;
+        }
+        let (mut lo, mut hi) = (t0, t1);
+        for _ in 0..60 {
+            let mid = (lo + hi) / 2.;
+            if (evaluate(points, mid).1 < point.1) == upwards {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        if evaluate(points, (lo + hi) / 2.).0 > point.0 {
+            ret += if upwards { 1 } else { -1 };
+        }
+    }
+    ret
+}
+
+/// Twice the signed area swept by the curve with control points `points` relative to the origin,
+/// i.e. the integral of `x dy - y dx` along the curve. Summed over a closed contour it gives twice
+/// the contour's area, positive if the contour runs counter-clockwise.
+pub fn signed_area(points: &[FloatPoint]) -> f64 {
+    /* Three point Gauss-Legendre quadrature is exact for the polynomials of degree up to 5 that
+     * cubic curves produce. */
+    const NODES: [(f64, f64); 3] = [
+        (0.112_701_665_379_258_3, 5. / 18.),
+        (0.5, 8. / 18.),
+        (0.887_298_334_620_741_7, 5. / 18.),
+    ];
+    if points.len() < 2 {
+        return 0.;
+    }
+    let d = derivative(points);
+    NODES
+        .iter()
+        .map(|&(t, weight)| {
+            let (p, dp) = (evaluate(points, t), evaluate(&d, t));
+            weight * (p.0 * dp.1 - p.1 * dp.0)
+        })
+        .sum()
+}
+
+/// Axis aligned bounding box of the control points, as `(min_x, min_y, max_x, max_y)`.
+fn hull_bbox(points: &[FloatPoint]) -> (f64, f64, f64, f64) {
+    points.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+    )
+}
+
+/// Whether every control point is within `tolerance` of the chord.
+fn is_flat(points: &[FloatPoint], tolerance: f64) -> bool {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (dx, dy) = (last.0 - first.0, last.1 - first.1);
+    let length = dx.hypot(dy);
+    points[1..points.len() - 1].iter().all(|p| {
+        if length < 1e-12 {
+            (p.0 - first.0).hypot(p.1 - first.1) <= tolerance
+        } else {
+            ((p.0 - first.0) * dy - (p.1 - first.1) * dx).abs() / length <= tolerance
+        }
+    })
+}
+
+/// Parameter pairs `(t, u)` where the curve with control points `a` at `t` meets the curve with
+/// control points `b` at `u`, found by subdividing both until they are flat. Where the curves
+/// overlap, only the ends of the overlap are returned.
+pub fn intersections(a: &[FloatPoint], b: &[FloatPoint]) -> Vec<(f64, f64)> {
+    const TOLERANCE: f64 = 1e-3;
+
+    let mut ret = vec![];
+    let reversed = b.iter().rev().copied().collect::<Vec<_>>();
+    if a == b || a == reversed.as_slice() {
+        /* The same curve: it only meets itself at the ends. */
+        let flip = |t: f64| if a == b { t } else { 1. - t };
+        return vec![(0., flip(0.)), (1., flip(1.))];
+    }
+    let mut budget = 1 << 14;
+    intersections_rec((a, 0., 1.), (b, 0., 1.), TOLERANCE, &mut budget, &mut ret);
+    ret.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    let mut deduped: Vec<(f64, f64)> = vec![];
+    for (t, u) in ret {
+        let p = evaluate(a, t);
+        if !deduped.iter().any(|&(t2, _)| {
+            let q = evaluate(a, t2);
+            (p.0 - q.0).hypot(p.1 - q.1) < TOLERANCE * 10.
+        }) {
+            deduped.push((t, u));
+        }
+    }
+    deduped
+}
+
+type CurvePiece<'a> = (&'a [FloatPoint], f64, f64);
+
+fn intersections_rec(
+    (a, a0, a1): CurvePiece,
+    (b, b0, b1): CurvePiece,
+    tolerance: f64,
+    budget: &mut usize,
+    out: &mut Vec<(f64, f64)>,
+) {
+    let (ax0, ay0, ax1, ay1) = hull_bbox(a);
+    let (bx0, by0, bx1, by1) = hull_bbox(b);
+    if ax1 < bx0 - tolerance
+        || bx1 < ax0 - tolerance
+        || ay1 < by0 - tolerance
+        || by1 < ay0 - tolerance
+        || *budget == 0
+    {
+        return;
+    }
+    *budget -= 1;
+    let a_flat = is_flat(a, tolerance);
+    let b_flat = is_flat(b, tolerance);
+    if a_flat && b_flat {
+        for (s, u) in line_intersections((a[0], a[a.len() - 1]), (b[0], b[b.len() - 1]), tolerance)
+        {
+            out.push((a0 + (a1 - a0) * s, b0 + (b1 - b0) * u));
+        }
+        return;
+    }
+    let halves = |points: &[FloatPoint], t0: f64, t1: f64, flat: bool| {
+        if flat {
+            vec![(points.to_vec(), t0, t1)]
+        } else {
+            let (l, r) = split_at(points, 0.5);
+            let mid = (t0 + t1) / 2.;
+            vec![(l, t0, mid), (r, mid, t1)]
+        }
+    };
+    for (a, a0, a1) in halves(a, a0, a1, a_flat) {
+        for (b, b0, b1) in halves(b, b0, b1, b_flat) {
+            intersections_rec((&a, a0, a1), (&b, b0, b1), tolerance, budget, out);
+        }
+    }
+}
+
+/// Parameter pairs where segments `a` and `b` meet. Collinear overlapping segments meet at the
+/// ends of the overlap.
+fn line_intersections(
+    (p0, p1): (FloatPoint, FloatPoint),
+    (q0, q1): (FloatPoint, FloatPoint),
+    tolerance: f64,
+) -> Vec<(f64, f64)> {
+    let cross = |a: FloatPoint, b: FloatPoint| a.0 * b.1 - a.1 * b.0;
+    let d = (p1.0 - p0.0, p1.1 - p0.1);
+    let e = (q1.0 - q0.0, q1.1 - q0.1);
+    let f = (q0.0 - p0.0, q0.1 - p0.1);
+    let denominator = cross(d, e);
+    let (d_len, e_len) = (d.0.hypot(d.1), e.0.hypot(e.1));
+    if d_len < 1e-12 || e_len < 1e-12 {
+        return vec![];
+    }
+    if denominator.abs() < 1e-9 * d_len * e_len {
+        /* Parallel: they meet only if collinear. */
+        if cross(d, f).abs() / d_len > tolerance {
+            return vec![];
+        }
+        let project_d = |p: FloatPoint| ((p.0 - p0.0) * d.0 + (p.1 - p0.1) * d.1) / (d_len * d_len);
+        let project_e = |p: FloatPoint| ((p.0 - q0.0) * e.0 + (p.1 - q0.1) * e.1) / (e_len * e_len);
+        let in_range = |t: f64| (-1e-9..=1. + 1e-9).contains(&t);
+        let mut ret = vec![];
+        for (u, q) in [(0., q0), (1., q1)] {
+            let s = project_d(q);
+            if in_range(s) {
+                ret.push((s.clamp(0., 1.), u));
+            }
+        }
+        for (s, p) in [(0., p0), (1., p1)] {
+            let u = project_e(p);
+            if in_range(u) {
+                ret.push((s, u.clamp(0., 1.)));
+            }
+        }
+        return ret;
+    }
+    let s = cross(f, e) / denominator;
+    let u = cross(f, d) / denominator;
+    let slack = tolerance / d_len.min(e_len);
+    if (-slack..=1. + slack).contains(&s) && (-slack..=1. + slack).contains(&u) {
+        vec![(s.clamp(0., 1.), u.clamp(0., 1.))]
+    } else {
+        vec![]
+    }
+}
+
+#[test]
+fn test_curve_intersections() {
+    let line = Bezier::new(false, vec![(0, 50), (100, 50)]);
+    let arch = Bezier::new(false, vec![(0, 0), (0, 133), (100, 133), (100, 0)]);
+    let hits = line.intersections(&arch);
+    assert_eq!(hits.len(), 2, "{:?}", hits);
+    for (t, u) in hits {
+        let p = evaluate(&line.float_points(), t);
+        let q = evaluate(&arch.float_points(), u);
+        assert!((p.0 - q.0).hypot(p.1 - q.1) < 0.01, "{:?} {:?}", p, q);
+    }
+    let (a, b) = arch.split(0.5);
+    assert_eq!(a.points().borrow()[3], b.points().borrow()[0]);
+    assert_eq!(a.points().borrow()[3], (50, 100));
+
+    let overlapping = Bezier::new(false, vec![(50, 50), (150, 50)]);
+    let mut hits = line.intersections(&overlapping);
+    hits.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    assert_eq!(hits, vec![(0.5, 0.), (1., 0.5)]);
+
+    /* A horizontal ray from inside a counter-clockwise square crosses its right side upwards. */
+    let square = [
+        [(0., 0.), (100., 0.)],
+        [(100., 0.), (100., 100.)],
+        [(100., 100.), (0., 100.)],
+        [(0., 100.), (0., 0.)],
+    ];
+    assert_eq!(
+        square.iter().map(|s| winding(s, (50., 50.))).sum::<i32>(),
+        1
+    );
+    assert_eq!(
+        square.iter().map(|s| winding(s, (150., 50.))).sum::<i32>(),
+        0
+    );
+    assert_eq!(winding(&arch.float_points(), (50., 50.)), -1);
+    assert_eq!(extrema(&arch.float_points()).1, vec![0.5]);
+}
@@ -0,0 +1,207 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The UFO 3 "user name to file name" convention, used to name `.glif` files and layer
+//! directories so that they are unique even on case-insensitive file systems.
+//!
+//! See <https://unifiedfontobject.org/versions/ufo3/conventions/#common-user-name-to-file-name-algorithm>.
+
+use std::collections::HashSet;
+
+/// Maximum length of a file name, in bytes.
+pub const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// Length of the number appended to a name that clashes with an existing one.
+const CLASH_COUNTER_LENGTH: usize = 15;
+
+const ILLEGAL_CHARACTERS: &[char] = &['"', '*', '+', '/', ':', '<', '>', '?', '[', '\\', ']', '|'];
+
+const RESERVED_FILE_NAMES: &[&str] = &[
+    "con", "prn", "aux", "clock$", "nul", "a:-z:", "com1", "lpt1", "lpt2", "lpt3", "com2", "com3",
+    "com4",
+];
+
+/// Convert `user_name` to a file name that starts with `prefix`, ends with `suffix` and, ignoring
+/// case, is not in `existing`. `existing` must contain lowercase file names.
+///
+/// Uppercase characters are followed by `_`, illegal characters are replaced with `_`, an
+/// initial `.` becomes `_` (if there's no prefix), and parts between `.`s that are reserved file
+/// names on Windows are prefixed with `_`. Clashes are resolved by appending a 15 digit number.
+pub fn user_name_to_file_name(
+    user_name: &str,
+    existing: &HashSet<String>,
+    prefix: &str,
+    suffix: &str,
+) -> String {
+    let mut filtered = String::with_capacity(user_name.len());
+    for (i, c) in user_name.chars().enumerate() {
+        if (i == 0 && c == '.' && prefix.is_empty())
+            || matches!(c, '\u{0}'..='\u{1f}' | '\u{7f}')
+            || ILLEGAL_CHARACTERS.contains(&c)
+        {
+            filtered.push('_');
+        } else if c.to_lowercase().ne(std::iter::once(c)) {
+            filtered.push(c);
+            filtered.push('_');
+        } else {
+            filtered.push(c);
+        }
+    }
+    let filtered = clip(
+        &filtered,
+        MAX_FILE_NAME_LENGTH.saturating_sub(prefix.len() + suffix.len()),
+    );
+    let user_name = filtered
+        .split('.')
+        .map(|part| {
+            if RESERVED_FILE_NAMES.contains(&part.to_lowercase().as_str()) {
+                format!("_{}", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(".");
+    let full_name = format!("{}{}{}", prefix, user_name, suffix);
+    if !existing.contains(&full_name.to_lowercase()) {
+        return full_name;
+    }
+    handle_clash(&user_name, existing, prefix, suffix)
+}
+
+/// Append a zero padded counter to `user_name`, clipping it if necessary. If every counter value
+/// is taken, fall back to using only a number as the name.
+fn handle_clash(user_name: &str, existing: &HashSet<String>, prefix: &str, suffix: &str) -> String {
+    let user_name = clip(
+        user_name,
+        MAX_FILE_NAME_LENGTH.saturating_sub(prefix.len() + suffix.len() + CLASH_COUNTER_LENGTH),
+    );
+    for counter in 1..999_999_999_999_999_u64 {
+        let full_name = format!(
+            "{}{}{:0width$}{}",
+            prefix,
+            user_name,
+            counter,
+            suffix,
+            width = CLASH_COUNTER_LENGTH
+        );
+        if !existing.contains(&full_name.to_lowercase()) {
+            return full_name;
+        }
+    }
+    let mut counter = 1_u64;
+    loop {
+        let full_name = format!("{}{}{}", prefix, counter, suffix);
+        if !existing.contains(&full_name.to_lowercase()) {
+            return full_name;
+        }
+        counter += 1;
+    }
+}
+
+/// The longest prefix of `s` that is at most `max_len` bytes long and ends at a character
+/// boundary.
+fn clip(s: &str, max_len: usize) -> &str {
+    if s.len() <= max_len {
+        return s;
+    }
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+#[test]
+fn test_user_name_to_file_name() {
+    let none = HashSet::new();
+    let f = |name: &str| user_name_to_file_name(name, &none, "", "");
+    /* Examples from the UFO specification. */
+    for (user_name, file_name) in [
+        ("a", "a"),
+        ("A", "A_"),
+        ("AE", "A_E_"),
+        ("Ae", "A_e"),
+        ("ae", "ae"),
+        ("aE", "aE_"),
+        ("a.alt", "a.alt"),
+        ("A.alt", "A_.alt"),
+        ("A.Alt", "A_.A_lt"),
+        ("A.aLt", "A_.aL_t"),
+        ("A.alT", "A_.alT_"),
+        ("T_H", "T__H_"),
+        ("T_h", "T__h"),
+        ("t_h", "t_h"),
+        ("F_F_I", "F__F__I_"),
+        ("f_f_i", "f_f_i"),
+        ("Aacute_V.swash", "A_acute_V_.swash"),
+        (".notdef", "_notdef"),
+        ("con", "_con"),
+        ("CON", "C_O_N_"),
+        ("con.alt", "_con.alt"),
+        ("alt.con", "alt._con"),
+    ] {
+        assert_eq!(f(user_name), file_name, "{}", user_name);
+    }
+    /* Illegal and control characters. */
+    assert_eq!(f("a/b*c\"d|e\u{7f}f\tg"), "a_b_c_d_e_f_g");
+    /* C1 controls aren't among the characters the specification replaces. */
+    assert_eq!(f("a\u{85}b"), "a\u{85}b");
+    assert_eq!(f("Ä"), "Ä_");
+    /* A leading period is kept if there is a prefix. */
+    assert_eq!(
+        user_name_to_file_name(".notdef", &none, "glyphs.", ""),
+        "glyphs..notdef"
+    );
+    assert_eq!(
+        user_name_to_file_name("A.sc", &none, "", ".glif"),
+        "A_.sc.glif"
+    );
+
+    /* Clipping */
+    let long = "a".repeat(300);
+    let file_name = user_name_to_file_name(&long, &none, "", ".glif");
+    assert_eq!(file_name.len(), MAX_FILE_NAME_LENGTH);
+    assert!(file_name.ends_with(".glif"));
+    let long = "é".repeat(200);
+    let file_name = user_name_to_file_name(&long, &none, "", ".glif");
+    assert!(file_name.len() <= MAX_FILE_NAME_LENGTH);
+    assert!(file_name.ends_with("é.glif"));
+
+    /* Clashes are case insensitive. */
+    let mut existing: HashSet<String> = ["a_.glif".to_string()].into_iter().collect();
+    assert_eq!(
+        user_name_to_file_name("A", &existing, "", ".glif"),
+        "A_000000000000001.glif"
+    );
+    existing.insert("a_000000000000001.glif".to_string());
+    assert_eq!(
+        user_name_to_file_name("A", &existing, "", ".glif"),
+        "A_000000000000002.glif"
+    );
+    let long = "a".repeat(300);
+    let clipped = user_name_to_file_name(&long, &none, "", ".glif");
+    existing.insert(clipped.clone());
+    let file_name = user_name_to_file_name(&long, &existing, "", ".glif");
+    assert_eq!(file_name.len(), MAX_FILE_NAME_LENGTH);
+    assert!(file_name.ends_with("000000000000001.glif"));
+    assert_ne!(file_name, clipped);
+}
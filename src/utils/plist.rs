@@ -0,0 +1,724 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Reading and writing XML property lists, as used by `fontinfo.plist`, `metainfo.plist` and
+//! friends in UFO directories.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::utils::{line_column, xml_escape};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plist {
+    pub dict: HashMap<String, DictValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DictValue {
+    Integer(i64),
+    String(String),
+    Array(Vec<DictValue>),
+    Real(f64),
+    Boolean(bool),
+    Dict(HashMap<String, DictValue>),
+    /// `<date>`, an ISO 8601 timestamp in UTC.
+    Date(DateTime<Utc>),
+    /// `<data>`, base64 encoded binary data.
+    Data(Vec<u8>),
+}
+
+/// Property list syntax error, with the 1-based position in the document where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlistError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for PlistError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            fmt,
+            "{} (line {}, column {})",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for PlistError {}
+
+type Result<T> = std::result::Result<T, PlistError>;
+
+impl Plist {
+    /// Parse a property list whose root value is a `<dict>`.
+    pub fn from_str(xml: &str) -> Result<Self> {
+        match parse(xml)? {
+            DictValue::Dict(dict) => Ok(Self { dict }),
+            _ => Err(PlistError {
+                line: 1,
+                column: 1,
+                message: "Property list root value is not a <dict>.".to_string(),
+            }),
+        }
+    }
+
+    /// Serialize to an XML property list. Keys are sorted so that output is stable.
+    pub fn to_xml(&self) -> String {
+        let mut ret = String::from(HEADER);
+        write_dict(&self.dict, &mut ret, 0);
+        ret.push_str("</plist>\n");
+        ret
+    }
+}
+
+impl DictValue {
+    /// Serialize to an XML property list with `self` as the root value.
+    pub fn to_xml(&self) -> String {
+        let mut ret = String::from(HEADER);
+        self.write_xml(&mut ret, 0);
+        ret.push_str("</plist>\n");
+        ret
+    }
+}
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+"#;
+
+/// Parse a property list with any kind of root value.
+pub fn parse(xml: &str) -> Result<DictValue> {
+    let mut parser = Parser {
+        xml,
+        reader: Reader::from_str(xml),
+    };
+    let mut buf = Vec::new();
+    let mut in_plist = false;
+    let mut root = None;
+    loop {
+        let event = parser
+            .reader
+            .read_event(&mut buf)
+            .map_err(|err| parser.error(err.to_string()))?;
+        match event {
+            Event::Decl(_) | Event::DocType(_) | Event::PI(_) | Event::Comment(_) => {}
+            ref e if is_whitespace(e) => {}
+            Event::Start(ref e) if e.name() == b"plist" && !in_plist && root.is_none() => {
+                in_plist = true;
+            }
+            Event::End(ref e) if e.name() == b"plist" && in_plist => {
+                in_plist = false;
+            }
+            Event::Start(ref e) if root.is_none() => {
+                let name = e.name().to_vec();
+                root = Some(parser.parse_element(&name)?);
+            }
+            Event::Empty(ref e) if root.is_none() => {
+                let name = e.name().to_vec();
+                root = Some(parser.empty_element(&name)?);
+            }
+            Event::Eof if !in_plist => {
+                return root.ok_or_else(|| parser.error("Property list is empty.".to_string()));
+            }
+            ref e => return Err(parser.unexpected(e)),
+        }
+        buf.clear();
+    }
+}
+
+fn is_whitespace(event: &Event) -> bool {
+    matches!(event, Event::Text(e) if e.iter().all(u8::is_ascii_whitespace))
+}
+
+struct Parser<'a> {
+    xml: &'a str,
+    reader: Reader<&'a [u8]>,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: String) -> PlistError {
+        let (line, column) = line_column(self.xml, self.reader.buffer_position());
+        PlistError {
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn unexpected(&self, event: &Event) -> PlistError {
+        let what = match event {
+            Event::Start(e) => format!("<{}>", String::from_utf8_lossy(e.name())),
+            Event::End(e) => format!("</{}>", String::from_utf8_lossy(e.name())),
+            Event::Empty(e) => format!("<{}/>", String::from_utf8_lossy(e.name())),
+            Event::Text(e) => format!("text {:?}", String::from_utf8_lossy(e)),
+            Event::Eof => "end of file".to_string(),
+            other => format!("{:?}", other),
+        };
+        self.error(format!("Unexpected {}.", what))
+    }
+
+    fn next_event<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Event<'b>> {
+        buf.clear();
+        self.reader
+            .read_event(buf)
+            .map_err(|err| self.error(err.to_string()))
+    }
+
+    fn parse_dict(&mut self) -> Result<HashMap<String, DictValue>> {
+        let mut ret = HashMap::default();
+        let mut buf = Vec::new();
+        loop {
+            match self.next_event(&mut buf)? {
+                Event::Start(ref e) if e.name() == b"key" => {
+                    let key = self.read_text(b"key")?;
+                    let value = self.parse_value()?;
+                    ret.insert(key, value);
+                }
+                Event::End(ref e) if e.name() == b"dict" => return Ok(ret),
+                Event::Comment(_) => {}
+                ref e if is_whitespace(e) => {}
+                ref e => return Err(self.unexpected(e)),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Vec<DictValue>> {
+        let mut ret = vec![];
+        let mut buf = Vec::new();
+        loop {
+            match self.next_event(&mut buf)? {
+                Event::Start(ref e) => {
+                    let name = e.name().to_vec();
+                    ret.push(self.parse_element(&name)?);
+                }
+                Event::Empty(ref e) => {
+                    let name = e.name().to_vec();
+                    ret.push(self.empty_element(&name)?);
+                }
+                Event::End(ref e) if e.name() == b"array" => return Ok(ret),
+                Event::Comment(_) => {}
+                ref e if is_whitespace(e) => {}
+                ref e => return Err(self.unexpected(e)),
+            }
+        }
+    }
+
+    /// Parse the value that follows a `<key>` element.
+    fn parse_value(&mut self) -> Result<DictValue> {
+        let mut buf = Vec::new();
+        loop {
+            match self.next_event(&mut buf)? {
+                Event::Start(ref e) => {
+                    let name = e.name().to_vec();
+                    return self.parse_element(&name);
+                }
+                Event::Empty(ref e) => {
+                    let name = e.name().to_vec();
+                    return self.empty_element(&name);
+                }
+                Event::Comment(_) => {}
+                ref e if is_whitespace(e) => {}
+                ref e => return Err(self.unexpected(e)),
+            }
+        }
+    }
+
+    fn empty_element(&self, name: &[u8]) -> Result<DictValue> {
+        Ok(match name {
+            b"true" => DictValue::Boolean(true),
+            b"false" => DictValue::Boolean(false),
+            b"string" => DictValue::String(String::new()),
+            b"data" => DictValue::Data(vec![]),
+            b"array" => DictValue::Array(vec![]),
+            b"dict" => DictValue::Dict(HashMap::default()),
+            other => {
+                return Err(self.error(format!(
+                    "Unexpected empty <{}/>.",
+                    String::from_utf8_lossy(other)
+                )))
+            }
+        })
+    }
+
+    /// Parse an element whose start tag `<name>` has just been read.
+    fn parse_element(&mut self, name: &[u8]) -> Result<DictValue> {
+        Ok(match name {
+            b"dict" => DictValue::Dict(self.parse_dict()?),
+            b"array" => DictValue::Array(self.parse_array()?),
+            b"string" => DictValue::String(self.read_text(name)?),
+            b"integer" => {
+                let text = self.read_text(name)?;
+                DictValue::Integer(
+                    text.trim().parse().map_err(|err| {
+                        self.error(format!("Invalid integer {:?}: {}", text, err))
+                    })?,
+                )
+            }
+            b"real" => {
+                let text = self.read_text(name)?;
+                DictValue::Real(
+                    text.trim()
+                        .parse()
+                        .map_err(|err| self.error(format!("Invalid real {:?}: {}", text, err)))?,
+                )
+            }
+            b"date" => {
+                let text = self.read_text(name)?;
+                DictValue::Date(
+                    DateTime::parse_from_rfc3339(text.trim())
+                        .map_err(|err| self.error(format!("Invalid date {:?}: {}", text, err)))?
+                        .with_timezone(&Utc),
+                )
+            }
+            b"data" => {
+                let text = self.read_text(name)?;
+                DictValue::Data(
+                    base64_decode(&text)
+                        .ok_or_else(|| self.error(format!("Invalid base64 data {:?}.", text)))?,
+                )
+            }
+            b"true" | b"false" => {
+                self.read_text(name)?;
+                DictValue::Boolean(name == b"true")
+            }
+            other => {
+                return Err(self.error(format!(
+                    "Unknown element <{}>.",
+                    String::from_utf8_lossy(other)
+                )))
+            }
+        })
+    }
+
+    /// Read character data until the end tag `</end>`.
+    fn read_text(&mut self, end: &[u8]) -> Result<String> {
+        let mut ret = String::new();
+        let mut buf = Vec::new();
+        loop {
+            match self.next_event(&mut buf)? {
+                Event::Text(ref e) => ret.push_str(
+                    &e.unescape_and_decode(&self.reader)
+                        .map_err(|err| self.error(err.to_string()))?,
+                ),
+                Event::CData(ref e) => ret.push_str(
+                    self.reader
+                        .decode(e)
+                        .map_err(|err| self.error(err.to_string()))?,
+                ),
+                Event::End(ref e) if e.name() == end => return Ok(ret),
+                Event::Comment(_) => {}
+                ref e => return Err(self.unexpected(e)),
+            }
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode `data` as padded base64, as used by `<data>` elements.
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut ret = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                ret.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                ret.push('=');
+            }
+        }
+    }
+    ret
+}
+
+/// Decode base64 `text`, ignoring whitespace. Returns `None` if `text` isn't valid base64.
+pub fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let digits = text
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect::<Vec<u8>>();
+    if digits.len() % 4 != 0 {
+        return None;
+    }
+    let mut ret = Vec::with_capacity(digits.len() / 4 * 3);
+    for (i, chunk) in digits.chunks(4).enumerate() {
+        let is_last = i + 1 == digits.len() / 4;
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return None;
+        }
+        let mut n = 0u32;
+        for &b in &chunk[..4 - padding] {
+            let v = BASE64_ALPHABET.iter().position(|&a| a == b)? as u32;
+            n = n << 6 | v;
+        }
+        n <<= 6 * padding as u32;
+        ret.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(ret)
+}
+
+/// Append `dict` to `out` as a `<dict>` element indented by `indent` tabs, for embedding property
+/// list values in other XML documents.
+pub fn write_dict(dict: &HashMap<String, DictValue>, out: &mut String, indent: usize) {
+    let tabs = "\t".repeat(indent);
+    if dict.is_empty() {
+        out.push_str(&format!("{}<dict/>\n", tabs));
+        return;
+    }
+    out.push_str(&format!("{}<dict>\n", tabs));
+    let mut keys = dict.keys().collect::<Vec<_>>();
+    keys.sort();
+    for key in keys {
+        out.push_str(&format!("{}\t<key>{}</key>\n", tabs, xml_escape(key)));
+        dict[key].write_xml(out, indent + 1);
+    }
+    out.push_str(&format!("{}</dict>\n", tabs));
+}
+
+impl DictValue {
+    fn write_xml(&self, out: &mut String, indent: usize) {
+        let tabs = "\t".repeat(indent);
+        match self {
+            DictValue::Integer(v) => out.push_str(&format!("{}<integer>{}</integer>\n", tabs, v)),
+            DictValue::Real(v) => out.push_str(&format!("{}<real>{:?}</real>\n", tabs, v)),
+            DictValue::String(v) => {
+                out.push_str(&format!("{}<string>{}</string>\n", tabs, xml_escape(v)))
+            }
+            DictValue::Boolean(true) => out.push_str(&format!("{}<true/>\n", tabs)),
+            DictValue::Boolean(false) => out.push_str(&format!("{}<false/>\n", tabs)),
+            DictValue::Date(v) => out.push_str(&format!(
+                "{}<date>{}</date>\n",
+                tabs,
+                v.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            )),
+            DictValue::Data(v) => {
+                out.push_str(&format!("{}<data>{}</data>\n", tabs, base64_encode(v)))
+            }
+            DictValue::Array(values) if values.is_empty() => {
+                out.push_str(&format!("{}<array/>\n", tabs))
+            }
+            DictValue::Array(values) => {
+                out.push_str(&format!("{}<array>\n", tabs));
+                for v in values {
+                    v.write_xml(out, indent + 1);
+                }
+                out.push_str(&format!("{}</array>\n", tabs));
+            }
+            DictValue::Dict(dict) => write_dict(dict, out, indent),
+        }
+    }
+}
+
+#[test]
+fn test_plist_write() {
+    let p: Plist = Plist::from_str(_PLIST).unwrap();
+    let p2: Plist = Plist::from_str(&p.to_xml()).unwrap();
+    assert_eq!(p, p2);
+}
+
+#[test]
+fn test_plist_parse() {
+    let p: Plist = Plist::from_str(_PLIST).unwrap();
+    assert_eq!(
+        p.dict["familyName"],
+        DictValue::String("Source Sans 3".to_string())
+    );
+    assert_eq!(p.dict["unitsPerEm"], DictValue::Integer(1000));
+    assert_eq!(p.dict["guidelines"], DictValue::Array(vec![]));
+
+    let p: Plist = Plist::from_str(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>postscriptIsFixedPitch</key>
+	<true/>
+	<key>note</key>
+	<string> a &amp; b
+</string>
+	<key>openTypeNameRecords</key>
+	<array>
+		<dict>
+			<key>nameID</key>
+			<integer>9</integer>
+			<key>string</key>
+			<string/>
+		</dict>
+	</array>
+</dict>
+</plist>"#,
+    )
+    .unwrap();
+    assert_eq!(p.dict["postscriptIsFixedPitch"], DictValue::Boolean(true));
+    assert_eq!(p.dict["note"], DictValue::String(" a & b\n".to_string()));
+    assert_eq!(
+        p.dict["openTypeNameRecords"],
+        DictValue::Array(vec![DictValue::Dict(
+            [
+                ("nameID".to_string(), DictValue::Integer(9)),
+                ("string".to_string(), DictValue::String(String::new())),
+            ]
+            .into_iter()
+            .collect()
+        )])
+    );
+    assert_eq!(Plist::from_str(&p.to_xml()).unwrap(), p);
+}
+
+#[test]
+fn test_plist_values_roundtrip() {
+    let value = DictValue::Dict(
+        [
+            (
+                "created".to_string(),
+                DictValue::Date(
+                    DateTime::parse_from_rfc3339("2022-11-05T13:04:59Z")
+                        .unwrap()
+                        .with_timezone(&Utc),
+                ),
+            ),
+            (
+                "data".to_string(),
+                DictValue::Data(b"gerb\x00\xff".to_vec()),
+            ),
+            ("empty data".to_string(), DictValue::Data(vec![])),
+            ("real".to_string(), DictValue::Real(-0.5)),
+            (
+                "<&>".to_string(),
+                DictValue::String("\"'<&>'\"".to_string()),
+            ),
+            (
+                "nested".to_string(),
+                DictValue::Array(vec![
+                    DictValue::Boolean(false),
+                    DictValue::Array(vec![DictValue::Dict(HashMap::default())]),
+                ]),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    let xml = value.to_xml();
+    assert!(xml.contains("<date>2022-11-05T13:04:59Z</date>"));
+    assert!(xml.contains("<data>Z2VyYgD/</data>"));
+    assert_eq!(parse(&xml).unwrap(), value);
+
+    assert_eq!(
+        parse("<plist version=\"1.0\"><array><string>public.default</string><data>\n  Z2Vy\n  Yg==\n</data></array></plist>")
+            .unwrap(),
+        DictValue::Array(vec![
+            DictValue::String("public.default".to_string()),
+            DictValue::Data(b"gerb".to_vec()),
+        ])
+    );
+    for data in [&b""[..], b"g", b"ge", b"ger", b"gerb"] {
+        assert_eq!(base64_decode(&base64_encode(data)).unwrap(), data);
+    }
+    assert!(base64_decode("Z2V=yYg==").is_none());
+}
+
+#[test]
+fn test_plist_errors() {
+    let err = Plist::from_str(
+        "<plist>\n<dict>\n\t<key>a</key>\n\t<integer>x</integer>\n</dict>\n</plist>",
+    )
+    .unwrap_err();
+    assert_eq!((err.line, err.column), (4, 22));
+    let err = Plist::from_str("<plist>\n<dict>\n\t<foo/>\n</dict>\n</plist>").unwrap_err();
+    assert_eq!(err.line, 3);
+    assert!(Plist::from_str("<plist><array/></plist>").is_err());
+    assert!(parse("<plist><dict>").is_err());
+    assert!(parse("").is_err());
+}
+
+const _PLIST: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+	<dict>
+		<key>ascender</key>
+		<integer>712</integer>
+		<key>capHeight</key>
+		<integer>656</integer>
+		<key>copyright</key>
+		<string>Copyright 2010–2021 Adobe Systems Incorporated (http://www.adobe.com/), with Reserved Font Name 'Source'.</string>
+		<key>descender</key>
+		<integer>-205</integer>
+		<key>familyName</key>
+		<string>Source Sans 3</string>
+		<key>guidelines</key>
+		<array>
+		</array>
+		<key>italicAngle</key>
+		<integer>0</integer>
+		<key>openTypeHheaAscender</key>
+		<integer>1024</integer>
+		<key>openTypeHheaDescender</key>
+		<integer>-400</integer>
+		<key>openTypeHheaLineGap</key>
+		<integer>0</integer>
+		<key>openTypeNameDesigner</key>
+		<string>Paul D. Hunt</string>
+		<key>openTypeNameLicense</key>
+		<string>This Font Software is licensed under the SIL Open Font License, Version 1.1. This license is available with a FAQ at: http://scripts.sil.org/OFL. This Font Software is distributed on an 'AS IS' BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the SIL Open Font License for the specific language, permissions and limitations governing your use of this Font Software.</string>
+		<key>openTypeNameLicenseURL</key>
+		<string>http://scripts.sil.org/OFL</string>
+		<key>openTypeNameManufacturer</key>
+		<string>Adobe Systems Incorporated</string>
+		<key>openTypeNameManufacturerURL</key>
+		<string>http://www.adobe.com/type</string>
+		<key>openTypeOS2CodePageRanges</key>
+		<array>
+			<integer>0</integer>
+			<integer>1</integer>
+			<integer>2</integer>
+			<integer>3</integer>
+			<integer>4</integer>
+			<integer>7</integer>
+			<integer>8</integer>
+			<integer>29</integer>
+		</array>
+		<key>openTypeOS2Panose</key>
+		<array>
+			<integer>2</integer>
+			<integer>11</integer>
+			<integer>5</integer>
+			<integer>3</integer>
+			<integer>3</integer>
+			<integer>4</integer>
+			<integer>3</integer>
+			<integer>2</integer>
+			<integer>2</integer>
+			<integer>4</integer>
+		</array>
+		<key>openTypeOS2TypoAscender</key>
+		<integer>750</integer>
+		<key>openTypeOS2TypoDescender</key>
+		<integer>-250</integer>
+		<key>openTypeOS2TypoLineGap</key>
+		<integer>0</integer>
+		<key>openTypeOS2UnicodeRanges</key>
+		<array>
+			<integer>0</integer>
+			<integer>1</integer>
+			<integer>2</integer>
+			<integer>4</integer>
+			<integer>5</integer>
+			<integer>6</integer>
+			<integer>7</integer>
+			<integer>9</integer>
+			<integer>29</integer>
+			<integer>30</integer>
+			<integer>32</integer>
+			<integer>57</integer>
+		</array>
+		<key>openTypeOS2VendorID</key>
+		<string>ADBO</string>
+		<key>openTypeOS2WinAscent</key>
+		<integer>984</integer>
+		<key>openTypeOS2WinDescent</key>
+		<integer>273</integer>
+		<key>postscriptBlueFuzz</key>
+		<integer>0</integer>
+		<key>postscriptBlueScale</key>
+		<real>0.0625</real>
+		<key>postscriptBlueValues</key>
+		<array>
+			<integer>-12</integer>
+			<integer>0</integer>
+			<integer>486</integer>
+			<integer>498</integer>
+			<integer>518</integer>
+			<integer>530</integer>
+			<integer>574</integer>
+			<integer>586</integer>
+			<integer>638</integer>
+			<integer>650</integer>
+			<integer>656</integer>
+			<integer>668</integer>
+			<integer>712</integer>
+			<integer>724</integer>
+		</array>
+		<key>postscriptFamilyBlues</key>
+		<array>
+			<integer>-12</integer>
+			<integer>0</integer>
+			<integer>486</integer>
+			<integer>498</integer>
+			<integer>518</integer>
+			<integer>530</integer>
+			<integer>574</integer>
+			<integer>586</integer>
+			<integer>638</integer>
+			<integer>650</integer>
+			<integer>656</integer>
+			<integer>668</integer>
+			<integer>712</integer>
+			<integer>724</integer>
+		</array>
+		<key>postscriptFamilyOtherBlues</key>
+		<array>
+			<integer>-217</integer>
+			<integer>-205</integer>
+		</array>
+		<key>postscriptFontName</key>
+		<string>SourceSans3-Regular</string>
+		<key>postscriptOtherBlues</key>
+		<array>
+			<integer>-217</integer>
+			<integer>-205</integer>
+		</array>
+		<key>postscriptStemSnapH</key>
+		<array>
+			<integer>67</integer>
+			<integer>78</integer>
+		</array>
+		<key>postscriptStemSnapV</key>
+		<array>
+			<integer>84</integer>
+			<integer>95</integer>
+		</array>
+		<key>postscriptUnderlinePosition</key>
+		<integer>-75</integer>
+		<key>postscriptUnderlineThickness</key>
+		<integer>50</integer>
+		<key>styleName</key>
+		<string>Regular</string>
+		<key>trademark</key>
+		<string>Source is a trademark of Adobe Systems Incorporated in the United States and/or other countries.</string>
+		<key>unitsPerEm</key>
+		<integer>1000</integer>
+		<key>versionMajor</key>
+		<integer>3</integer>
+		<key>versionMinor</key>
+		<integer>38</integer>
+		<key>xHeight</key>
+		<integer>486</integer>
+	</dict>
+</plist>
+"##;
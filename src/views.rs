@@ -21,6 +21,8 @@
 
 mod glyph_edit;
 mod glyphs_overview;
+mod kerning_edit;
 
 pub use glyph_edit::*;
 pub use glyphs_overview::*;
+pub use kerning_edit::*;
@@ -28,8 +28,11 @@ use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::glyphs::{Contour, Glyph, GlyphDrawingOptions, Guideline};
+use crate::glyphs::{
+    join_contours, BooleanOperation, Contour, Glyph, GlyphDrawingOptions, Guideline,
+};
 use crate::project::Project;
+use crate::utils::range_query::KdTree;
 
 mod bezier_pen;
 mod viewhide;
@@ -58,6 +61,7 @@ enum ControlPointMode {
     None,
     Drag,
     DragGuideline(usize),
+    DragAnchor(usize),
 }
 
 impl Default for ControlPointMode {
@@ -98,151 +102,73 @@ impl Tool {
 #[derive(Debug, Clone)]
 struct GlyphState {
     app: gtk::Application,
+    project: Project,
     glyph: Rc<RefCell<Glyph>>,
-    reference: Rc<RefCell<Glyph>>,
     selection: Vec<usize>,
     tool: Tool,
     points: Rc<RefCell<Vec<ControlPoint>>>,
     points_map: Rc<RefCell<HashMap<(i64, i64), Vec<usize>>>>,
     kd_tree: Rc<RefCell<crate::utils::range_query::KdTree>>,
+    /// Anchor positions, identified by their index in the glyph's anchors.
+    anchors_kd_tree: Rc<RefCell<KdTree>>,
     drar: gtk::DrawingArea,
 }
 
 impl GlyphState {
-    fn new(glyph: &Rc<RefCell<Glyph>>, app: gtk::Application, drar: gtk::DrawingArea) -> Self {
+    fn new(
+        glyph: &Rc<RefCell<Glyph>>,
+        app: gtk::Application,
+        project: Project,
+        drar: gtk::DrawingArea,
+    ) -> Self {
         let control_points = Rc::new(RefCell::new(vec![]));
         let points_map: Rc<RefCell<HashMap<(i64, i64), Vec<usize>>>> =
             Rc::new(RefCell::new(HashMap::default()));
 
         let mut ret = GlyphState {
-            // FIXME: needs a deep clone (duplicate) here
-            glyph: Rc::new(RefCell::new(glyph.borrow().clone())),
+            glyph: Rc::clone(glyph),
             app,
-            reference: Rc::clone(glyph),
+            project,
             points: control_points,
             points_map,
             tool: Tool::default(),
             selection: vec![],
             kd_tree: Rc::new(RefCell::new(crate::utils::range_query::KdTree::new(&[]))),
+            anchors_kd_tree: Rc::new(RefCell::new(anchors_kd_tree(&glyph.borrow()))),
             drar,
         };
 
         for (contour_index, contour) in glyph.borrow().contours.iter().enumerate() {
             ret.add_contour(contour, contour_index);
         }
+        for anchor in glyph.borrow().anchors.iter() {
+            /* Anchors can also be edited from the TabInfo sidebar, so keep hit testing and the
+             * view in sync with any property change. */
+            anchor.connect_notify_local(
+                None,
+                clone!(@weak ret.glyph as glyph, @weak ret.anchors_kd_tree as kd_tree, @weak ret.project as project, @weak ret.drar as drar => move |_, _| {
+                    if let Ok(mut glyph) = glyph.try_borrow_mut() {
+                        glyph.modified = true;
+                    }
+                    if let Ok(glyph) = glyph.try_borrow() {
+                        *kd_tree.borrow_mut() = anchors_kd_tree(&glyph);
+                    }
+                    *project.imp().modified.borrow_mut() = true;
+                    drar.queue_draw();
+                }),
+            );
+        }
         ret
     }
 
     fn add_contour(&mut self, contour: &Contour, contour_index: usize) {
-        let mut points = self.points.borrow_mut();
-        let mut points_map = self.points_map.borrow_mut();
-        let prev_len = points.len();
-        for (curve_index, curve) in contour.curves().borrow().iter().enumerate() {
-            match curve.points().borrow().len() {
-                4 => {
-                    for (endpoint, handle) in [(0, 1), (3, 2)] {
-                        let mut point_index = points.len();
-                        points.push(ControlPoint {
-                            contour_index,
-                            curve_index,
-                            point_index: endpoint,
-                            position: curve.points().borrow()[endpoint],
-                            kind: Endpoint {
-                                handle: Some(point_index + 1),
-                            },
-                        });
-                        points_map
-                            .entry(curve.points().borrow()[endpoint])
-                            .or_default()
-                            .push(point_index);
-                        let endpoint_index = point_index;
-                        point_index += 1;
-                        points.push(ControlPoint {
-                            contour_index,
-                            curve_index,
-                            point_index: handle,
-                            position: curve.points().borrow()[handle],
-                            kind: Handle {
-                                end_points: vec![endpoint_index],
-                            },
-                        });
-                        points_map
-                            .entry(curve.points().borrow()[handle])
-                            .or_default()
-                            .push(point_index);
-                    }
-                }
-                3 => {
-                    let mut point_index = points.len();
-                    points.push(ControlPoint {
-                        contour_index,
-                        curve_index,
-                        point_index: 0,
-                        position: curve.points().borrow()[0],
-                        kind: Endpoint {
-                            handle: Some(point_index + 1),
-                        },
-                    });
-                    points_map
-                        .entry(curve.points().borrow()[0])
-                        .or_default()
-                        .push(point_index);
-                    point_index += 1;
-                    points.push(ControlPoint {
-                        contour_index,
-                        curve_index,
-                        point_index: 1,
-                        position: curve.points().borrow()[1],
-                        kind: Handle {
-                            end_points: vec![point_index - 1, point_index + 1],
-                        },
-                    });
-                    points_map
-                        .entry(curve.points().borrow()[1])
-                        .or_default()
-                        .push(point_index);
-                    point_index += 1;
-                    points.push(ControlPoint {
-                        contour_index,
-                        curve_index,
-                        point_index: 2,
-                        position: curve.points().borrow()[2],
-                        kind: Endpoint {
-                            handle: Some(point_index - 1),
-                        },
-                    });
-                    points_map
-                        .entry(curve.points().borrow()[2])
-                        .or_default()
-                        .push(point_index);
-                }
-                2 => {
-                    let mut point_index = points.len();
-                    for endpoint in 0..=1 {
-                        points.push(ControlPoint {
-                            contour_index,
-                            curve_index,
-                            point_index: endpoint,
-                            position: curve.points().borrow()[endpoint],
-                            kind: Endpoint { handle: None },
-                        });
-                        points_map
-                            .entry(curve.points().borrow()[endpoint])
-                            .or_default()
-                            .push(point_index);
-                        point_index += 1;
-                    }
-                }
-                1 => {}
-                0 => {}
-                _ => unreachable!(), //FIXME
-            }
-        }
-        let mut kd_tree = self.kd_tree.borrow_mut();
-        for i in prev_len..points.len() {
-            let pos = points[i].position;
-            kd_tree.add(pos, i);
-        }
+        add_contour_points(
+            &mut self.points.borrow_mut(),
+            &mut self.points_map.borrow_mut(),
+            &mut self.kd_tree.borrow_mut(),
+            contour,
+            contour_index,
+        );
     }
 
     fn set_selection(&mut self, selection: &[(usize, (i64, i64))]) {
@@ -256,14 +182,383 @@ impl GlyphState {
     }
 
     fn update_positions(&mut self, new_pos: (i64, i64)) {
-        let mut action = self.update_point(&self.selection, new_pos);
+        let action = self.update_point(&self.selection, new_pos);
+        self.add_undo_action(action);
+    }
+
+    /// Mark the glyph and its project as having unsaved changes.
+    fn set_modified(&self) {
+        self.glyph.borrow_mut().modified = true;
+        *self.project.imp().modified.borrow_mut() = true;
+    }
+
+    /// Perform `action`, which edits the glyph, and register it in the undo database.
+    fn add_undo_action(&self, action: crate::Action) {
+        self.register_action(action);
+        self.set_modified();
+    }
+
+    /// Perform `action` and register it in the undo database without marking the glyph as
+    /// modified, for actions that only change other glyphs.
+    fn register_action(&self, mut action: crate::Action) {
         (action.redo)();
         let app: &crate::Application =
-            crate::Application::from_instance(&self.app.downcast_ref::<crate::GerbApp>().unwrap());
+            crate::Application::from_instance(self.app.downcast_ref::<crate::GerbApp>().unwrap());
         let undo_db = app.undo_db.borrow_mut();
         undo_db.event(action);
     }
 
+    /// Indices of the contours that have a selected point, in drawing order.
+    fn selected_contours(&self) -> Vec<usize> {
+        let mut indices = self
+            .selection
+            .iter()
+            .filter_map(|&i| self.points.borrow().get(i).map(|p| p.contour_index))
+            .collect::<Vec<usize>>();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Append copies of the selected contours, or of every contour if nothing is selected, to
+    /// `target`, which is the same glyph in another layer.
+    fn copy_contours_to(&self, target: Rc<RefCell<Glyph>>) -> crate::Action {
+        let glyph = self.glyph.borrow();
+        let mut indices = self.selected_contours();
+        if indices.is_empty() {
+            indices = (0..glyph.contours.len()).collect();
+        }
+        let contours = indices
+            .into_iter()
+            .map(|i| glyph.contours[i].duplicate())
+            .collect::<Vec<Contour>>();
+        let prev_len = target.borrow().contours.len();
+        let drar = self.drar.clone();
+        crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "copy-contours",
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: Box::new(clone!(@weak target, @weak drar => move || {
+                let mut target = target.borrow_mut();
+                target.contours.extend(contours.iter().cloned());
+                target.modified = true;
+                drar.queue_draw();
+            })),
+            undo: Box::new(clone!(@weak target, @weak drar => move || {
+                target.borrow_mut().contours.truncate(prev_len);
+                drar.queue_draw();
+            })),
+        }
+    }
+
+    /// Append `contours`, for example ones read from an SVG file, to the glyph.
+    fn import_contours(&self, contours: Vec<Contour>) -> crate::Action {
+        let prev_len = self.glyph.borrow().contours.len();
+        let prev_points_len = self.points.borrow().len();
+        let drar = self.drar.clone();
+        crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "import-contours",
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: Box::new(
+                clone!(@weak self.points as points, @weak self.points_map as points_map, @weak self.kd_tree as kd_tree, @weak self.glyph as glyph, @weak drar => move || {
+                    let mut glyph = glyph.borrow_mut();
+                    let mut points = points.borrow_mut();
+                    let mut points_map = points_map.borrow_mut();
+                    let mut kd_tree = kd_tree.borrow_mut();
+                    for contour in contours.iter() {
+                        let contour = contour.duplicate();
+                        add_contour_points(
+                            &mut points,
+                            &mut points_map,
+                            &mut kd_tree,
+                            &contour,
+                            glyph.contours.len(),
+                        );
+                        glyph.contours.push(contour);
+                    }
+                    drar.queue_draw();
+                }),
+            ),
+            undo: Box::new(
+                clone!(@weak self.points as points, @weak self.points_map as points_map, @weak self.kd_tree as kd_tree, @weak self.glyph as glyph, @weak drar => move || {
+                    let mut glyph = glyph.borrow_mut();
+                    let mut points = points.borrow_mut();
+                    let mut points_map = points_map.borrow_mut();
+                    let mut kd_tree = kd_tree.borrow_mut();
+                    glyph.contours.truncate(prev_len);
+                    for idx in prev_points_len..points.len() {
+                        let position = points[idx].position;
+                        points_map.entry(position).and_modify(|points_vec| {
+                            points_vec.retain(|p| *p != idx);
+                        });
+                        kd_tree.remove(position, idx);
+                    }
+                    points.truncate(prev_points_len);
+                    drar.queue_draw();
+                }),
+            ),
+        }
+    }
+
+    /// Replace all of the glyph's contours with `contours`, for operations that rebuild the
+    /// outline. Undoing restores copies of the current contours.
+    fn replace_contours(&self, property: &'static str, contours: Vec<Contour>) -> crate::Action {
+        let old_contours = self
+            .glyph
+            .borrow()
+            .contours
+            .iter()
+            .map(Contour::duplicate)
+            .collect::<Vec<Contour>>();
+        let drar = self.drar.clone();
+        let set_contours = |contours: Vec<Contour>| -> Box<dyn FnMut()> {
+            Box::new(
+                clone!(@weak self.points as points, @weak self.points_map as points_map, @weak self.kd_tree as kd_tree, @weak self.glyph as glyph, @weak drar => move || {
+                    let mut glyph = glyph.borrow_mut();
+                    glyph.contours = contours.iter().map(Contour::duplicate).collect();
+                    let mut points = points.borrow_mut();
+                    let mut points_map = points_map.borrow_mut();
+                    let mut kd_tree = kd_tree.borrow_mut();
+                    points.clear();
+                    points_map.clear();
+                    *kd_tree = KdTree::new(&[]);
+                    for (contour_index, contour) in glyph.contours.iter().enumerate() {
+                        add_contour_points(
+                            &mut points,
+                            &mut points_map,
+                            &mut kd_tree,
+                            contour,
+                            contour_index,
+                        );
+                    }
+                    drar.queue_draw();
+                }),
+            )
+        };
+        crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property,
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: set_contours(contours),
+            undo: set_contours(old_contours),
+        }
+    }
+
+    /// Apply `operation` to the selected closed contours: the one drawn last is the second operand
+    /// and the others are the first. [`BooleanOperation::Union`] merges them all. Returns `None`
+    /// if there aren't enough contours selected.
+    fn boolean_operation(&self, operation: BooleanOperation) -> Option<crate::Action> {
+        let glyph = self.glyph.borrow();
+        let selected = self
+            .selected_contours()
+            .into_iter()
+            .filter(|&i| !*glyph.contours[i].open().borrow())
+            .collect::<Vec<usize>>();
+        let (b, a) = match selected.split_last() {
+            Some((_, [])) if operation != BooleanOperation::Union => return None,
+            Some((b, a)) => (*b, a),
+            None => return None,
+        };
+        let operands = |indices: &[usize]| {
+            indices
+                .iter()
+                .map(|&i| glyph.contours[i].clone())
+                .collect::<Vec<Contour>>()
+        };
+        let result = if operation == BooleanOperation::Union {
+            crate::glyphs::boolean_operation(&operands(&selected), &[], operation)
+        } else {
+            crate::glyphs::boolean_operation(&operands(a), &operands(&[b]), operation)
+        };
+        let mut contours = glyph
+            .contours
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !selected.contains(i))
+            .map(|(_, c)| c.duplicate())
+            .collect::<Vec<Contour>>();
+        contours.extend(result);
+        drop(glyph);
+        Some(self.replace_contours("boolean-operation", contours))
+    }
+
+    /// The selected on-curve points as `(contour index, point index)` pairs, numbered the way
+    /// [`Contour::delete_point`] and the other contour operations expect, without duplicates.
+    fn selected_on_curve_points(&self) -> Vec<(usize, usize)> {
+        let glyph = self.glyph.borrow();
+        let points = self.points.borrow();
+        let mut ret = self
+            .selection
+            .iter()
+            .filter_map(|&i| points.get(i))
+            .filter(|p| matches!(p.kind, Endpoint { .. }))
+            .filter_map(|p| {
+                let contour = glyph.contours.get(p.contour_index)?;
+                let curves = contour.curves().borrow();
+                let mut index = if p.point_index == 0 {
+                    p.curve_index
+                } else {
+                    p.curve_index + 1
+                };
+                /* A closed contour that ends where it started ends at its start point. */
+                if index == curves.len()
+                    && !*contour.open().borrow()
+                    && curves[0].points().borrow().first()
+                        == curves[index - 1].points().borrow().last()
+                {
+                    index = 0;
+                }
+                Some((p.contour_index, index))
+            })
+            .collect::<Vec<(usize, usize)>>();
+        ret.sort_unstable();
+        ret.dedup();
+        ret
+    }
+
+    /// Apply `edit` to copies of the glyph's contours and, if it reports a change, return the
+    /// action that replaces the contours with the edited ones. Contours left without curves are
+    /// removed.
+    fn edit_contours(
+        &self,
+        property: &'static str,
+        edit: impl FnOnce(&mut Vec<Contour>) -> bool,
+    ) -> Option<crate::Action> {
+        let mut contours = self
+            .glyph
+            .borrow()
+            .contours
+            .iter()
+            .map(Contour::duplicate)
+            .collect::<Vec<Contour>>();
+        if !edit(&mut contours) {
+            return None;
+        }
+        contours.retain(|c| !c.curves().borrow().is_empty());
+        Some(self.replace_contours(property, contours))
+    }
+
+    /// Add an on-curve point to curve `curve_index` of contour `contour_index` at `t`.
+    fn insert_point(
+        &self,
+        contour_index: usize,
+        curve_index: usize,
+        t: f64,
+    ) -> Option<crate::Action> {
+        self.edit_contours("insert-point", |contours| {
+            contours
+                .get(contour_index)
+                .map(|c| c.split_curve(curve_index, t))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Remove the selected on-curve points, refitting the curves around them.
+    fn delete_points(&self) -> Option<crate::Action> {
+        let selected = self.selected_on_curve_points();
+        self.edit_contours("delete-points", |contours| {
+            let mut changed = false;
+            /* Deleting a point only renumbers the points after it. */
+            for &(contour_index, index) in selected.iter().rev() {
+                changed |= contours[contour_index].delete_point(index);
+            }
+            changed
+        })
+    }
+
+    /// Reverse the direction of the contours that have a selected point.
+    fn reverse_contours(&self) -> Option<crate::Action> {
+        let selected = self.selected_contours();
+        if selected.is_empty() {
+            return None;
+        }
+        self.edit_contours("reverse-contours", |contours| {
+            for &i in &selected {
+                contours[i].reverse();
+            }
+            true
+        })
+    }
+
+    /// Make the first selected on-curve point of each closed contour its start point.
+    fn set_start_points(&self) -> Option<crate::Action> {
+        let selected = self.selected_on_curve_points();
+        self.edit_contours("set-start-point", |contours| {
+            let mut changed = false;
+            let mut done = vec![];
+            for (contour_index, index) in selected {
+                if !done.contains(&contour_index) {
+                    done.push(contour_index);
+                    changed |= contours[contour_index].set_start_point(index);
+                }
+            }
+            changed
+        })
+    }
+
+    /// Open each closed contour at its first selected on-curve point.
+    fn break_contours(&self) -> Option<crate::Action> {
+        let selected = self.selected_on_curve_points();
+        self.edit_contours("break-contour", |contours| {
+            let mut changed = false;
+            let mut done = vec![];
+            for (contour_index, index) in selected {
+                if !done.contains(&contour_index) {
+                    done.push(contour_index);
+                    changed |= contours[contour_index].break_at(index);
+                }
+            }
+            changed
+        })
+    }
+
+    /// Join the two selected end points of open contours. If they are the two ends of the same
+    /// contour, it is closed.
+    fn join_end_points(&self) -> Option<crate::Action> {
+        let selected = self.selected_on_curve_points();
+        let glyph = self.glyph.borrow();
+        /* Each end as its contour index and whether it is the contour's start. */
+        let ends = selected
+            .into_iter()
+            .filter_map(|(contour_index, index)| {
+                let contour = &glyph.contours[contour_index];
+                if !*contour.open().borrow() {
+                    return None;
+                }
+                if index == 0 {
+                    Some((contour_index, true))
+                } else if index == contour.curves().borrow().len() {
+                    Some((contour_index, false))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<(usize, bool)>>();
+        let (first, second) = match ends.as_slice() {
+            [first, second] => (*first, *second),
+            _ => return None,
+        };
+        drop(glyph);
+        self.edit_contours("join-contours", |contours| {
+            let joined = join_contours(&contours[first.0], first.1, &contours[second.0], second.1);
+            contours[first.0] = joined;
+            if second.0 != first.0 {
+                contours.remove(second.0);
+            }
+            true
+        })
+    }
+
     fn new_guideline(&self, angle: f64, (x, y): (i64, i64)) -> crate::Action {
         let drar = self.drar.clone();
         crate::Action {
@@ -312,6 +607,32 @@ impl GlyphState {
         }
     }
 
+    fn update_anchor(&self, idx: usize, (x, y): (i64, i64)) -> crate::Action {
+        let (anchor, old_position) = {
+            let g = self.glyph.borrow();
+            let anchor = g.anchors[idx].clone();
+            let old_position = (anchor.x(), anchor.y());
+            (anchor, old_position)
+        };
+        let redo_anchor = anchor.clone();
+        crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "anchor",
+                id: unsafe { std::mem::transmute::<&[usize], &[u8]>(&[idx]).into() },
+            },
+            compress: true,
+            redo: Box::new(move || {
+                redo_anchor.set_property("x", x as f64);
+                redo_anchor.set_property("y", y as f64);
+            }),
+            undo: Box::new(move || {
+                anchor.set_property("x", old_position.0);
+                anchor.set_property("y", old_position.1);
+            }),
+        }
+    }
+
     fn delete_guideline(&self, idx: usize) -> crate::Action {
         let drar = self.drar.clone();
         let json: serde_json::Value =
@@ -422,6 +743,8 @@ pub struct GlyphEditArea {
     app: OnceCell<gtk::Application>,
     glyph: OnceCell<Rc<RefCell<Glyph>>>,
     glyph_state: OnceCell<RefCell<GlyphState>>,
+    /// Name of the layer being edited.
+    layer: RefCell<String>,
     drawing_area: OnceCell<gtk::DrawingArea>,
     hovering: Cell<Option<(usize, usize)>>,
     statusbar_context_id: Cell<Option<u32>>,
@@ -478,7 +801,7 @@ impl ObjectImpl for GlyphEditArea {
                 let zoom_factor = obj.imp().zoom.get();
                 let camera = obj.imp().camera.get();
                 let event_position = event.position();
-                let units_per_em = *obj.imp().project.get().unwrap().imp().units_per_em.borrow();
+                let units_per_em = obj.imp().project.get().unwrap().fontinfo().units_per_em();
                 let f = units_per_em / EM_SQUARE_PIXELS;
                 let position = (((event_position.0 * f - camera.0 * f * zoom_factor) / zoom_factor) as i64, (units_per_em - ((event_position.1 * f - camera.1 * f * zoom_factor) / zoom_factor)) as i64);
                 obj.imp().transformed_mouse.set(position);
@@ -493,12 +816,8 @@ impl ObjectImpl for GlyphEditArea {
                             } else {
                                 0.
                             };
-                            let mut action = glyph_state.new_guideline(angle, position);
-                            (action.redo)();
-                            let app: &crate::Application =
-                                crate::Application::from_instance(&obj.imp().app.get().unwrap().downcast_ref::<crate::GerbApp>().unwrap());
-                            let undo_db = app.undo_db.borrow_mut();
-                            undo_db.event(action);
+                            let action = glyph_state.new_guideline(angle, position);
+                            glyph_state.add_undo_action(action);
                         }
 
                         if glyph_state.tool.is_manipulate() {
@@ -506,9 +825,18 @@ impl ObjectImpl for GlyphEditArea {
                             let GlyphState {
                                 ref mut tool,
                                 ref glyph,
+                                ref anchors_kd_tree,
                                 ..
                             } = *glyph_state;
+                            if let Some(&(i, _)) = anchors_kd_tree.borrow().query(position, 10).first() {
+                                obj.imp().select_object(Some(glyph.borrow().anchors[i].clone().upcast::<gtk::glib::Object>()));
+                                *tool = Tool::Manipulate { mode: ControlPointMode::DragAnchor(i) };
+                                is_guideline = true;
+                            }
                             for (i, g) in glyph.borrow().guidelines.iter().enumerate() {
+                                if is_guideline {
+                                    break;
+                                }
                                 if g.imp().on_line_query(position, None) {
                                     obj.imp().select_object(Some(g.clone().upcast::<gtk::glib::Object>()));
                                     *tool = Tool::Manipulate { mode: ControlPointMode::DragGuideline(i) };
@@ -529,6 +857,7 @@ impl ObjectImpl for GlyphEditArea {
                                 let contour_index = glyph_state.glyph.borrow().contours.len();
                                 glyph_state.add_contour(&new_contour, contour_index);
                                 glyph_state.glyph.borrow_mut().contours.push(new_contour);
+                                glyph_state.set_modified();
                             }
                         }
                     },
@@ -536,36 +865,41 @@ impl ObjectImpl for GlyphEditArea {
                         obj.imp().glyph_state.get().unwrap().borrow_mut().tool = Tool::Panning;
                     },
                     gtk::gdk::BUTTON_SECONDARY => {
-                        let glyph_state = obj.imp().glyph_state.get().unwrap().borrow();
-                        if glyph_state.tool.is_manipulate() {
-                            let glyph = glyph_state.glyph.borrow_mut();
-                            for (i, g) in glyph.guidelines.iter().enumerate() {
-                                if g.imp().on_line_query(position, None) {
-                                    let menu = gtk::Menu::builder().attach_widget(_self).take_focus(true).visible(true).build();
-                                    let name = gtk::MenuItem::builder().label(&format!("{} - {}", g.name().as_ref().map(String::as_str).unwrap_or("Anonymous guideline"), g.identifier().as_ref().map(String::as_str).unwrap_or("No identifier"))).sensitive(false).visible(true).build();
-                                    menu.append(&name);
-                                    menu.append(&gtk::SeparatorMenuItem::builder().visible(true).build());
-                                    let delete = gtk::MenuItem::builder().label("Delete").sensitive(true).visible(true).build();
-                                    drop(glyph);
-                                    drop(glyph_state);
-                                    delete.connect_activate(clone!(@weak obj, @weak _self as drar => move |_del_self| {
-                                        let glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
-                                        if glyph_state.glyph.borrow().guidelines.get(i).is_some() { // Prevent panic if `i` out of bounds
-                                            let mut action = glyph_state.delete_guideline(i);
-                                            (action.redo)();
-                                            let app: &crate::Application =
-                                                crate::Application::from_instance(&obj.imp().app.get().unwrap().downcast_ref::<crate::GerbApp>().unwrap());
-                                            let undo_db = app.undo_db.borrow_mut();
-                                            undo_db.event(action);
-                                            drar.queue_draw();
-                                        }
-                                    }));
-                                    menu.append(&delete);
-                                    menu.show_all();
-                                    menu.popup_easy(event.button(), event.time());
-                                    break;
+                        let (is_manipulate, on_guideline) = {
+                            let glyph_state = obj.imp().glyph_state.get().unwrap().borrow();
+                            let is_manipulate = glyph_state.tool.is_manipulate();
+                            let mut on_guideline = false;
+                            if is_manipulate {
+                                let glyph = glyph_state.glyph.borrow_mut();
+                                for (i, g) in glyph.guidelines.iter().enumerate() {
+                                    if g.imp().on_line_query(position, None) {
+                                        let menu = gtk::Menu::builder().attach_widget(_self).take_focus(true).visible(true).build();
+                                        let name = gtk::MenuItem::builder().label(&format!("{} - {}", g.name().as_ref().map(String::as_str).unwrap_or("Anonymous guideline"), g.identifier().as_ref().map(String::as_str).unwrap_or("No identifier"))).sensitive(false).visible(true).build();
+                                        menu.append(&name);
+                                        menu.append(&gtk::SeparatorMenuItem::builder().visible(true).build());
+                                        let delete = gtk::MenuItem::builder().label("Delete").sensitive(true).visible(true).build();
+                                        drop(glyph);
+                                        drop(glyph_state);
+                                        delete.connect_activate(clone!(@weak obj, @weak _self as drar => move |_del_self| {
+                                            let glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+                                            if glyph_state.glyph.borrow().guidelines.get(i).is_some() { // Prevent panic if `i` out of bounds
+                                                let action = glyph_state.delete_guideline(i);
+                                                glyph_state.add_undo_action(action);
+                                                drar.queue_draw();
+                                            }
+                                        }));
+                                        menu.append(&delete);
+                                        menu.show_all();
+                                        menu.popup_easy(event.button(), event.time());
+                                        on_guideline = true;
+                                        break;
+                                    }
                                 }
                             }
+                            (is_manipulate, on_guideline)
+                        };
+                        if is_manipulate && !on_guideline {
+                            obj.imp().contour_menu(&obj, event, position);
                         }
                         return Inhibit(true);
                     }
@@ -589,7 +923,7 @@ impl ObjectImpl for GlyphEditArea {
                         let zoom_factor = obj.imp().zoom.get();
                         let camera = obj.imp().camera.get();
                         let position = event.position();
-                        let units_per_em = *obj.imp().project.get().unwrap().imp().units_per_em.borrow();
+                        let units_per_em = obj.imp().project.get().unwrap().fontinfo().units_per_em();
                         let f = units_per_em / EM_SQUARE_PIXELS;
                         let position = (((position.0*f - camera.0*f * zoom_factor)/zoom_factor) as i64, (units_per_em - ((position.1*f-camera.1*f * zoom_factor)/zoom_factor)) as i64);
                         obj.imp().transformed_mouse.set(position);
@@ -600,6 +934,7 @@ impl ObjectImpl for GlyphEditArea {
                             let contour_index = glyph_state.glyph.borrow().contours.len();
                             glyph_state.add_contour(&new_contour, contour_index);
                             glyph_state.glyph.borrow_mut().contours.push(new_contour);
+                            glyph_state.set_modified();
                         }
                     }
                     Tool::BezierPen { ref mut state } if event.button() == gtk::gdk::BUTTON_SECONDARY => {
@@ -609,6 +944,7 @@ impl ObjectImpl for GlyphEditArea {
                         let contour_index = glyph_state.glyph.borrow().contours.len();
                         glyph_state.add_contour(&new_contour, contour_index);
                         glyph_state.glyph.borrow_mut().contours.push(new_contour);
+                        glyph_state.set_modified();
                     }
                     Tool::BezierPen { .. } => {},
                 }
@@ -641,23 +977,22 @@ impl ObjectImpl for GlyphEditArea {
                     let zoom_factor = obj.imp().zoom.get();
                     let camera = obj.imp().camera.get();
                     let event_position = event.position();
-                    let units_per_em = *obj.imp().project.get().unwrap().imp().units_per_em.borrow();
+                    let units_per_em = obj.imp().project.get().unwrap().fontinfo().units_per_em();
                     let f = units_per_em / EM_SQUARE_PIXELS;
                     let position = (((event_position.0 * f - camera.0 * f * zoom_factor) / zoom_factor) as i64, (units_per_em - ((event_position.1 * f - camera.1 * f * zoom_factor) / zoom_factor)) as i64);
                     obj.imp().transformed_mouse.set(position);
                     if let Tool::Manipulate { mode: ControlPointMode::Drag } = glyph_state.tool {
                         glyph_state.update_positions(position);
                     } else if let Tool::Manipulate { mode: ControlPointMode::DragGuideline(idx) } = glyph_state.tool {
-                        let mut action = glyph_state.update_guideline(idx, position);
-                        (action.redo)();
-                        let app: &crate::Application =
-                            crate::Application::from_instance(&obj.imp().app.get().unwrap().downcast_ref::<crate::GerbApp>().unwrap());
-                        let undo_db = app.undo_db.borrow_mut();
-                        undo_db.event(action);
+                        let action = glyph_state.update_guideline(idx, position);
+                        glyph_state.add_undo_action(action);
+                    } else if let Tool::Manipulate { mode: ControlPointMode::DragAnchor(idx) } = glyph_state.tool {
+                        let action = glyph_state.update_anchor(idx, position);
+                        glyph_state.add_undo_action(action);
                     }
 
                     let pts = glyph_state.kd_tree.borrow().query(position, 10);
-                    if pts.is_empty() {
+                    if pts.is_empty() && glyph_state.anchors_kd_tree.borrow().query(position, 10).is_empty() {
                         obj.imp().hovering.set(None);
                         if let Some(screen) = _self.window() {
                             let display = screen.display();
@@ -704,25 +1039,26 @@ impl ObjectImpl for GlyphEditArea {
         );
 
         drawing_area.connect_draw(clone!(@weak obj => @default-return Inhibit(false), move |drar: &gtk::DrawingArea, cr: &gtk::cairo::Context| {
-            let (show_grid, show_guidelines, show_handles, inner_fill) = {
+            let (show_grid, show_guidelines, show_handles, show_anchors, inner_fill) = {
                 let viewhide = obj.imp().viewhidebox.get().unwrap();
                 let show_grid = viewhide.property::<bool>("show-grid");
                 let show_guidelines = viewhide.property::<bool>("show-guidelines");
                 let show_handles = viewhide.property::<bool>("show-handles");
+                let show_anchors = viewhide.property::<bool>("show-anchors");
                 let inner_fill = viewhide.property::<bool>("inner-fill");
-                (show_grid, show_guidelines, show_handles, inner_fill)
+                (show_grid, show_guidelines, show_handles, show_anchors, inner_fill)
             };
             let app: &crate::GerbApp =
                 obj.imp().app.get().unwrap().downcast_ref::<crate::GerbApp>().unwrap();
             let settings = app.imp().settings.clone();
             let width = drar.allocated_width() as f64;
             let height = drar.allocated_height() as f64;
-            let project = obj.imp().project.get().unwrap().imp();
-            let units_per_em = *project.units_per_em.borrow();
-            let x_height = *project.x_height.borrow();
-            let cap_height = *project.cap_height.borrow();
-            let _ascender = *project.ascender.borrow();
-            let _descender = *project.descender.borrow();
+            let fontinfo = obj.imp().project.get().unwrap().fontinfo();
+            let units_per_em = fontinfo.units_per_em();
+            let x_height = fontinfo.x_height();
+            let cap_height = fontinfo.cap_height();
+            let _ascender = fontinfo.ascender();
+            let _descender = fontinfo.descender();
             let f = EM_SQUARE_PIXELS / units_per_em;
             let glyph_state = obj.imp().glyph_state.get().unwrap().borrow();
             let glyph_width = f * glyph_state.glyph.borrow().width.unwrap_or(units_per_em);
@@ -817,6 +1153,20 @@ impl ObjectImpl for GlyphEditArea {
                 units_per_em,
                 line_width: glyph_line_width,
             };
+            /* Draw the glyph's other layers dimmed underneath */
+            let project = obj.imp().project.get().unwrap();
+            let glyph_name = obj.imp().glyph.get().unwrap().borrow().name.to_string();
+            let layer = obj.imp().layer.borrow().clone();
+            for other in project.layer_names().into_iter().filter(|l| *l != layer) {
+                if let Some(g) = project.layer_glyph(&other, &glyph_name) {
+                    g.borrow().draw(cr, GlyphDrawingOptions {
+                        outline: (0.2, 0.2, 0.2, 0.3),
+                        inner_fill: Some((0., 0., 0., 0.1)),
+                        highlight: None,
+                        ..options
+                    });
+                }
+            }
             glyph_state.glyph.borrow().draw(cr, options);
 
             if let Tool::BezierPen { ref state } = glyph_state.tool {
@@ -850,6 +1200,27 @@ impl ObjectImpl for GlyphEditArea {
                         }
                     }
                 }
+                /* Point an arrow from each contour's start point in the direction it runs. */
+                let size = handle_size / f;
+                for contour in glyph_state.glyph.borrow().contours.iter() {
+                    let points = match contour.curves().borrow().first() {
+                        Some(curve) if curve.points().borrow().len() > 1 => curve.float_points(),
+                        _ => continue,
+                    };
+                    let (dx, dy) = crate::utils::curves::tangent(&points, 0.);
+                    let length = dx.hypot(dy);
+                    if length == 0. {
+                        continue;
+                    }
+                    let (dx, dy) = (dx / length, dy / length);
+                    let (x, y) = points[0];
+                    let (base_x, base_y) = (x + dx * size, y + dy * size);
+                    cr.move_to(x + dx * 2.5 * size, y + dy * 2.5 * size);
+                    cr.line_to(base_x - dy * 0.6 * size, base_y + dx * 0.6 * size);
+                    cr.line_to(base_x + dy * 0.6 * size, base_y - dx * 0.6 * size);
+                    cr.close_path();
+                    cr.fill().unwrap();
+                }
             }
 
             cr.restore().unwrap();
@@ -895,6 +1266,19 @@ impl ObjectImpl for GlyphEditArea {
                 }
             }
 
+            if show_anchors {
+                let mut matrix = gtk::cairo::Matrix::identity();
+                matrix.scale(zoom_factor, zoom_factor);
+                matrix.translate(camera.0, camera.1);
+                matrix.scale(EM_SQUARE_PIXELS / units_per_em, EM_SQUARE_PIXELS / units_per_em);
+                matrix.translate(0., units_per_em.abs());
+                matrix.scale(1.0, -1.0);
+                let hovered = glyph_state.anchors_kd_tree.borrow().query(obj.imp().transformed_mouse.get(), 10);
+                for (i, a) in glyph_state.glyph.borrow().anchors.iter().enumerate() {
+                    a.imp().draw(cr, matrix, hovered.iter().any(|(j, _)| *j == i));
+                }
+            }
+
             /* Draw rulers */
             cr.rectangle(0., 0., width, RULER_BREADTH);
             cr.set_source_rgb(1., 1., 1.);
@@ -1163,6 +1547,56 @@ impl ObjectImpl for GlyphEditArea {
             window.add(&hbox);
             window.show_all();
         }));
+        let import_button = gtk::ToolButton::new(gtk::ToolButton::NONE, Some("Import SVG..."));
+        import_button.set_visible(true);
+        import_button.set_tooltip_text(Some("Add the paths of an SVG file to the glyph"));
+        import_button.connect_clicked(clone!(@weak obj => move |_| {
+            let window = obj.toplevel().and_then(|w| w.downcast::<gtk::Window>().ok());
+            let dialog = gtk::FileChooserNative::new(
+                Some("Import SVG paths from..."),
+                window.as_ref(),
+                gtk::FileChooserAction::Open,
+                None,
+                None,
+            );
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some("SVG images"));
+            filter.add_mime_type("image/svg+xml");
+            filter.add_pattern("*.svg");
+            dialog.add_filter(&filter);
+            dialog.run();
+            dialog.hide();
+            let path = match dialog.filename() {
+                Some(p) => p,
+                None => return,
+            };
+            let imp = obj.imp();
+            let result = std::fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {}: {}", path.display(), err))
+                .and_then(|svg| {
+                    crate::import::svg_to_contours(&svg, &imp.project.get().unwrap().fontinfo())
+                });
+            match result {
+                Ok(contours) => {
+                    let glyph_state = imp.glyph_state.get().unwrap().borrow();
+                    let action = glyph_state.import_contours(contours);
+                    glyph_state.add_undo_action(action);
+                }
+                Err(err) => {
+                    let dialog = gtk::MessageDialog::new(
+                        window.as_ref(),
+                        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+                        gtk::MessageType::Error,
+                        gtk::ButtonsType::Close,
+                        &crate::utils::xml_escape(&err),
+                    );
+                    dialog.set_title("Error: Could not import SVG");
+                    dialog.set_use_markup(true);
+                    dialog.run();
+                    dialog.hide();
+                }
+            }
+        }));
         toolbar.add(&panning_button);
         toolbar.set_item_homogeneous(&panning_button, false);
         toolbar.add(&manipulate_button);
@@ -1177,6 +1611,105 @@ impl ObjectImpl for GlyphEditArea {
         toolbar.set_item_homogeneous(&zoom_out_button, false);
         toolbar_box.pack_start(&toolbar, false, false, 0);
         toolbar_box.pack_start(&zoom_percent_label, false, false, 0);
+        let path_menu = gtk::Menu::new();
+        for (label, operation) in [
+            ("Union", BooleanOperation::Union),
+            ("Subtract", BooleanOperation::Subtract),
+            ("Intersect", BooleanOperation::Intersect),
+            ("Exclude", BooleanOperation::Exclude),
+        ] {
+            let item = gtk::MenuItem::builder().label(label).visible(true).build();
+            item.connect_activate(clone!(@weak obj => move |_| {
+                let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+                if let Some(action) = glyph_state.boolean_operation(operation) {
+                    glyph_state.add_undo_action(action);
+                    glyph_state.selection.clear();
+                }
+            }));
+            path_menu.append(&item);
+        }
+        path_menu.append(&gtk::SeparatorMenuItem::builder().visible(true).build());
+        let remove_overlaps = gtk::MenuItem::builder()
+            .label("Remove overlaps")
+            .visible(true)
+            .build();
+        remove_overlaps.connect_activate(clone!(@weak obj => move |_| {
+            let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+            let contours = crate::glyphs::remove_overlaps(&glyph_state.glyph.borrow().contours);
+            let action = glyph_state.replace_contours("remove-overlaps", contours);
+            glyph_state.add_undo_action(action);
+            glyph_state.selection.clear();
+        }));
+        path_menu.append(&remove_overlaps);
+        let correct_directions = gtk::MenuItem::builder()
+            .label("Correct path direction")
+            .visible(true)
+            .build();
+        correct_directions.connect_activate(clone!(@weak obj => move |_| {
+            let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+            let contours = glyph_state
+                .glyph
+                .borrow()
+                .contours
+                .iter()
+                .map(Contour::duplicate)
+                .collect::<Vec<Contour>>();
+            if crate::glyphs::correct_directions(&contours) {
+                let action = glyph_state.replace_contours("correct-directions", contours);
+                glyph_state.add_undo_action(action);
+                glyph_state.selection.clear();
+            }
+        }));
+        path_menu.append(&correct_directions);
+        let add_extrema = gtk::MenuItem::builder()
+            .label("Add points at extrema")
+            .visible(true)
+            .build();
+        add_extrema.connect_activate(clone!(@weak obj => move |_| {
+            let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+            let contours = glyph_state
+                .glyph
+                .borrow()
+                .contours
+                .iter()
+                .map(Contour::duplicate)
+                .collect::<Vec<Contour>>();
+            if crate::glyphs::add_extrema(&contours) {
+                let action = glyph_state.replace_contours("add-extrema", contours);
+                glyph_state.add_undo_action(action);
+                glyph_state.selection.clear();
+            }
+        }));
+        path_menu.append(&add_extrema);
+        path_menu.append(&gtk::SeparatorMenuItem::builder().visible(true).build());
+        for (label, operation) in [
+            (
+                "Reverse contours",
+                GlyphState::reverse_contours as fn(&GlyphState) -> Option<crate::Action>,
+            ),
+            ("Join end points", GlyphState::join_end_points),
+            ("Break contours", GlyphState::break_contours),
+            ("Set start points", GlyphState::set_start_points),
+            ("Delete points", GlyphState::delete_points),
+        ] {
+            let item = gtk::MenuItem::builder().label(label).visible(true).build();
+            item.connect_activate(clone!(@weak obj => move |_| {
+                let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+                if let Some(action) = operation(&glyph_state) {
+                    glyph_state.add_undo_action(action);
+                    glyph_state.selection.clear();
+                }
+            }));
+            path_menu.append(&item);
+        }
+        let path_button = gtk::MenuButton::builder()
+            .label("Path")
+            .tooltip_text("Operations on the outline: combine the selected contours, remove overlaps, correct directions, add points at extrema, and edit the contours of the selected points.")
+            .popup(&path_menu)
+            .visible(true)
+            .build();
+        toolbar_box.pack_start(&import_button, false, false, 0);
+        toolbar_box.pack_start(&path_button, false, false, 0);
         toolbar_box.pack_start(&debug_button, false, false, 0);
         toolbar_box.style_context().add_class("glyph-edit-toolbox");
         let viewhidebox = viewhide::ViewHideBox::new();
@@ -1309,6 +1842,174 @@ impl GlyphEditArea {
         }
     }
 
+    /// If the project has more than one layer, add a combo box to the toolbar that switches the
+    /// layer being edited and a menu to copy contours to the other layers.
+    fn add_layer_switcher(&self, obj: &GlyphEditView) {
+        let project = self.project.get().unwrap();
+        let layer_names = project.layer_names();
+        if layer_names.len() < 2 {
+            return;
+        }
+        let toolbar_box = self.toolbar_box.get().unwrap();
+        let layer_combo = gtk::ComboBoxText::builder()
+            .visible(true)
+            .tooltip_text("Layer")
+            .build();
+        for name in &layer_names {
+            layer_combo.append(Some(name), name);
+        }
+        layer_combo.set_active_id(Some(&self.layer.borrow()));
+        layer_combo.connect_changed(clone!(@weak obj => move |combo| {
+            if let Some(layer) = combo.active_id() {
+                obj.imp().set_layer(layer.as_str());
+            }
+        }));
+        let copy_menu = gtk::Menu::new();
+        for name in layer_names {
+            let item = gtk::MenuItem::builder()
+                .label(&format!("Copy contours to {}", name))
+                .visible(true)
+                .build();
+            item.connect_activate(clone!(@weak obj => move |_| {
+                let imp = obj.imp();
+                let glyph_name = imp.glyph.get().unwrap().borrow().name.to_string();
+                if let Some(target) = imp.project.get().unwrap().layer_glyph_or_insert(&name, &glyph_name) {
+                    let glyph_state = imp.glyph_state.get().unwrap().borrow();
+                    if Rc::ptr_eq(&target, &glyph_state.glyph) {
+                        return;
+                    }
+                    let action = glyph_state.copy_contours_to(target);
+                    glyph_state.register_action(action);
+                    *imp.project.get().unwrap().imp().modified.borrow_mut() = true;
+                }
+            }));
+            copy_menu.append(&item);
+        }
+        let copy_button = gtk::MenuButton::builder()
+            .label("Copy to layer")
+            .tooltip_text("Copy the selected contours, or all contours, to another layer")
+            .popup(&copy_menu)
+            .visible(true)
+            .build();
+        toolbar_box.pack_start(&layer_combo, false, false, 0);
+        toolbar_box.pack_start(&copy_button, false, false, 0);
+    }
+
+    /// Edit the glyph's version in `layer`, creating an empty one if it doesn't exist yet.
+    fn set_layer(&self, layer: &str) {
+        let project = self.project.get().unwrap();
+        let glyph_name = self.glyph.get().unwrap().borrow().name.to_string();
+        let glyph = match project.layer_glyph_or_insert(layer, &glyph_name) {
+            Some(g) => g,
+            None => return,
+        };
+        *self.layer.borrow_mut() = layer.to_string();
+        *self.glyph_state.get().unwrap().borrow_mut() = GlyphState::new(
+            &glyph,
+            self.app.get().unwrap().clone(),
+            project.clone(),
+            self.drawing_area.get().unwrap().clone(),
+        );
+        self.hovering.set(None);
+        self.select_object(None);
+        self.show_lib();
+        self.drawing_area.get().unwrap().queue_draw();
+    }
+
+    fn show_lib(&self) {
+        if let Some(app) = self
+            .app
+            .get()
+            .and_then(|app| app.downcast_ref::<crate::GerbApp>())
+        {
+            let glyph = self.glyph_state.get().unwrap().borrow().glyph.clone();
+            let project = self.project.get().unwrap().clone();
+            let title = format!("Glyph lib: {}", glyph.borrow().name);
+            let lib = glyph.borrow().lib.clone();
+            app.tabinfo().set_lib(&title, &lib, move |lib| {
+                let mut glyph = glyph.borrow_mut();
+                glyph.lib = lib;
+                glyph.modified = true;
+                *project.imp().modified.borrow_mut() = true;
+            });
+        }
+    }
+
+    /// Pop up a menu of the operations on the on-curve point or the curve at `position`, if there
+    /// is one there. Right clicking an unselected point selects it first.
+    fn contour_menu(
+        &self,
+        obj: &GlyphEditView,
+        event: &gtk::gdk::EventButton,
+        position: (i64, i64),
+    ) {
+        let mut glyph_state = self.glyph_state.get().unwrap().borrow_mut();
+        let on_curve = glyph_state
+            .kd_tree
+            .borrow()
+            .query(position, 10)
+            .into_iter()
+            .filter(|&(i, _)| matches!(glyph_state.points.borrow()[i].kind, Endpoint { .. }))
+            .collect::<Vec<_>>();
+        let menu = gtk::Menu::builder()
+            .attach_widget(self.drawing_area.get().unwrap())
+            .take_focus(true)
+            .visible(true)
+            .build();
+        let add_item = |label: &str, operation: fn(&GlyphState) -> Option<crate::Action>| {
+            let item = gtk::MenuItem::builder().label(label).visible(true).build();
+            item.connect_activate(clone!(@weak obj => move |_| {
+                let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+                if let Some(action) = operation(&glyph_state) {
+                    glyph_state.add_undo_action(action);
+                    glyph_state.selection.clear();
+                }
+            }));
+            menu.append(&item);
+        };
+        if !on_curve.is_empty() {
+            if !on_curve
+                .iter()
+                .any(|(i, _)| glyph_state.selection.contains(i))
+            {
+                glyph_state.set_selection(&on_curve[..1]);
+            }
+            add_item("Delete point", GlyphState::delete_points);
+            add_item("Set as start point", GlyphState::set_start_points);
+            add_item("Break contour here", GlyphState::break_contours);
+            add_item("Join end points", GlyphState::join_end_points);
+            add_item("Reverse contour", GlyphState::reverse_contours);
+        } else {
+            let glyph = glyph_state.glyph.borrow();
+            let hit = glyph.contours.iter().enumerate().find_map(|(ic, contour)| {
+                contour
+                    .curves()
+                    .borrow()
+                    .iter()
+                    .position(|curve| curve.on_curve_query(position, None))
+                    .map(|jc| (ic, jc, contour.curves().borrow()[jc].nearest(position)))
+            });
+            let (contour_index, curve_index, t) = match hit {
+                Some(hit) => hit,
+                None => return,
+            };
+            let item = gtk::MenuItem::builder()
+                .label("Insert point here")
+                .visible(true)
+                .build();
+            item.connect_activate(clone!(@weak obj => move |_| {
+                let mut glyph_state = obj.imp().glyph_state.get().unwrap().borrow_mut();
+                if let Some(action) = glyph_state.insert_point(contour_index, curve_index, t) {
+                    glyph_state.add_undo_action(action);
+                    glyph_state.selection.clear();
+                }
+            }));
+            menu.append(&item);
+        }
+        self.drawing_area.get().unwrap().queue_draw();
+        menu.popup_easy(event.button(), event.time());
+    }
+
     fn select_object(&self, new_obj: Option<glib::Object>) {
         if let Some(app) = self
             .app
@@ -1334,12 +2035,146 @@ impl GlyphEditView {
             .set(RefCell::new(GlyphState::new(
                 &glyph,
                 app.clone(),
+                project.clone(),
                 ret.imp().drawing_area.get().unwrap().clone(),
             )))
             .expect("Failed to create glyph state");
+        *ret.imp().layer.borrow_mut() = project.imp().default_layer_name.borrow().clone();
         ret.imp().glyph.set(glyph).unwrap();
         ret.imp().app.set(app).unwrap();
         ret.imp().project.set(project).unwrap();
+        ret.imp().add_layer_switcher(&ret);
         ret
     }
+
+    /// Show the `<lib>` of the glyph being edited in the sidebar.
+    pub fn show_lib(&self) {
+        self.imp().show_lib();
+    }
+}
+
+/// Add the control points of `contour`, the glyph's `contour_index`-th contour, to the point
+/// lookup structures of a [`GlyphState`].
+fn add_contour_points(
+    points: &mut Vec<ControlPoint>,
+    points_map: &mut HashMap<(i64, i64), Vec<usize>>,
+    kd_tree: &mut KdTree,
+    contour: &Contour,
+    contour_index: usize,
+) {
+    let prev_len = points.len();
+    for (curve_index, curve) in contour.curves().borrow().iter().enumerate() {
+        match curve.points().borrow().len() {
+            4 => {
+                for (endpoint, handle) in [(0, 1), (3, 2)] {
+                    let mut point_index = points.len();
+                    points.push(ControlPoint {
+                        contour_index,
+                        curve_index,
+                        point_index: endpoint,
+                        position: curve.points().borrow()[endpoint],
+                        kind: Endpoint {
+                            handle: Some(point_index + 1),
+                        },
+                    });
+                    points_map
+                        .entry(curve.points().borrow()[endpoint])
+                        .or_default()
+                        .push(point_index);
+                    let endpoint_index = point_index;
+                    point_index += 1;
+                    points.push(ControlPoint {
+                        contour_index,
+                        curve_index,
+                        point_index: handle,
+                        position: curve.points().borrow()[handle],
+                        kind: Handle {
+                            end_points: vec![endpoint_index],
+                        },
+                    });
+                    points_map
+                        .entry(curve.points().borrow()[handle])
+                        .or_default()
+                        .push(point_index);
+                }
+            }
+            3 => {
+                let mut point_index = points.len();
+                points.push(ControlPoint {
+                    contour_index,
+                    curve_index,
+                    point_index: 0,
+                    position: curve.points().borrow()[0],
+                    kind: Endpoint {
+                        handle: Some(point_index + 1),
+                    },
+                });
+                points_map
+                    .entry(curve.points().borrow()[0])
+                    .or_default()
+                    .push(point_index);
+                point_index += 1;
+                points.push(ControlPoint {
+                    contour_index,
+                    curve_index,
+                    point_index: 1,
+                    position: curve.points().borrow()[1],
+                    kind: Handle {
+                        end_points: vec![point_index - 1, point_index + 1],
+                    },
+                });
+                points_map
+                    .entry(curve.points().borrow()[1])
+                    .or_default()
+                    .push(point_index);
+                point_index += 1;
+                points.push(ControlPoint {
+                    contour_index,
+                    curve_index,
+                    point_index: 2,
+                    position: curve.points().borrow()[2],
+                    kind: Endpoint {
+                        handle: Some(point_index - 1),
+                    },
+                });
+                points_map
+                    .entry(curve.points().borrow()[2])
+                    .or_default()
+                    .push(point_index);
+            }
+            2 => {
+                let mut point_index = points.len();
+                for endpoint in 0..=1 {
+                    points.push(ControlPoint {
+                        contour_index,
+                        curve_index,
+                        point_index: endpoint,
+                        position: curve.points().borrow()[endpoint],
+                        kind: Endpoint { handle: None },
+                    });
+                    points_map
+                        .entry(curve.points().borrow()[endpoint])
+                        .or_default()
+                        .push(point_index);
+                    point_index += 1;
+                }
+            }
+            1 => {}
+            0 => {}
+            _ => unreachable!(), //FIXME
+        }
+    }
+    for i in prev_len..points.len() {
+        let pos = points[i].position;
+        kd_tree.add(pos, i);
+    }
+}
+
+/// Build a [`KdTree`] of `glyph`'s anchor positions, identified by their index.
+fn anchors_kd_tree(glyph: &Glyph) -> KdTree {
+    let mut ret = KdTree::new(&[]);
+    for (i, a) in glyph.anchors.iter().enumerate() {
+        ret.add(a.imp().position(), i);
+    }
+    ret
 }
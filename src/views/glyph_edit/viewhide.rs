@@ -31,10 +31,12 @@ pub struct ViewHideBoxInner {
     show_grid_btn: OnceCell<gtk::CheckButton>,
     show_guidelines_btn: OnceCell<gtk::CheckButton>,
     show_handles_btn: OnceCell<gtk::CheckButton>,
+    show_anchors_btn: OnceCell<gtk::CheckButton>,
     inner_fill_btn: OnceCell<gtk::CheckButton>,
     show_grid: Cell<bool>,
     show_guidelines: Cell<bool>,
     show_handles: Cell<bool>,
+    show_anchors: Cell<bool>,
     inner_fill: Cell<bool>,
 }
 
@@ -51,6 +53,7 @@ impl ObjectImpl for ViewHideBoxInner {
         self.show_grid.set(true);
         self.show_guidelines.set(true);
         self.show_handles.set(true);
+        self.show_anchors.set(true);
         self.inner_fill.set(false);
         //obj.set_orientation(gtk::Orientation::Vertical);
         //obj.set_orientation(gtk::Orientation::Horizontal);
@@ -85,6 +88,14 @@ impl ObjectImpl for ViewHideBoxInner {
         self.show_handles_btn
             .set(btn)
             .expect("Failed to create ViewHideBox");
+        let btn = gtk::CheckButton::with_label("Show anchors");
+        btn.set_visible(true);
+        btn.set_active(true);
+        obj.pack_start(&btn, false, false, 0);
+        btn.bind_property("active", obj, "show-anchors").build();
+        self.show_anchors_btn
+            .set(btn)
+            .expect("Failed to create ViewHideBox");
         let btn = gtk::CheckButton::with_label("Inner fill");
         btn.set_visible(true);
         btn.set_active(false);
@@ -120,6 +131,13 @@ impl ObjectImpl for ViewHideBoxInner {
                         true,
                         ParamFlags::READWRITE,
                     ),
+                    ParamSpecBoolean::new(
+                        "show-anchors",
+                        "show-anchors",
+                        "show-anchors",
+                        true,
+                        ParamFlags::READWRITE,
+                    ),
                     ParamSpecBoolean::new(
                         "inner-fill",
                         "inner-fill",
@@ -137,6 +155,7 @@ impl ObjectImpl for ViewHideBoxInner {
             "show-grid" => self.show_grid.get().to_value(),
             "show-guidelines" => self.show_guidelines.get().to_value(),
             "show-handles" => self.show_handles.get().to_value(),
+            "show-anchors" => self.show_anchors.get().to_value(),
             "inner-fill" => self.inner_fill.get().to_value(),
             _ => unreachable!(),
         }
@@ -156,6 +175,10 @@ impl ObjectImpl for ViewHideBoxInner {
                 let val = value.get().expect("The value needs to be of type `bool`.");
                 self.show_handles.set(val);
             }
+            "show-anchors" => {
+                let val = value.get().expect("The value needs to be of type `bool`.");
+                self.show_anchors.set(val);
+            }
             "inner-fill" => {
                 let val = value.get().expect("The value needs to be of type `bool`.");
                 self.inner_fill.set(val);
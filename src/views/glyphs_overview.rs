@@ -29,7 +29,7 @@ use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::glyphs::{Glyph, GlyphDrawingOptions, GlyphKind};
+use crate::glyphs::{correct_directions, Contour, Glyph, GlyphDrawingOptions, GlyphKind};
 use crate::project::Project;
 use crate::unicode::blocks::*;
 
@@ -47,7 +47,7 @@ pub struct GlyphsArea {
     hide_empty: Cell<bool>,
     zoom_factor: Cell<f64>,
     filter_input: RefCell<Option<String>>,
-    widgets: OnceCell<Vec<GlyphBoxItem>>,
+    widgets: RefCell<Vec<GlyphBoxItem>>,
 }
 
 #[glib::object_subclass]
@@ -72,7 +72,24 @@ impl ObjectImpl for GlyphsArea {
             .can_focus(true)
             .column_spacing(5)
             .row_spacing(5)
+            .selection_mode(gtk::SelectionMode::Multiple)
             .build();
+        grid.set_sort_func(Some(Box::new(
+            clone!(@weak obj => @default-return 0, move |a: &gtk::FlowBoxChild, b: &gtk::FlowBoxChild| {
+                let glyph = |c: &gtk::FlowBoxChild| {
+                    c.child()
+                        .and_then(|w| w.downcast::<GlyphBoxItem>().ok())
+                        .map(|w| w.imp().glyph.get().unwrap().clone())
+                };
+                match (glyph(a), glyph(b), obj.imp().project.get()) {
+                    (Some(a), Some(b), Some(project)) => {
+                        project.cmp_glyphs(&a.borrow(), &b.borrow()) as i32
+                    }
+                    (Some(a), Some(b), None) => a.cmp(&b) as i32,
+                    _ => 0,
+                }
+            }),
+        )));
 
         let overlay = gtk::Overlay::builder()
             .expand(true)
@@ -178,11 +195,44 @@ impl ObjectImpl for GlyphsArea {
             .build();
 
         add_glyph_button.connect_clicked(clone!(@weak obj => move |_| {
+            obj.add_glyph();
         }));
 
         tool_palette.add(&add_glyph_button);
         tool_palette.set_item_homogeneous(&add_glyph_button, false);
 
+        let import_glyphs_button = gtk::ToolButton::builder()
+            .label("Import glyphs")
+            .tooltip_text("Copy glyphs from another font.ufo")
+            .valign(gtk::Align::Center)
+            .halign(gtk::Align::Start)
+            .visible(true)
+            .build();
+
+        import_glyphs_button.connect_clicked(clone!(@weak obj => move |_| {
+            obj.import_glyphs();
+        }));
+
+        tool_palette.add(&import_glyphs_button);
+        tool_palette.set_item_homogeneous(&import_glyphs_button, false);
+
+        let correct_directions_button = gtk::ToolButton::builder()
+            .label("Correct directions")
+            .tooltip_text(
+                "Make outlines run counter-clockwise and counters clockwise in every glyph",
+            )
+            .valign(gtk::Align::Center)
+            .halign(gtk::Align::Start)
+            .visible(true)
+            .build();
+
+        correct_directions_button.connect_clicked(clone!(@weak obj => move |_| {
+            obj.correct_directions();
+        }));
+
+        tool_palette.add(&correct_directions_button);
+        tool_palette.set_item_homogeneous(&correct_directions_button, false);
+
         let search_entry = gtk::Entry::builder()
             .expand(true)
             .visible(true)
@@ -400,7 +450,7 @@ impl GlyphsOverview {
         {
             let glyphs_b = project.imp().glyphs.borrow();
             let mut glyphs = glyphs_b.values().collect::<Vec<&Rc<RefCell<Glyph>>>>();
-            glyphs.sort();
+            glyphs.sort_by(|a, b| project.cmp_glyphs(&a.borrow(), &b.borrow()));
             for glyph in glyphs {
                 let glyph_box = GlyphBoxItem::new(app.clone(), project.clone(), glyph.clone());
                 grid.add(&glyph_box);
@@ -408,7 +458,7 @@ impl GlyphsOverview {
             }
         }
         ret.imp().app.set(app).unwrap();
-        ret.imp().widgets.set(widgets).unwrap();
+        *ret.imp().widgets.borrow_mut() = widgets;
         ret.imp().project.set(project).unwrap();
         ret.update_grid();
         ret.update_tree_store();
@@ -421,7 +471,7 @@ impl GlyphsOverview {
         show_blocks.clear();
         tree_store.clear();
         let mut blocks_set: std::collections::HashMap<&'static str, usize> = Default::default();
-        for c in self.imp().widgets.get().unwrap() {
+        for c in self.imp().widgets.borrow().iter() {
             let glyph = c.imp().glyph.get().unwrap().borrow();
             if let GlyphKind::Char(c) = glyph.kind {
                 if let Some(idx) = c.char_block() {
@@ -539,6 +589,599 @@ impl GlyphsOverview {
         )));
         grid.queue_draw();
     }
+
+    fn add_glyph_box(&self, glyph: Rc<RefCell<Glyph>>) {
+        let imp = self.imp();
+        let glyph_box = GlyphBoxItem::new(
+            imp.app.get().unwrap().clone(),
+            imp.project.get().unwrap().clone(),
+            glyph,
+        );
+        glyph_box.set_visible(true);
+        imp.grid.get().unwrap().add(&glyph_box);
+        imp.widgets.borrow_mut().push(glyph_box);
+        self.update_tree_store();
+        self.update_grid();
+    }
+
+    fn remove_glyph_box(&self, glyph: &Rc<RefCell<Glyph>>) {
+        let imp = self.imp();
+        let grid = imp.grid.get().unwrap();
+        imp.widgets.borrow_mut().retain(|w| {
+            if Rc::ptr_eq(w.imp().glyph.get().unwrap(), glyph) {
+                if let Some(parent) = w.parent() {
+                    grid.remove(&parent);
+                }
+                false
+            } else {
+                true
+            }
+        });
+        self.update_tree_store();
+        self.update_grid();
+    }
+
+    fn window(&self) -> Option<gtk::Window> {
+        self.toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok())
+    }
+
+    fn add_undo_action(&self, mut action: crate::Action) {
+        (action.redo)();
+        let app: &crate::Application = crate::Application::from_instance(
+            self.imp()
+                .app
+                .get()
+                .unwrap()
+                .downcast_ref::<crate::GerbApp>()
+                .unwrap(),
+        );
+        let undo_db = app.undo_db.borrow_mut();
+        undo_db.event(action);
+    }
+
+    fn show_error(&self, msg: &str) {
+        show_error(self.window().as_ref(), None, msg);
+    }
+
+    /// Ask the user for a new glyph's name and code point and add it to the project.
+    pub fn add_glyph(&self) {
+        let project = self.imp().project.get().unwrap();
+        let (name, unicode) = match glyph_name_dialog(self.window().as_ref(), "Add glyph", "", true)
+        {
+            Some(v) => v,
+            None => return,
+        };
+        if project.imp().glyphs.borrow().contains_key(&name) {
+            self.show_error(&format!(
+                "Glyph <i>{}</i> already exists.",
+                crate::utils::xml_escape(&name)
+            ));
+            return;
+        }
+        let mut glyph = Glyph::new_empty(name, unicode.unwrap_or('\0'));
+        glyph.contours.clear();
+        glyph.modified = true;
+        if unicode.is_none() {
+            glyph.kind = GlyphKind::Component;
+            glyph.name2 = None;
+            glyph.unicode.clear();
+        }
+        self.add_undo_action(self.insert_glyph_action(Rc::new(RefCell::new(glyph))));
+    }
+
+    /// Add a copy of `glyph` to the project, under the first free name of the form
+    /// `name.001`, `name.002` and so on.
+    pub fn duplicate_glyph(&self, glyph: &Rc<RefCell<Glyph>>) {
+        let project = self.imp().project.get().unwrap();
+        let name = {
+            let glyphs = project.imp().glyphs.borrow();
+            let base = glyph.borrow().name.to_string();
+            (1..)
+                .map(|i| format!("{}.{:03}", base, i))
+                .find(|n| !glyphs.contains_key(n))
+                .unwrap()
+        };
+        let duplicate = glyph.borrow().duplicate(name);
+        self.add_undo_action(self.insert_glyph_action(Rc::new(RefCell::new(duplicate))));
+    }
+
+    /// Ask the user for a new name for `glyph`.
+    pub fn rename_glyph(&self, glyph: &Rc<RefCell<Glyph>>) {
+        let project = self.imp().project.get().unwrap();
+        let old_name = glyph.borrow().name.to_string();
+        let new_name =
+            match glyph_name_dialog(self.window().as_ref(), "Rename glyph", &old_name, false) {
+                Some((n, _)) if n != old_name => n,
+                _ => return,
+            };
+        if project.imp().glyphs.borrow().contains_key(&new_name) {
+            self.show_error(&format!(
+                "Glyph <i>{}</i> already exists.",
+                crate::utils::xml_escape(&new_name)
+            ));
+            return;
+        }
+        let grid = self.imp().grid.get().unwrap().clone();
+        let rename = clone!(@weak self as obj, @weak project, @weak grid => move |from: &str, to: &str| {
+            if let Err(err) = project.rename_glyph(from, to) {
+                obj.show_error(&crate::utils::xml_escape(&err.to_string()));
+                return;
+            }
+            grid.invalidate_sort();
+            grid.queue_draw();
+        });
+        let (redo_old, redo_new) = (old_name.clone(), new_name.clone());
+        let rename2 = rename.clone();
+        self.add_undo_action(crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "glyphs",
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: Box::new(move || rename(&redo_old, &redo_new)),
+            undo: Box::new(move || rename2(&new_name, &old_name)),
+        });
+    }
+
+    /// The glyphs selected with Ctrl+click that aren't hidden by the current filter.
+    pub fn selected_glyphs(&self) -> Vec<Rc<RefCell<Glyph>>> {
+        self.imp()
+            .grid
+            .get()
+            .unwrap()
+            .selected_children()
+            .into_iter()
+            .filter(|c| c.is_child_visible())
+            .filter_map(|c| c.child()?.downcast::<GlyphBoxItem>().ok())
+            .map(|w| w.imp().glyph.get().unwrap().clone())
+            .collect()
+    }
+
+    /// Export the selected glyphs as SVG if `glyph` is one of them, otherwise only `glyph`.
+    pub fn export_glyphs_svg(&self, glyph: &Rc<RefCell<Glyph>>) {
+        let mut selected = self.selected_glyphs();
+        if !selected.iter().any(|g| Rc::ptr_eq(g, glyph)) {
+            selected = vec![glyph.clone()];
+        }
+        let names = selected
+            .iter()
+            .map(|g| g.borrow().name.to_string())
+            .collect::<Vec<String>>();
+        export_svg_dialog(
+            self.window().as_ref(),
+            self.imp().project.get().unwrap(),
+            Some(&names),
+        );
+    }
+
+    /// Ask for another project and copy the glyphs the user picks from it into this one, as a
+    /// single undoable action.
+    pub fn import_glyphs(&self) {
+        let imp = self.imp();
+        let project = imp.project.get().unwrap();
+        let window = self.window();
+        let dialog = gtk::FileChooserNative::new(
+            Some("Import glyphs from font.ufo directory..."),
+            window.as_ref(),
+            gtk::FileChooserAction::SelectFolder,
+            None,
+            None,
+        );
+        dialog.run();
+        dialog.hide();
+        let path = match dialog.filename() {
+            Some(p) => p,
+            None => return,
+        };
+        let source = match crate::project::Project::from_path(&path.to_string_lossy()) {
+            Ok(p) => p,
+            Err(err) => {
+                self.show_error(&crate::utils::xml_escape(&err.to_string()));
+                return;
+            }
+        };
+        let (names, conflict, scale) =
+            match import_glyphs_dialog(window.as_ref(), imp.app.get().unwrap(), &source, project) {
+                Some(v) => v,
+                None => return,
+            };
+        let glyphs = crate::import::import_glyphs(&source, project, &names, conflict, scale)
+            .into_iter()
+            .map(|g| Rc::new(RefCell::new(g)))
+            .collect::<Vec<_>>();
+        if glyphs.is_empty() {
+            return;
+        }
+        /* The glyph each imported one replaced, if there was one with its name. */
+        let replaced = Rc::new(RefCell::new(Vec::with_capacity(glyphs.len())));
+        let glyphs = Rc::new(glyphs);
+        self.add_undo_action(crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "glyphs",
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: Box::new(
+                clone!(@weak self as obj, @weak project, @strong glyphs, @strong replaced => move || {
+                    let mut replaced = replaced.borrow_mut();
+                    replaced.clear();
+                    for glyph in glyphs.iter() {
+                        let old = project.replace_glyph(glyph.clone());
+                        if let Some(ref old) = old {
+                            obj.remove_glyph_box(old);
+                        }
+                        obj.add_glyph_box(glyph.clone());
+                        replaced.push(old);
+                    }
+                }),
+            ),
+            undo: Box::new(
+                clone!(@weak self as obj, @weak project, @strong glyphs, @strong replaced => move || {
+                    for (glyph, old) in glyphs.iter().zip(replaced.borrow_mut().drain(..)) {
+                        obj.remove_glyph_box(glyph);
+                        if let Some(old) = old {
+                            project.replace_glyph(old.clone());
+                            obj.add_glyph_box(old);
+                        } else {
+                            let name = glyph.borrow().name.to_string();
+                            project.remove_glyph(&name);
+                        }
+                    }
+                }),
+            ),
+        });
+    }
+
+    /// Fix the contour directions of every glyph of the project, as a single undoable action.
+    pub fn correct_directions(&self) {
+        let project = self.imp().project.get().unwrap();
+        /* Each changed glyph with copies of its contours before and after. */
+        let changes = project
+            .imp()
+            .glyphs
+            .borrow()
+            .values()
+            .filter_map(|glyph| {
+                let old = glyph
+                    .borrow()
+                    .contours
+                    .iter()
+                    .map(Contour::duplicate)
+                    .collect::<Vec<Contour>>();
+                let new = old.iter().map(Contour::duplicate).collect::<Vec<Contour>>();
+                if correct_directions(&new) {
+                    Some((glyph.clone(), old, new))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return;
+        }
+        let changes = Rc::new(changes);
+        let set_contours = |new: bool| -> Box<dyn FnMut()> {
+            Box::new(clone!(@weak self as obj, @strong changes => move || {
+                for (glyph, old, new_contours) in changes.iter() {
+                    let contours = if new { new_contours } else { old };
+                    let mut glyph = glyph.borrow_mut();
+                    glyph.contours = contours.iter().map(Contour::duplicate).collect();
+                    glyph.modified = true;
+                }
+                obj.imp().grid.get().unwrap().queue_draw();
+            }))
+        };
+        self.add_undo_action(crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "correct-directions",
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: set_contours(true),
+            undo: set_contours(false),
+        });
+    }
+
+    /// Remove `glyph` from the project.
+    pub fn delete_glyph(&self, glyph: &Rc<RefCell<Glyph>>) {
+        let mut action = self.insert_glyph_action(glyph.clone());
+        std::mem::swap(&mut action.redo, &mut action.undo);
+        self.add_undo_action(action);
+    }
+
+    /// Action that adds `glyph` to the project on redo and removes it on undo.
+    fn insert_glyph_action(&self, glyph: Rc<RefCell<Glyph>>) -> crate::Action {
+        let project = self.imp().project.get().unwrap();
+        crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "glyphs",
+                id: Box::new([]),
+            },
+            compress: false,
+            redo: Box::new(
+                clone!(@weak self as obj, @weak project, @strong glyph => move || {
+                    if let Err(err) = project.add_glyph(glyph.clone()) {
+                        obj.show_error(&crate::utils::xml_escape(&err.to_string()));
+                        return;
+                    }
+                    obj.add_glyph_box(glyph.clone());
+                }),
+            ),
+            undo: Box::new(
+                clone!(@weak self as obj, @weak project, @strong glyph => move || {
+                    let name = glyph.borrow().name.to_string();
+                    if project.remove_glyph(&name).is_some() {
+                        obj.remove_glyph_box(&glyph);
+                    }
+                }),
+            ),
+        }
+    }
+}
+
+/// Ask for a directory and write the glyphs named in `selection`, or all glyphs, and an SVG font
+/// of them there. See [`crate::export::export_svg`].
+pub fn export_svg_dialog(
+    window: Option<&gtk::Window>,
+    project: &Project,
+    selection: Option<&[String]>,
+) {
+    let dialog = gtk::FileChooserNative::new(
+        Some("Export glyphs as SVG to..."),
+        window,
+        gtk::FileChooserAction::SelectFolder,
+        None,
+        None,
+    );
+    dialog.set_create_folders(true);
+    dialog.run();
+    dialog.hide();
+    let dir = match dialog.filename() {
+        Some(d) => d,
+        None => return,
+    };
+    if let Err(err) = crate::export::export_svg(project, &dir, selection) {
+        show_error(
+            window,
+            Some("Error: Could not export glyphs"),
+            &crate::utils::xml_escape(&err.to_string()),
+        );
+    }
+}
+
+/// Show a modal error dialog. `msg` is Pango markup, so text from elsewhere has to be escaped.
+fn show_error(window: Option<&gtk::Window>, title: Option<&str>, msg: &str) {
+    let dialog = gtk::MessageDialog::new(
+        window,
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        gtk::MessageType::Error,
+        gtk::ButtonsType::Close,
+        msg,
+    );
+    if let Some(title) = title {
+        dialog.set_title(title);
+    }
+    dialog.set_use_markup(true);
+    dialog.run();
+    dialog.hide();
+}
+
+/// Ask for a glyph name and, if `ask_unicode` is set, an optional code point given either as the
+/// character itself or in hexadecimal (`U+0041`, `0041`).
+fn glyph_name_dialog(
+    window: Option<&gtk::Window>,
+    title: &str,
+    name: &str,
+    ask_unicode: bool,
+) -> Option<(String, Option<char>)> {
+    let dialog = gtk::Dialog::with_buttons(
+        Some(title),
+        window,
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        &[
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("OK", gtk::ResponseType::Accept),
+        ],
+    );
+    dialog.set_default_response(gtk::ResponseType::Accept);
+    let grid = gtk::Grid::builder()
+        .visible(true)
+        .column_spacing(5)
+        .row_spacing(5)
+        .margin(10)
+        .build();
+    let name_entry = gtk::Entry::builder()
+        .text(name)
+        .activates_default(true)
+        .visible(true)
+        .hexpand(true)
+        .build();
+    grid.attach(
+        &gtk::Label::builder().label("Name").visible(true).build(),
+        0,
+        0,
+        1,
+        1,
+    );
+    grid.attach(&name_entry, 1, 0, 1, 1);
+    let unicode_entry = gtk::Entry::builder()
+        .placeholder_text("e.g. U+0041 or A")
+        .activates_default(true)
+        .visible(true)
+        .hexpand(true)
+        .build();
+    if ask_unicode {
+        grid.attach(
+            &gtk::Label::builder().label("Unicode").visible(true).build(),
+            0,
+            1,
+            1,
+            1,
+        );
+        grid.attach(&unicode_entry, 1, 1, 1, 1);
+    }
+    dialog.content_area().add(&grid);
+    let ret = loop {
+        if dialog.run() != gtk::ResponseType::Accept {
+            break None;
+        }
+        let name = name_entry.text().trim().to_string();
+        if name.is_empty() || name.chars().any(char::is_control) {
+            name_entry.grab_focus();
+            continue;
+        }
+        let unicode = unicode_entry.text().trim().to_string();
+        if unicode.is_empty() {
+            break Some((name, None));
+        }
+        let mut chars = unicode.chars();
+        let c = match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => u32::from_str_radix(
+                unicode.trim_start_matches("U+").trim_start_matches("u+"),
+                16,
+            )
+            .ok()
+            .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => break Some((name, Some(c))),
+            None => {
+                unicode_entry.grab_focus();
+                continue;
+            }
+        }
+    };
+    dialog.hide();
+    ret
+}
+
+/// Let the user pick glyphs of `source` to import into `target`, and what to do about names
+/// they already use.
+fn import_glyphs_dialog(
+    window: Option<&gtk::Window>,
+    app: &gtk::Application,
+    source: &Project,
+    target: &Project,
+) -> Option<(Vec<String>, crate::import::NameConflict, bool)> {
+    use crate::import::NameConflict;
+
+    let dialog = gtk::Dialog::with_buttons(
+        Some(&format!(
+            "Import glyphs from {}",
+            source.imp().name.borrow().as_str()
+        )),
+        window,
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        &[
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("Import", gtk::ResponseType::Accept),
+        ],
+    );
+    dialog.set_default_size(720, 560);
+    dialog.set_default_response(gtk::ResponseType::Accept);
+    let grid = gtk::FlowBox::builder()
+        .visible(true)
+        .selection_mode(gtk::SelectionMode::Multiple)
+        .activate_on_single_click(false)
+        .valign(gtk::Align::Start)
+        .column_spacing(5)
+        .row_spacing(5)
+        .margin(10)
+        .build();
+    let mut glyphs = source
+        .imp()
+        .glyphs
+        .borrow()
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    glyphs.sort_by(|a, b| source.cmp_glyphs(&a.borrow(), &b.borrow()));
+    for glyph in glyphs {
+        let glyph_box = GlyphBoxItem::new(app.clone(), source.clone(), glyph);
+        glyph_box.set_visible(true);
+        grid.add(&glyph_box);
+    }
+    grid.connect_selected_children_changed(|grid| grid.queue_draw());
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .expand(true)
+        .visible(true)
+        .child(&grid)
+        .build();
+    let options = gtk::Grid::builder()
+        .visible(true)
+        .column_spacing(5)
+        .row_spacing(5)
+        .margin(10)
+        .build();
+    options.attach(
+        &gtk::Label::builder()
+            .label("Select glyphs with click, Ctrl+click and Ctrl+A. Glyphs used as components are imported too.")
+            .wrap(true)
+            .halign(gtk::Align::Start)
+            .visible(true)
+            .build(),
+        0,
+        0,
+        2,
+        1,
+    );
+    let conflict = gtk::ComboBoxText::builder().visible(true).build();
+    conflict.append(Some("rename"), "Rename imported glyph");
+    conflict.append(Some("overwrite"), "Overwrite existing glyph");
+    conflict.append(Some("skip"), "Skip glyph");
+    conflict.set_active_id(Some("rename"));
+    options.attach(
+        &gtk::Label::builder()
+            .label("If the name exists")
+            .visible(true)
+            .build(),
+        0,
+        1,
+        1,
+        1,
+    );
+    options.attach(&conflict, 1, 1, 1, 1);
+    let (source_upm, target_upm) = (
+        source.fontinfo().units_per_em(),
+        target.fontinfo().units_per_em(),
+    );
+    let scale = gtk::CheckButton::builder()
+        .label(&format!(
+            "Scale from {} to {} units per em",
+            source_upm, target_upm
+        ))
+        .active(true)
+        .visible(source_upm != target_upm)
+        .build();
+    options.attach(&scale, 1, 2, 1, 1);
+    dialog
+        .content_area()
+        .pack_start(&scrolled_window, true, true, 0);
+    dialog.content_area().pack_start(&options, false, false, 0);
+    let ret = if dialog.run() == gtk::ResponseType::Accept {
+        let names = grid
+            .selected_children()
+            .into_iter()
+            .filter_map(|c| c.child()?.downcast::<GlyphBoxItem>().ok())
+            .map(|w| w.imp().glyph.get().unwrap().borrow().name.to_string())
+            .collect::<Vec<String>>();
+        let conflict = match conflict.active_id().as_deref() {
+            Some("overwrite") => NameConflict::Overwrite,
+            Some("skip") => NameConflict::Skip,
+            _ => NameConflict::Rename,
+        };
+        Some((names, conflict, scale.is_visible() && scale.is_active()))
+    } else {
+        None
+    };
+    dialog.hide();
+    ret
 }
 
 #[derive(Debug, Default)]
@@ -572,17 +1215,109 @@ impl ObjectImpl for GlyphBox {
         obj.set_can_focus(true);
         obj.set_expand(false);
 
-        obj.connect(
-            "button-press-event",
-            false,
-            clone!(@weak obj => @default-return Some(false.to_value()), move |_| {
-                obj.imp().app.get().unwrap().downcast_ref::<crate::GerbApp>().unwrap().imp().window.get().unwrap().emit_by_name::<()>("open-glyph-edit", &[&obj]);
-                println!("open-glyph-edit emitted!");
-
-
-                Some(true.to_value())
-            }),
-        );
+        obj.connect_button_press_event(|obj, event| -> Inhibit {
+            if obj.overview().is_none() {
+                /* Outside the overview, as in the import dialog, only the flow box's own
+                 * selection applies. */
+                return Inhibit(false);
+            }
+            obj.grab_focus();
+            match event.button() {
+                gtk::gdk::BUTTON_PRIMARY
+                    if event.state().contains(gtk::gdk::ModifierType::CONTROL_MASK) =>
+                {
+                    if let (Some(child), Some(grid)) = (
+                        obj.parent()
+                            .and_then(|p| p.downcast::<gtk::FlowBoxChild>().ok()),
+                        obj.overview().map(|o| o.imp().grid.get().unwrap().clone()),
+                    ) {
+                        if child.is_selected() {
+                            grid.unselect_child(&child);
+                        } else {
+                            grid.select_child(&child);
+                        }
+                        obj.queue_draw();
+                    }
+                }
+                gtk::gdk::BUTTON_PRIMARY => {
+                    obj.imp()
+                        .app
+                        .get()
+                        .unwrap()
+                        .downcast_ref::<crate::GerbApp>()
+                        .unwrap()
+                        .imp()
+                        .window
+                        .get()
+                        .unwrap()
+                        .emit_by_name::<()>("open-glyph-edit", &[&obj]);
+                }
+                gtk::gdk::BUTTON_SECONDARY => {
+                    let menu = gtk::Menu::builder()
+                        .attach_widget(obj)
+                        .take_focus(true)
+                        .visible(true)
+                        .build();
+                    for (label, f) in [
+                        (
+                            "Duplicate",
+                            GlyphsOverview::duplicate_glyph
+                                as fn(&GlyphsOverview, &Rc<RefCell<Glyph>>),
+                        ),
+                        ("Rename...", GlyphsOverview::rename_glyph),
+                        ("Delete", GlyphsOverview::delete_glyph),
+                        ("Export SVG...", GlyphsOverview::export_glyphs_svg),
+                    ] {
+                        let item = gtk::MenuItem::builder().label(label).visible(true).build();
+                        item.connect_activate(clone!(@weak obj => move |_| {
+                            if let Some(overview) = obj.overview() {
+                                f(&overview, obj.imp().glyph.get().unwrap());
+                            }
+                        }));
+                        menu.append(&item);
+                    }
+                    menu.show_all();
+                    menu.popup_easy(event.button(), event.time());
+                }
+                _ => {}
+            }
+            Inhibit(true)
+        });
+        obj.connect_key_press_event(|obj, event| -> Inhibit {
+            let overview = match obj.overview() {
+                Some(o) => o,
+                None => return Inhibit(false),
+            };
+            let glyph = obj.imp().glyph.get().unwrap();
+            match event.keyval() {
+                gtk::gdk::keys::constants::Delete => overview.delete_glyph(glyph),
+                gtk::gdk::keys::constants::F2 => overview.rename_glyph(glyph),
+                gtk::gdk::keys::constants::Escape => {
+                    overview.imp().grid.get().unwrap().unselect_all();
+                    overview.queue_draw();
+                }
+                gtk::gdk::keys::constants::d
+                    if event.state().contains(gtk::gdk::ModifierType::CONTROL_MASK) =>
+                {
+                    overview.duplicate_glyph(glyph)
+                }
+                gtk::gdk::keys::constants::Return => {
+                    obj.imp()
+                        .app
+                        .get()
+                        .unwrap()
+                        .downcast_ref::<crate::GerbApp>()
+                        .unwrap()
+                        .imp()
+                        .window
+                        .get()
+                        .unwrap()
+                        .emit_by_name::<()>("open-glyph-edit", &[obj]);
+                }
+                _ => return Inhibit(false),
+            }
+            Inhibit(true)
+        });
         let drawing_area = gtk::DrawingArea::builder()
             .expand(true)
             .visible(true)
@@ -609,8 +1344,13 @@ impl ObjectImpl for GlyphBox {
         drawing_area.connect_draw(clone!(@weak obj => @default-return Inhibit(false), move |_drar: &gtk::DrawingArea, cr: &Context| {
             cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
             let is_focused: bool = obj.imp().focused.get();
+            let is_selected: bool = obj
+                .parent()
+                .and_then(|p| p.downcast::<gtk::FlowBoxChild>().ok())
+                .map(|c| c.is_selected())
+                .unwrap_or(false);
             let zoom_factor: f64 = obj.imp().zoom_factor.get();
-            let units_per_em = *obj.imp().project.get().unwrap().imp().units_per_em.borrow();
+            let units_per_em = obj.imp().project.get().unwrap().fontinfo().units_per_em();
             //cr.scale(500f64, 500f64);
             //let (r, g, b) = crate::utils::hex_color_to_rgb("#c4c4c4").unwrap();
             //cr.set_source_rgb(r, g, b);
@@ -628,7 +1368,9 @@ impl ObjectImpl for GlyphBox {
             cr.set_line_width(1.5);
             let (point, (width, height)) = crate::utils::draw_round_rectangle(cr, (x, y), (zoom_factor * GLYPH_BOX_WIDTH, zoom_factor * GLYPH_BOX_HEIGHT), 1.0, 1.5);
             let glyph_width = glyph.width.unwrap_or(units_per_em) * (width * 0.8) / units_per_em;
-            if is_focused {
+            if is_selected {
+                cr.set_source_rgb(196./255., 218./255., 245./255.);
+            } else if is_focused {
                 cr.set_source_rgb(1., 250./255., 141./255.);
             } else {
                 cr.set_source_rgb(1., 1., 1.);
@@ -699,6 +1441,8 @@ impl ObjectImpl for GlyphBox {
 
         obj.set_events(
             gtk::gdk::EventMask::POINTER_MOTION_MASK
+                | gtk::gdk::EventMask::BUTTON_PRESS_MASK
+                | gtk::gdk::EventMask::KEY_PRESS_MASK
                 | gtk::gdk::EventMask::ENTER_NOTIFY_MASK
                 | gtk::gdk::EventMask::LEAVE_NOTIFY_MASK,
         );
@@ -756,4 +1500,9 @@ impl GlyphBoxItem {
         ret.imp().glyph.set(glyph).unwrap();
         ret
     }
+
+    fn overview(&self) -> Option<GlyphsOverview> {
+        self.ancestor(GlyphsOverview::static_type())
+            .and_then(|w| w.downcast::<GlyphsOverview>().ok())
+    }
 }
@@ -0,0 +1,425 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use glib::{clone, ParamFlags, ParamSpec, ParamSpecBoolean, ParamSpecString, Value};
+use gtk::cairo::{Context, FontSlant, FontWeight};
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use once_cell::unsync::OnceCell;
+use std::cell::{Cell, RefCell};
+
+use crate::glyphs::GlyphDrawingOptions;
+use crate::project::Project;
+
+#[derive(Debug, Default)]
+pub struct KerningEditArea {
+    app: OnceCell<gtk::Application>,
+    project: OnceCell<Project>,
+    drawing_area: OnceCell<gtk::DrawingArea>,
+    value_spin: OnceCell<gtk::SpinButton>,
+    pair_label: OnceCell<gtk::Label>,
+    /// Names of the glyphs being kerned.
+    pair: RefCell<Option<(String, String)>>,
+    /// Set while the spin button is updated to reflect the project, so that it doesn't record
+    /// an undo action.
+    updating: Cell<bool>,
+    /// Mouse x position and stored kerning value when a drag started.
+    drag: RefCell<Option<(f64, Option<f64>)>>,
+    /// Font units per pixel in the last drawing.
+    units_per_pixel: Cell<f64>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for KerningEditArea {
+    const NAME: &'static str = "KerningEditArea";
+    type Type = KerningEditView;
+    type ParentType = gtk::Bin;
+}
+
+impl ObjectImpl for KerningEditArea {
+    fn constructed(&self, obj: &Self::Type) {
+        self.parent_constructed(obj);
+        self.units_per_pixel.set(1.);
+
+        let pair_entry = gtk::Entry::builder()
+            .visible(true)
+            .placeholder_text("Type a pair, e.g. AV or T o")
+            .build();
+        pair_entry.connect_changed(clone!(@weak obj => move |_self| {
+            obj.set_pair_from_text(&_self.text());
+        }));
+
+        let value_spin = gtk::SpinButton::with_range(-16384., 16384., 1.);
+        value_spin.set_digits(0);
+        value_spin.set_visible(true);
+        value_spin.set_sensitive(false);
+        value_spin.connect_value_changed(clone!(@weak obj => move |_self| {
+            if obj.imp().updating.get() {
+                return;
+            }
+            if let Some((first, second)) = obj.kerning_pair() {
+                let old = obj.imp().project.get().unwrap().imp().kerning.borrow().get(&first, &second);
+                let new = Some(_self.value());
+                if old != new {
+                    obj.add_undo_action(obj.kerning_action((first, second), old, new, true));
+                }
+            }
+        }));
+
+        let pair_label = gtk::Label::builder().visible(true).use_markup(true).build();
+
+        let toolbar = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(5)
+            .margin(5)
+            .visible(true)
+            .build();
+        toolbar.pack_start(&pair_entry, false, false, 0);
+        toolbar.pack_start(
+            &gtk::Label::builder()
+                .label("Kerning:")
+                .visible(true)
+                .build(),
+            false,
+            false,
+            0,
+        );
+        toolbar.pack_start(&value_spin, false, false, 0);
+        toolbar.pack_start(&pair_label, false, false, 0);
+
+        let drawing_area = gtk::DrawingArea::builder()
+            .expand(true)
+            .visible(true)
+            .tooltip_text("Drag horizontally to adjust the kerning value")
+            .build();
+        drawing_area.set_events(
+            gtk::gdk::EventMask::BUTTON_PRESS_MASK
+                | gtk::gdk::EventMask::BUTTON_RELEASE_MASK
+                | gtk::gdk::EventMask::BUTTON_MOTION_MASK,
+        );
+        drawing_area.connect_button_press_event(
+            clone!(@weak obj => @default-return Inhibit(false), move |_self, event| {
+                if event.button() != gtk::gdk::BUTTON_PRIMARY {
+                    return Inhibit(false);
+                }
+                if let Some((first, second)) = obj.kerning_pair() {
+                    let value = obj.imp().project.get().unwrap().imp().kerning.borrow().get(&first, &second);
+                    *obj.imp().drag.borrow_mut() = Some((event.position().0, value));
+                }
+                Inhibit(true)
+            }),
+        );
+        drawing_area.connect_motion_notify_event(
+            clone!(@weak obj => @default-return Inhibit(false), move |_self, event| {
+                let drag = *obj.imp().drag.borrow();
+                if let (Some((start_x, start_value)), Some((first, second))) = (drag, obj.kerning_pair()) {
+                    let project = obj.imp().project.get().unwrap();
+                    let delta = (event.position().0 - start_x) * obj.imp().units_per_pixel.get();
+                    let value = (start_value.unwrap_or(0.) + delta).round();
+                    project.imp().kerning.borrow_mut().set(&first, &second, Some(value));
+                    obj.update_value();
+                }
+                Inhibit(true)
+            }),
+        );
+        drawing_area.connect_button_release_event(
+            clone!(@weak obj => @default-return Inhibit(false), move |_self, _event| {
+                let drag = obj.imp().drag.borrow_mut().take();
+                if let (Some((_, old)), Some((first, second))) = (drag, obj.kerning_pair()) {
+                    let new = obj.imp().project.get().unwrap().imp().kerning.borrow().get(&first, &second);
+                    if old != new {
+                        obj.add_undo_action(obj.kerning_action((first, second), old, new, false));
+                    }
+                }
+                Inhibit(true)
+            }),
+        );
+        drawing_area.connect_draw(
+            clone!(@weak obj => @default-return Inhibit(false), move |_self, cr: &Context| {
+                obj.draw(_self, cr);
+                Inhibit(false)
+            }),
+        );
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .expand(true)
+            .visible(true)
+            .build();
+        vbox.pack_start(&toolbar, false, false, 0);
+        vbox.pack_start(&drawing_area, true, true, 0);
+        obj.add(&vbox);
+        obj.set_visible(true);
+        obj.set_expand(true);
+
+        self.drawing_area.set(drawing_area).unwrap();
+        self.value_spin.set(value_spin).unwrap();
+        self.pair_label.set(pair_label).unwrap();
+    }
+
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: once_cell::sync::Lazy<Vec<ParamSpec>> =
+            once_cell::sync::Lazy::new(|| {
+                vec![
+                    ParamSpecString::new(
+                        "tab-title",
+                        "tab-title",
+                        "tab-title",
+                        Some("kerning"),
+                        ParamFlags::READABLE,
+                    ),
+                    ParamSpecBoolean::new(
+                        "tab-can-close",
+                        "tab-can-close",
+                        "tab-can-close",
+                        true,
+                        ParamFlags::READABLE,
+                    ),
+                ]
+            });
+        PROPERTIES.as_ref()
+    }
+
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "tab-title" => "kerning".to_value(),
+            "tab-can-close" => true.to_value(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl WidgetImpl for KerningEditArea {}
+impl ContainerImpl for KerningEditArea {}
+impl BinImpl for KerningEditArea {}
+
+glib::wrapper! {
+    pub struct KerningEditView(ObjectSubclass<KerningEditArea>)
+        @extends gtk::Widget, gtk::Container, gtk::Bin;
+}
+
+impl KerningEditView {
+    pub fn new(app: gtk::Application, project: Project) -> Self {
+        let ret: Self = glib::Object::new(&[]).expect("Failed to create kerning view");
+        ret.imp().app.set(app).unwrap();
+        ret.imp().project.set(project).unwrap();
+        ret
+    }
+
+    /// Parse `text` either as two glyph names separated by whitespace or as two characters, and
+    /// show that pair.
+    fn set_pair_from_text(&self, text: &str) {
+        let project = self.imp().project.get().unwrap();
+        let pair = {
+            let glyphs = project.imp().glyphs.borrow();
+            let names = text.split_whitespace().collect::<Vec<&str>>();
+            let chars = text
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<Vec<char>>();
+            let glyph_for_char = |c: char| {
+                glyphs
+                    .values()
+                    .find(|g| g.borrow().unicode.contains(&c))
+                    .map(|g| g.borrow().name.to_string())
+            };
+            if names.len() == 2 && names.iter().all(|n| glyphs.contains_key(*n)) {
+                Some((names[0].to_string(), names[1].to_string()))
+            } else if chars.len() == 2 {
+                glyph_for_char(chars[0]).zip(glyph_for_char(chars[1]))
+            } else {
+                None
+            }
+        };
+        *self.imp().pair.borrow_mut() = pair;
+        self.update_value();
+    }
+
+    /// The stored kerning pair that applies to the glyphs being kerned: the one found by group
+    /// lookup if there is one, otherwise the glyphs themselves.
+    fn kerning_pair(&self) -> Option<(String, String)> {
+        let (left, right) = self.imp().pair.borrow().clone()?;
+        let project = self.imp().project.get().unwrap();
+        let kerning = project.imp().kerning.borrow();
+        Some(
+            kerning
+                .lookup(&project.imp().groups.borrow(), &left, &right)
+                .map(|(pair, _)| pair)
+                .unwrap_or((left, right)),
+        )
+    }
+
+    /// Update the toolbar and drawing to reflect the project's current kerning.
+    fn update_value(&self) {
+        let imp = self.imp();
+        let project = imp.project.get().unwrap();
+        let value_spin = imp.value_spin.get().unwrap();
+        let pair_label = imp.pair_label.get().unwrap();
+        imp.updating.set(true);
+        if let Some((left, right)) = imp.pair.borrow().clone() {
+            let (first, second) = self.kerning_pair().unwrap();
+            value_spin.set_sensitive(true);
+            value_spin.set_value(project.kerning_value(&left, &right));
+            pair_label.set_markup(&format!(
+                "<i>{}</i> <i>{}</i>",
+                glib::markup_escape_text(&first),
+                glib::markup_escape_text(&second)
+            ));
+        } else {
+            value_spin.set_sensitive(false);
+            value_spin.set_value(0.);
+            pair_label.set_markup("");
+        }
+        imp.updating.set(false);
+        imp.drawing_area.get().unwrap().queue_draw();
+    }
+
+    /// An action that changes the stored value of `pair` from `old` to `new`. `None` means the
+    /// pair doesn't exist.
+    fn kerning_action(
+        &self,
+        (first, second): (String, String),
+        old: Option<f64>,
+        new: Option<f64>,
+        compress: bool,
+    ) -> crate::Action {
+        let project = self.imp().project.get().unwrap().clone();
+        let set = clone!(@weak self as obj, @weak project => move |first: &str, second: &str, value: Option<f64>| {
+            project.imp().kerning.borrow_mut().set(first, second, value);
+            *project.imp().modified.borrow_mut() = true;
+            obj.update_value();
+        });
+        let set2 = set.clone();
+        let (first2, second2) = (first.clone(), second.clone());
+        crate::Action {
+            stamp: crate::EventStamp {
+                t: std::any::TypeId::of::<Self>(),
+                property: "kerning",
+                id: format!("{} {}", first, second)
+                    .into_bytes()
+                    .into_boxed_slice(),
+            },
+            compress,
+            redo: Box::new(move || set(&first, &second, new)),
+            undo: Box::new(move || set2(&first2, &second2, old)),
+        }
+    }
+
+    fn add_undo_action(&self, mut action: crate::Action) {
+        (action.redo)();
+        let app: &crate::Application = crate::Application::from_instance(
+            self.imp()
+                .app
+                .get()
+                .unwrap()
+                .downcast_ref::<crate::GerbApp>()
+                .unwrap(),
+        );
+        let undo_db = app.undo_db.borrow_mut();
+        undo_db.event(action);
+    }
+
+    fn draw(&self, drawing_area: &gtk::DrawingArea, cr: &Context) {
+        cr.set_source_rgb(1., 1., 1.);
+        cr.paint().expect("Invalid cairo surface state");
+        let (width, height) = (
+            drawing_area.allocated_width() as f64,
+            drawing_area.allocated_height() as f64,
+        );
+        let project = self.imp().project.get().unwrap();
+        let (left, right) = match self.imp().pair.borrow().clone() {
+            Some(pair) => pair,
+            None => {
+                cr.set_source_rgba(0., 0., 0., 0.4);
+                cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+                cr.set_font_size(14.);
+                let msg = "Type a pair of glyphs above.";
+                let extents = cr.text_extents(msg).expect("Invalid cairo surface state");
+                cr.move_to((width - extents.width) / 2., height / 2.);
+                cr.show_text(msg).expect("Invalid cairo surface state");
+                return;
+            }
+        };
+        let glyphs = project.imp().glyphs.borrow();
+        let (left_glyph, right_glyph) = match (glyphs.get(&left), glyphs.get(&right)) {
+            (Some(l), Some(r)) => (l.borrow(), r.borrow()),
+            _ => return,
+        };
+        let fontinfo = project.fontinfo();
+        let units_per_em = fontinfo.units_per_em();
+        let kerning = project.kerning_value(&left, &right);
+        let left_width = left_glyph.width.unwrap_or(units_per_em);
+        let right_width = right_glyph.width.unwrap_or(units_per_em);
+        let total_width = (left_width + kerning + right_width).max(1.);
+        let scale = (0.8 * width / total_width).min(0.6 * height / units_per_em);
+        self.imp().units_per_pixel.set(1. / scale);
+        let baseline = height * 0.7;
+        let origin = (width - total_width * scale) / 2.;
+        let top = baseline - units_per_em * scale;
+
+        cr.set_line_width(1.);
+        cr.set_source_rgba(0., 0., 0., 0.3);
+        cr.move_to(0., baseline);
+        cr.line_to(width, baseline);
+        cr.stroke().expect("Invalid cairo surface state");
+        let right_origin = origin + (left_width + kerning) * scale;
+        for (x, w) in [(origin, left_width), (right_origin, right_width)] {
+            cr.rectangle(
+                x,
+                baseline - fontinfo.ascender() * scale,
+                w * scale,
+                (fontinfo.ascender() - fontinfo.descender()) * scale,
+            );
+            cr.stroke().expect("Invalid cairo surface state");
+        }
+
+        for (x, glyph) in [(origin, &left_glyph), (right_origin, &right_glyph)] {
+            let mut matrix = gtk::cairo::Matrix::identity();
+            matrix.translate(x, top);
+            matrix.scale(scale, scale);
+            glyph.draw(
+                cr,
+                GlyphDrawingOptions {
+                    outline: (0., 0., 0., 0.),
+                    inner_fill: Some((0.2, 0.2, 0.2, 1.)),
+                    highlight: None,
+                    matrix,
+                    units_per_em,
+                    ..Default::default()
+                },
+            );
+        }
+
+        cr.set_source_rgb(0.8, 0.1, 0.1);
+        cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+        cr.set_font_size(14.);
+        let label = format!("{}", kerning);
+        let extents = cr
+            .text_extents(&label)
+            .expect("Invalid cairo surface state");
+        cr.move_to(
+            origin + left_width * scale - extents.width / 2.,
+            baseline + 20. - fontinfo.descender() * scale,
+        );
+        cr.show_text(&label).expect("Invalid cairo surface state");
+    }
+}
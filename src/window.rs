@@ -19,6 +19,14 @@
  * along with gerb. If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod load_report;
+pub use load_report::*;
+mod new_project;
+pub use new_project::*;
+#[cfg(feature = "pdf")]
+mod proof;
+#[cfg(feature = "pdf")]
+pub use proof::*;
 mod tabinfo;
 pub use tabinfo::*;
 
@@ -132,13 +140,28 @@ impl WindowSidebar {
     }
 
     fn load_project(&self, project: &Project) {
-        self.project_label.set_markup(&format!("<big>{name}</big>\n\nMajor version: {version_major}\nMinor version: {version_minor}\n\nUnits per <i>em</i>: {units_per_em}\ndescender: {descender}\nascender: {ascender}\n<i>x</i>-height: {x_height}\ncap height: {cap_height}\nitalic angle: {italic_angle}", name=&project.imp().name.borrow(), version_major=project.imp().version_major.borrow(),version_minor=project.imp().version_minor.borrow(), units_per_em=project.imp().units_per_em.borrow(), descender=project.imp().descender.borrow(), x_height=project.imp().x_height.borrow(), cap_height=project.imp().cap_height.borrow(), ascender=project.imp().ascender.borrow(), italic_angle=project.imp().italic_angle.borrow()));
+        let fontinfo = project.fontinfo();
+        self.project_label.set_markup(&format!("<big>{name}</big>\n\nMajor version: {version_major}\nMinor version: {version_minor}\n\nUnits per <i>em</i>: {units_per_em}\ndescender: {descender}\nascender: {ascender}\n<i>x</i>-height: {x_height}\ncap height: {cap_height}\nitalic angle: {italic_angle}", name=&project.imp().name.borrow(), version_major=fontinfo.imp().version_major.borrow().unwrap_or_default(), version_minor=fontinfo.imp().version_minor.borrow().unwrap_or_default(), units_per_em=fontinfo.units_per_em(), descender=fontinfo.descender(), x_height=fontinfo.x_height(), cap_height=fontinfo.cap_height(), ascender=fontinfo.ascender(), italic_angle=fontinfo.italic_angle()));
+        self.tabinfo.set_object(Some(fontinfo.upcast()));
+        self.show_project_lib(project);
         self.project_label.set_single_line_mode(false);
         self.project_label.set_use_markup(true);
         self.project_label.queue_draw();
         self.minimap.queue_draw();
     }
 
+    fn show_project_lib(&self, project: &Project) {
+        let lib = project.imp().lib.borrow().clone();
+        self.tabinfo.set_lib(
+            "Font lib",
+            &lib,
+            clone!(@weak project => move |lib| {
+                *project.imp().lib.borrow_mut() = lib;
+                *project.imp().modified.borrow_mut() = true;
+            }),
+        );
+    }
+
     fn unload_project(&self) {
         self.project_label.set_markup("No project loaded.");
         self.project_label.queue_draw();
@@ -162,7 +185,7 @@ pub struct Window {
     app: OnceCell<gtk::Application>,
     super_: OnceCell<MainWindow>,
     pub widgets: OnceCell<WindowWidgets>,
-    project: RefCell<Project>,
+    pub project: RefCell<Project>,
 }
 
 #[glib::object_subclass]
@@ -204,6 +227,17 @@ impl ObjectImpl for Window {
             .name("main-window-paned")
             .build();
         paned.pack2(&notebook, true, false);
+        notebook.connect_switch_page(clone!(@weak obj => move |_, page, _| {
+            let widgets = match obj.imp().widgets.get() {
+                Some(widgets) => widgets,
+                None => return,
+            };
+            if let Some(edit_view) = page.downcast_ref::<crate::views::GlyphEditView>() {
+                edit_view.show_lib();
+            } else {
+                widgets.sidebar.show_project_lib(&obj.imp().project.borrow());
+            }
+        }));
 
         let vbox = gtk::Box::builder()
             .orientation(gtk::Orientation::Vertical)
@@ -242,10 +276,12 @@ impl ObjectImpl for Window {
 
         obj.connect_local("open-project", false, clone!(@weak obj => @default-return Some(false.to_value()), move |v: &[gtk::glib::Value]| {
             //println!("open-project received!");
-            match v[1].get::<String>().map_err(|err| err.into()).and_then(|path| Project::from_path(&path)) {
+            let path = v[1].get::<String>().unwrap();
+            match Project::from_path(&path) {
                 Ok(project) => {
-                    obj.imp().load_project(project);
+                    obj.imp().load_project(project.clone());
                     obj.queue_draw();
+                    load_report_dialog(obj.upcast_ref(), &project);
                 }
                 Err(err) => {
                     let dialog = gtk::MessageDialog::new(
@@ -253,7 +289,7 @@ impl ObjectImpl for Window {
                         gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
                         gtk::MessageType::Error,
                         gtk::ButtonsType::Close,
-                        &err.to_string());
+                        &crate::utils::xml_escape(&err.to_string()));
                     dialog.set_title("Error: Could not open project");
                     dialog.set_use_markup(true);
                     dialog.run();
@@ -438,6 +474,20 @@ impl Window {
         }));
     }
 
+    pub fn edit_kerning(&self) {
+        let widgets = self.widgets.get().unwrap();
+        let kerning_view = crate::views::KerningEditView::new(
+            self.app.get().unwrap().clone(),
+            self.project.borrow().clone(),
+        );
+        add_tab(
+            &widgets.notebook,
+            kerning_view.upcast_ref::<gtk::Widget>(),
+            true,
+            true,
+        );
+    }
+
     pub fn unload_project(&self) {
         let widgets = self.widgets.get().unwrap();
         widgets.headerbar.set_subtitle(None);
@@ -0,0 +1,125 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::project::Project;
+use crate::utils::xml_escape;
+
+/// List the problems found while loading `project`, if there were any.
+pub fn load_report_dialog(window: &gtk::Window, project: &Project) {
+    let report = project.imp().load_report.borrow();
+    if report.is_empty() {
+        return;
+    }
+    let ufo_path = project.imp().path.borrow().clone();
+    let dialog = gtk::Dialog::with_buttons(
+        Some("Load report"),
+        Some(window),
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        &[("Close", gtk::ResponseType::Close)],
+    );
+    dialog.set_default_size(640, 360);
+
+    let skipped = report.skipped_glyphs();
+    let mut summary = format!(
+        "{} problem{} found while loading the project.",
+        report.warnings.len(),
+        if report.warnings.len() == 1 {
+            " was"
+        } else {
+            "s were"
+        }
+    );
+    if !skipped.is_empty() {
+        summary.push_str(&format!(
+            "\n<b>Glyphs that could not be loaded:</b> {}",
+            xml_escape(&skipped.join(", "))
+        ));
+    }
+    let label = gtk::Label::builder()
+        .label(&summary)
+        .use_markup(true)
+        .wrap(true)
+        .xalign(0.)
+        .margin(10)
+        .visible(true)
+        .build();
+
+    let store = gtk::ListStore::new(&[
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+    ]);
+    for warning in report.warnings.iter() {
+        let error = &warning.error;
+        let file = ufo_path
+            .as_ref()
+            .and_then(|p| error.path.strip_prefix(p).ok())
+            .unwrap_or(&error.path)
+            .display()
+            .to_string();
+        let location = error
+            .location()
+            .map(|(line, column)| format!("{}:{}", line, column))
+            .unwrap_or_default();
+        let mut message = error.message();
+        if warning.skipped {
+            message.push_str(" The glyph was not loaded.");
+        }
+        store.set(
+            &store.append(),
+            &[
+                (0, &warning.glyph.clone().unwrap_or_default()),
+                (1, &file),
+                (2, &location),
+                (3, &message),
+            ],
+        );
+    }
+    let view = gtk::TreeView::builder()
+        .model(&store)
+        .expand(true)
+        .visible(true)
+        .build();
+    for (i, title) in ["Glyph", "File", "Line", "Problem"].into_iter().enumerate() {
+        let renderer = gtk::CellRendererText::new();
+        let column = gtk::TreeViewColumn::with_attributes(title, &renderer, &[("text", i as i32)]);
+        column.set_resizable(true);
+        column.set_sort_column_id(i as i32);
+        view.append_column(&column);
+    }
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .expand(true)
+        .visible(true)
+        .margin(10)
+        .child(&view)
+        .build();
+
+    dialog.content_area().add(&label);
+    dialog
+        .content_area()
+        .pack_start(&scrolled_window, true, true, 0);
+    dialog.run();
+    dialog.hide();
+}
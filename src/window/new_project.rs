@@ -0,0 +1,173 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gtk::prelude::*;
+use std::path::PathBuf;
+
+use crate::project::ProjectTemplate;
+use crate::unicode::blocks::UNICODE_BLOCKS;
+
+/// Ask the user for the settings of a new project and where to save it.
+///
+/// Returns `None` if the dialog was cancelled.
+pub fn new_project_dialog(window: &gtk::Window) -> Option<(ProjectTemplate, PathBuf)> {
+    let defaults = ProjectTemplate::default();
+    let dialog = gtk::Dialog::with_buttons(
+        Some("New project"),
+        Some(window),
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        &[
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("Create", gtk::ResponseType::Accept),
+        ],
+    );
+    dialog.set_default_response(gtk::ResponseType::Accept);
+    let grid = gtk::Grid::builder()
+        .expand(true)
+        .visible(true)
+        .column_spacing(5)
+        .row_spacing(5)
+        .margin(10)
+        .build();
+    let mut row = 0;
+    let mut attach = |label: &str, widget: &gtk::Widget| {
+        grid.attach(
+            &gtk::Label::builder()
+                .label(label)
+                .halign(gtk::Align::End)
+                .visible(true)
+                .build(),
+            0,
+            row,
+            1,
+            1,
+        );
+        widget.set_visible(true);
+        widget.set_hexpand(true);
+        grid.attach(widget, 1, row, 1, 1);
+        row += 1;
+    };
+
+    let family_name = gtk::Entry::builder()
+        .text(&defaults.family_name)
+        .activates_default(true)
+        .build();
+    attach("Family name", family_name.upcast_ref());
+    let style_name = gtk::Entry::builder()
+        .text(&defaults.style_name)
+        .activates_default(true)
+        .build();
+    attach("Style name", style_name.upcast_ref());
+    let metric = |label: &str, value: f64, min: f64| {
+        let spin = gtk::SpinButton::with_range(min, 16384., 1.);
+        spin.set_digits(0);
+        spin.set_value(value);
+        (label.to_string(), spin)
+    };
+    let metrics = [
+        metric("Units per em", defaults.units_per_em, 16.),
+        metric("Ascender", defaults.ascender, -16384.),
+        metric("Descender", defaults.descender, -16384.),
+        metric("x-height", defaults.x_height, -16384.),
+        metric("Cap height", defaults.cap_height, -16384.),
+    ];
+    for (label, spin) in &metrics {
+        attach(label, spin.upcast_ref());
+    }
+
+    let charset = gtk::ComboBoxText::new();
+    charset.append(Some("none"), "None");
+    for (i, (_, name, _, _, _)) in UNICODE_BLOCKS.iter().enumerate() {
+        charset.append(Some(&i.to_string()), name);
+    }
+    charset.set_active_id(Some("0"));
+    attach("Character set", charset.upcast_ref());
+
+    let location = gtk::FileChooserButton::new(
+        "Select parent directory...",
+        gtk::FileChooserAction::SelectFolder,
+    );
+    if let Some(home) = std::env::var_os("HOME") {
+        location.set_current_folder(home);
+    }
+    attach("Location", location.upcast_ref());
+
+    dialog.content_area().add(&grid);
+    dialog.show_all();
+    let ret = loop {
+        if dialog.run() != gtk::ResponseType::Accept {
+            break None;
+        }
+        let family_name = family_name.text().trim().to_string();
+        if family_name.is_empty() {
+            input_error(&dialog, "Family name cannot be empty.");
+            continue;
+        }
+        let style_name = style_name.text().trim().to_string();
+        let parent = match location.filename() {
+            Some(p) => p,
+            None => {
+                input_error(&dialog, "Select a location for the project.");
+                continue;
+            }
+        };
+        let dir_name = if style_name.is_empty() {
+            format!("{}.ufo", family_name)
+        } else {
+            format!("{}-{}.ufo", family_name, style_name)
+        };
+        let path = parent.join(dir_name.replace('/', "_"));
+        if path.exists() {
+            input_error(&dialog, &format!("{} already exists.", path.display()));
+            continue;
+        }
+        let value = |i: usize| metrics[i].1.value();
+        let template = ProjectTemplate {
+            family_name,
+            style_name,
+            units_per_em: value(0),
+            ascender: value(1),
+            descender: value(2),
+            x_height: value(3),
+            cap_height: value(4),
+            charset: charset
+                .active_id()
+                .and_then(|id| id.parse::<usize>().ok())
+                .map(ProjectTemplate::block_charset)
+                .unwrap_or_default(),
+        };
+        break Some((template, path));
+    };
+    dialog.hide();
+    ret
+}
+
+fn input_error(dialog: &gtk::Dialog, msg: &str) {
+    let error = gtk::MessageDialog::new(
+        Some(dialog),
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        gtk::MessageType::Error,
+        gtk::ButtonsType::Close,
+        msg,
+    );
+    error.run();
+    error.hide();
+}
@@ -0,0 +1,127 @@
+/*
+ * gerb
+ *
+ * Copyright 2022 - Manos Pitsidianakis
+ *
+ * This file is part of gerb.
+ *
+ * gerb is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * gerb is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with gerb. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gtk::prelude::*;
+
+use crate::export::{ProofOptions, A4, LETTER};
+
+/// Ask the user what to include in a PDF proof.
+///
+/// Returns `None` if the dialog was cancelled.
+pub fn proof_dialog(window: &gtk::Window) -> Option<ProofOptions> {
+    let defaults = ProofOptions::default();
+    let dialog = gtk::Dialog::with_buttons(
+        Some("Print proof"),
+        Some(window),
+        gtk::DialogFlags::DESTROY_WITH_PARENT | gtk::DialogFlags::MODAL,
+        &[
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("Save PDF...", gtk::ResponseType::Accept),
+        ],
+    );
+    dialog.set_default_response(gtk::ResponseType::Accept);
+    let grid = gtk::Grid::builder()
+        .expand(true)
+        .visible(true)
+        .column_spacing(5)
+        .row_spacing(5)
+        .margin(10)
+        .build();
+    let mut row = 0;
+    let mut attach = |label: &str, widget: &gtk::Widget| {
+        grid.attach(
+            &gtk::Label::builder()
+                .label(label)
+                .halign(gtk::Align::End)
+                .valign(gtk::Align::Start)
+                .visible(true)
+                .build(),
+            0,
+            row,
+            1,
+            1,
+        );
+        widget.set_visible(true);
+        widget.set_hexpand(true);
+        grid.attach(widget, 1, row, 1, 1);
+        row += 1;
+    };
+
+    let page_size = gtk::ComboBoxText::new();
+    page_size.append(Some("a4"), "A4");
+    page_size.append(Some("letter"), "Letter");
+    page_size.set_active_id(Some("a4"));
+    attach("Page size", page_size.upcast_ref());
+    let show_grid = gtk::CheckButton::with_label("Character set");
+    show_grid.set_active(defaults.grid_size.is_some());
+    attach("Include", show_grid.upcast_ref());
+    let waterfall_text = gtk::Entry::builder()
+        .text(&defaults.waterfall_text)
+        .activates_default(true)
+        .build();
+    attach("Waterfall text", waterfall_text.upcast_ref());
+    let text_size = gtk::SpinButton::with_range(4., 144., 1.);
+    text_size.set_value(defaults.text_size);
+    attach("Text size", text_size.upcast_ref());
+    let texts = gtk::TextView::builder()
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .visible(true)
+        .build();
+    texts.buffer().unwrap().set_text(
+        "The quick brown fox jumps over the lazy dog.\nSPHINX OF BLACK QUARTZ, JUDGE MY VOW.",
+    );
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .min_content_height(120)
+        .min_content_width(300)
+        .child(&texts)
+        .build();
+    attach("Text", scrolled_window.upcast_ref());
+
+    dialog.content_area().add(&grid);
+    dialog.show_all();
+    let ret = if dialog.run() == gtk::ResponseType::Accept {
+        let buffer = texts.buffer().unwrap();
+        let text = buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .map(|t| t.to_string())
+            .unwrap_or_default();
+        Some(ProofOptions {
+            page_size: if page_size.active_id().as_deref() == Some("letter") {
+                LETTER
+            } else {
+                A4
+            },
+            grid_size: defaults.grid_size.filter(|_| show_grid.is_active()),
+            waterfall_text: waterfall_text.text().to_string(),
+            texts: if text.trim().is_empty() {
+                vec![]
+            } else {
+                vec![text]
+            },
+            text_size: text_size.value(),
+            ..defaults
+        })
+    } else {
+        None
+    };
+    dialog.hide();
+    ret
+}
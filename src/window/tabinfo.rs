@@ -20,18 +20,55 @@
  */
 
 use gtk::glib;
+use gtk::glib::clone;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use once_cell::unsync::OnceCell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::utils::plist::{base64_decode, base64_encode, DictValue};
+
+/* Columns of the lib tree store. */
+const LIB_KEY: u32 = 0;
+const LIB_TYPE: u32 = 1;
+const LIB_VALUE: u32 = 2;
+const LIB_KEY_EDITABLE: u32 = 3;
+const LIB_VALUE_EDITABLE: u32 = 4;
+
+const LIB_TYPES: &[&str] = &[
+    "String",
+    "Integer",
+    "Real",
+    "Boolean",
+    "Date",
+    "Data",
+    "Array",
+    "Dictionary",
+];
+
+/// Called with the whole edited lib dictionary after every change in the lib tree.
+struct LibCallback(Box<dyn Fn(HashMap<String, DictValue>)>);
+
+impl std::fmt::Debug for LibCallback {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_tuple("LibCallback").finish()
+    }
+}
 
 #[derive(Debug)]
 struct TabInfoWidgets {
     grid: gtk::Grid,
+    properties: gtk::ScrolledWindow,
+    lib_expander: gtk::Expander,
+    lib_store: gtk::TreeStore,
+    lib_view: gtk::TreeView,
 }
 
 #[derive(Debug, Default)]
 pub struct TabInfoInner {
     widgets: OnceCell<TabInfoWidgets>,
+    lib_callback: RefCell<Option<LibCallback>>,
 }
 
 #[glib::object_subclass]
@@ -69,16 +106,213 @@ impl ObjectImpl for TabInfoInner {
             .build();
         flow_box.add(&edit);
         */
+        obj.upcast_ref::<gtk::Box>()
+            .set_orientation(gtk::Orientation::Vertical);
         obj.pack_start(&scrolled_window, true, true, 0);
+
+        let lib_store = gtk::TreeStore::new(&[
+            String::static_type(),
+            String::static_type(),
+            String::static_type(),
+            bool::static_type(),
+            bool::static_type(),
+        ]);
+        let lib_view = gtk::TreeView::builder()
+            .model(&lib_store)
+            .expand(true)
+            .visible(true)
+            .can_focus(true)
+            .build();
+
+        let key_renderer = gtk::CellRendererText::new();
+        key_renderer.connect_edited(
+            clone!(@weak obj, @weak lib_store => move |_, path, new_key| {
+                let iter = match lib_store.iter(&path) {
+                    Some(iter) => iter,
+                    None => return,
+                };
+                if new_key.is_empty() || lib_key_exists(&lib_store, lib_store.iter_parent(&iter).as_ref(), new_key) {
+                    return;
+                }
+                lib_store.set_value(&iter, LIB_KEY, &new_key.to_value());
+                obj.imp().lib_changed();
+            }),
+        );
+        let key_column = gtk::TreeViewColumn::with_attributes(
+            "Key",
+            &key_renderer,
+            &[
+                ("text", LIB_KEY as i32),
+                ("editable", LIB_KEY_EDITABLE as i32),
+            ],
+        );
+        key_column.set_resizable(true);
+        key_column.set_expand(true);
+        lib_view.append_column(&key_column);
+
+        let types = gtk::ListStore::new(&[String::static_type()]);
+        for t in LIB_TYPES {
+            types.set(&types.append(), &[(0, t)]);
+        }
+        let type_renderer = gtk::CellRendererCombo::builder()
+            .model(&types)
+            .text_column(0)
+            .has_entry(false)
+            .editable(true)
+            .build();
+        type_renderer.connect_edited(
+            clone!(@weak obj, @weak lib_store => move |_, path, new_type| {
+                let iter = match lib_store.iter(&path) {
+                    Some(iter) => iter,
+                    None => return,
+                };
+                if lib_type(&lib_store, &iter) == new_type {
+                    return;
+                }
+                while let Some(child) = lib_store.iter_children(Some(&iter)) {
+                    lib_store.remove(&child);
+                }
+                let value = lib_value_default(new_type);
+                lib_store.set(
+                    &iter,
+                    &[
+                        (LIB_TYPE, &new_type),
+                        (LIB_VALUE, &lib_value_text(&value)),
+                        (LIB_VALUE_EDITABLE, &lib_value_is_scalar(&value)),
+                    ],
+                );
+                obj.imp().lib_changed();
+            }),
+        );
+        lib_view.append_column(&gtk::TreeViewColumn::with_attributes(
+            "Type",
+            &type_renderer,
+            &[("text", LIB_TYPE as i32)],
+        ));
+
+        let value_renderer = gtk::CellRendererText::new();
+        value_renderer.connect_edited(
+            clone!(@weak obj, @weak lib_store => move |_, path, new_value| {
+                let iter = match lib_store.iter(&path) {
+                    Some(iter) => iter,
+                    None => return,
+                };
+                let kind = lib_type(&lib_store, &iter);
+                if let Some(value) = lib_value_parse(&kind, new_value) {
+                    lib_store.set_value(&iter, LIB_VALUE, &lib_value_text(&value).to_value());
+                    obj.imp().lib_changed();
+                }
+            }),
+        );
+        let value_column = gtk::TreeViewColumn::with_attributes(
+            "Value",
+            &value_renderer,
+            &[
+                ("text", LIB_VALUE as i32),
+                ("editable", LIB_VALUE_EDITABLE as i32),
+            ],
+        );
+        value_column.set_resizable(true);
+        value_column.set_expand(true);
+        lib_view.append_column(&value_column);
+
+        let lib_scrolled_window = gtk::ScrolledWindow::builder()
+            .expand(true)
+            .visible(true)
+            .can_focus(true)
+            .min_content_height(150)
+            .build();
+        lib_scrolled_window.set_child(Some(&lib_view));
+
+        let add_button = gtk::Button::builder()
+            .label("Add")
+            .tooltip_text(
+                "Add a value to the selected array or dictionary, or next to the selected value.",
+            )
+            .visible(true)
+            .build();
+        add_button.connect_clicked(
+            clone!(@weak obj, @weak lib_store, @weak lib_view => move |_| {
+                let parent = match lib_view.selection().selected() {
+                    Some((_, iter)) if lib_is_container(&lib_store, &iter) => Some(iter),
+                    Some((_, iter)) => lib_store.iter_parent(&iter),
+                    None => None,
+                };
+                let key = (0..)
+                    .map(|i| if i == 0 { "newKey".to_string() } else { format!("newKey{}", i) })
+                    .find(|k| !lib_key_exists(&lib_store, parent.as_ref(), k))
+                    .unwrap();
+                let iter = lib_insert(&lib_store, parent.as_ref(), &key, &DictValue::String(String::new()));
+                lib_renumber(&lib_store, parent.as_ref());
+                if let Some(ref parent) = parent {
+                    lib_view.expand_row(&lib_store.path(parent).unwrap(), false);
+                }
+                lib_view.selection().select_iter(&iter);
+                obj.imp().lib_changed();
+            }),
+        );
+        let remove_button = gtk::Button::builder().label("Remove").visible(true).build();
+        remove_button.connect_clicked(
+            clone!(@weak obj, @weak lib_store, @weak lib_view => move |_| {
+                if let Some((_, iter)) = lib_view.selection().selected() {
+                    let parent = lib_store.iter_parent(&iter);
+                    lib_store.remove(&iter);
+                    lib_renumber(&lib_store, parent.as_ref());
+                    obj.imp().lib_changed();
+                }
+            }),
+        );
+        let button_box = gtk::ButtonBox::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .layout_style(gtk::ButtonBoxStyle::Start)
+            .spacing(5)
+            .visible(true)
+            .build();
+        button_box.add(&add_button);
+        button_box.add(&remove_button);
+
+        let lib_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(5)
+            .visible(true)
+            .build();
+        lib_box.pack_start(&lib_scrolled_window, true, true, 0);
+        lib_box.pack_start(&button_box, false, false, 0);
+        let lib_expander = gtk::Expander::builder()
+            .expanded(true)
+            .margin_start(5)
+            .visible(false)
+            .build();
+        lib_expander.set_child(Some(&lib_box));
+        obj.pack_start(&lib_expander, true, true, 0);
+
         obj.set_visible(true);
         obj.set_expand(true);
         self.widgets
-            .set(TabInfoWidgets { grid })
+            .set(TabInfoWidgets {
+                grid,
+                properties: scrolled_window,
+                lib_expander,
+                lib_store,
+                lib_view,
+            })
             .expect("Failed to initialize TabInfoInner state");
     }
 }
 
-impl TabInfoInner {}
+impl TabInfoInner {
+    fn lib_changed(&self) {
+        let store = &self.widgets.get().unwrap().lib_store;
+        if let Some(LibCallback(ref callback)) = *self.lib_callback.borrow() {
+            callback(lib_read(store, None).into_iter().collect());
+        }
+    }
+
+    fn update_visibility(&self, obj: &TabInfo) {
+        let widgets = self.widgets.get().unwrap();
+        obj.set_visible(widgets.properties.is_visible() || widgets.lib_expander.is_visible());
+    }
+}
 
 impl WidgetImpl for TabInfoInner {}
 impl ContainerImpl for TabInfoInner {}
@@ -165,6 +399,13 @@ impl TabInfo {
                     .build();
                 entry.upcast()
             }
+            "gboolean" => {
+                let check = gtk::CheckButton::builder().visible(true).build();
+                obj.bind_property(property, &check, "active")
+                    .flags(glib::BindingFlags::BIDIRECTIONAL | glib::BindingFlags::SYNC_CREATE)
+                    .build();
+                check.upcast()
+            }
             _other => gtk::Label::builder()
                 .label(&format!("{:?}", val))
                 .visible(true)
@@ -174,8 +415,9 @@ impl TabInfo {
     }
 
     pub fn set_object(&self, new_obj: Option<glib::Object>) {
+        let properties = self.imp().widgets.get().unwrap().properties.clone();
         if let Some(obj) = new_obj {
-            self.set_visible(true);
+            properties.set_visible(true);
             let grid = self.imp().widgets.get().unwrap().grid.clone();
             let children = grid.children();
             for c in children {
@@ -213,8 +455,219 @@ impl TabInfo {
             }
             grid.queue_draw();
         } else {
-            self.set_visible(false);
+            properties.set_visible(false);
+        }
+        self.imp().update_visibility(self);
+        self.queue_draw();
+    }
+
+    /// Show `lib` as an editable tree under `title`. `on_change` receives the whole dictionary
+    /// after every edit.
+    pub fn set_lib(
+        &self,
+        title: &str,
+        lib: &HashMap<String, DictValue>,
+        on_change: impl Fn(HashMap<String, DictValue>) + 'static,
+    ) {
+        let widgets = self.imp().widgets.get().unwrap();
+        *self.imp().lib_callback.borrow_mut() = Some(LibCallback(Box::new(on_change)));
+        widgets.lib_store.clear();
+        let mut keys = lib.keys().collect::<Vec<&String>>();
+        keys.sort();
+        for key in keys {
+            lib_insert(&widgets.lib_store, None, key, &lib[key]);
         }
+        widgets.lib_expander.set_label(Some(title));
+        widgets.lib_expander.set_visible(true);
+        widgets.lib_view.expand_all();
+        self.imp().update_visibility(self);
+        self.queue_draw();
+    }
+
+    /// Hide the lib tree. Edits are no longer reported.
+    pub fn clear_lib(&self) {
+        let widgets = self.imp().widgets.get().unwrap();
+        *self.imp().lib_callback.borrow_mut() = None;
+        widgets.lib_store.clear();
+        widgets.lib_expander.set_visible(false);
+        self.imp().update_visibility(self);
         self.queue_draw();
     }
 }
+
+fn lib_type_name(value: &DictValue) -> &'static str {
+    match value {
+        DictValue::String(_) => "String",
+        DictValue::Integer(_) => "Integer",
+        DictValue::Real(_) => "Real",
+        DictValue::Boolean(_) => "Boolean",
+        DictValue::Date(_) => "Date",
+        DictValue::Data(_) => "Data",
+        DictValue::Array(_) => "Array",
+        DictValue::Dict(_) => "Dictionary",
+    }
+}
+
+fn lib_value_is_scalar(value: &DictValue) -> bool {
+    !matches!(value, DictValue::Array(_) | DictValue::Dict(_))
+}
+
+fn lib_value_default(kind: &str) -> DictValue {
+    match kind {
+        "Integer" => DictValue::Integer(0),
+        "Real" => DictValue::Real(0.),
+        "Boolean" => DictValue::Boolean(false),
+        "Date" => DictValue::Date(chrono::Utc::now()),
+        "Data" => DictValue::Data(vec![]),
+        "Array" => DictValue::Array(vec![]),
+        "Dictionary" => DictValue::Dict(HashMap::default()),
+        _ => DictValue::String(String::new()),
+    }
+}
+
+/// The text shown in the value column. Containers show their size instead.
+fn lib_value_text(value: &DictValue) -> String {
+    match value {
+        DictValue::String(v) => v.clone(),
+        DictValue::Integer(v) => v.to_string(),
+        DictValue::Real(v) => v.to_string(),
+        DictValue::Boolean(v) => v.to_string(),
+        DictValue::Date(v) => v.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        DictValue::Data(v) => base64_encode(v),
+        DictValue::Array(v) => format!("{} items", v.len()),
+        DictValue::Dict(v) => format!("{} keys", v.len()),
+    }
+}
+
+/// Parse the value column text of a scalar of type `kind`.
+fn lib_value_parse(kind: &str, text: &str) -> Option<DictValue> {
+    Some(match kind {
+        "String" => DictValue::String(text.to_string()),
+        "Integer" => DictValue::Integer(text.trim().parse().ok()?),
+        "Real" => DictValue::Real(text.trim().parse().ok()?),
+        "Boolean" => DictValue::Boolean(text.trim().parse().ok()?),
+        "Date" => DictValue::Date(
+            chrono::DateTime::parse_from_rfc3339(text.trim())
+                .ok()?
+                .with_timezone(&chrono::Utc),
+        ),
+        "Data" => DictValue::Data(base64_decode(text)?),
+        _ => return None,
+    })
+}
+
+fn lib_insert(
+    store: &gtk::TreeStore,
+    parent: Option<&gtk::TreeIter>,
+    key: &str,
+    value: &DictValue,
+) -> gtk::TreeIter {
+    let in_array = matches!(parent, Some(p) if lib_type(store, p) == "Array");
+    let iter = store.append(parent);
+    store.set(
+        &iter,
+        &[
+            (LIB_KEY, &key),
+            (LIB_TYPE, &lib_type_name(value)),
+            (LIB_VALUE, &lib_value_text(value)),
+            (LIB_KEY_EDITABLE, &!in_array),
+            (LIB_VALUE_EDITABLE, &lib_value_is_scalar(value)),
+        ],
+    );
+    match value {
+        DictValue::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                lib_insert(store, Some(&iter), &i.to_string(), item);
+            }
+        }
+        DictValue::Dict(dict) => {
+            let mut keys = dict.keys().collect::<Vec<&String>>();
+            keys.sort();
+            for key in keys {
+                lib_insert(store, Some(&iter), key, &dict[key]);
+            }
+        }
+        _ => {}
+    }
+    iter
+}
+
+fn lib_children(store: &gtk::TreeStore, parent: Option<&gtk::TreeIter>) -> Vec<gtk::TreeIter> {
+    let mut ret = vec![];
+    if let Some(iter) = store.iter_children(parent) {
+        loop {
+            ret.push(iter);
+            if !store.iter_next(&iter) {
+                break;
+            }
+        }
+    }
+    ret
+}
+
+fn lib_type(store: &gtk::TreeStore, iter: &gtk::TreeIter) -> String {
+    store.value(iter, LIB_TYPE as i32).get::<String>().unwrap()
+}
+
+fn lib_is_container(store: &gtk::TreeStore, iter: &gtk::TreeIter) -> bool {
+    matches!(lib_type(store, iter).as_str(), "Array" | "Dictionary")
+}
+
+fn lib_key_exists(store: &gtk::TreeStore, parent: Option<&gtk::TreeIter>, key: &str) -> bool {
+    lib_children(store, parent)
+        .iter()
+        .any(|iter| store.value(iter, LIB_KEY as i32).get::<String>().unwrap() == key)
+}
+
+/// Update the counts shown for `parent` and, if it is an array, the indices of its items.
+fn lib_renumber(store: &gtk::TreeStore, parent: Option<&gtk::TreeIter>) {
+    let parent = match parent {
+        Some(parent) => parent,
+        None => return,
+    };
+    let children = lib_children(store, Some(parent));
+    if lib_type(store, parent) == "Array" {
+        for (i, iter) in children.iter().enumerate() {
+            store.set_value(iter, LIB_KEY, &i.to_string().to_value());
+        }
+        store.set_value(
+            parent,
+            LIB_VALUE,
+            &format!("{} items", children.len()).to_value(),
+        );
+    } else {
+        store.set_value(
+            parent,
+            LIB_VALUE,
+            &format!("{} keys", children.len()).to_value(),
+        );
+    }
+}
+
+/// Read the children of `parent` back into keys and values.
+fn lib_read(store: &gtk::TreeStore, parent: Option<&gtk::TreeIter>) -> Vec<(String, DictValue)> {
+    lib_children(store, parent)
+        .into_iter()
+        .map(|iter| {
+            let key = store.value(&iter, LIB_KEY as i32).get::<String>().unwrap();
+            let kind = lib_type(store, &iter);
+            let value = match kind.as_str() {
+                "Array" => DictValue::Array(
+                    lib_read(store, Some(&iter))
+                        .into_iter()
+                        .map(|(_, v)| v)
+                        .collect(),
+                ),
+                "Dictionary" => DictValue::Dict(lib_read(store, Some(&iter)).into_iter().collect()),
+                _ => {
+                    let text = store
+                        .value(&iter, LIB_VALUE as i32)
+                        .get::<String>()
+                        .unwrap();
+                    lib_value_parse(&kind, &text).unwrap_or_else(|| lib_value_default(&kind))
+                }
+            };
+            (key, value)
+        })
+        .collect()
+}